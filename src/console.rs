@@ -1,72 +1,114 @@
 //! Game master console
 
+mod auth;
+mod editor;
+mod http;
+mod reply;
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
 use std::sync::Arc;
 use std::time::Duration;
 
 use tokio::io;
 use tokio::net;
-use tokio::sync::{RwLock, watch};
-use tokio_util::codec;
+use tokio::sync::broadcast;
 
 use crate::error;
 use crate::game;
-use crate::player;
+use crate::room;
 
 use error::WrappedErr;
+pub use auth::Tokens;
+
+use auth::Role;
+use editor::Input;
+use reply::{Code, CommandError, Format, Value};
 
 
 /// Implementation of the game master logic
 ///
-/// This function starts the game if a SIGUSR1 is received. If a `listener` is
-/// passed, the function will accept connections from the associated socket and
-/// serve game master consoles over them.
+/// This function starts the games in all rooms which are in the lobby phase if
+/// a SIGUSR1 is received. On SIGTERM, all games are ended and the function
+/// returns once all rooms are gone. If a `listener` is passed, the function
+/// will accept connections from the associated socket and serve game master
+/// consoles over them. If a `remote` listener is passed, consoles are also
+/// served over TCP connections accepted from it. Game masters connecting
+/// remotely need to authenticate using one of the given tokens.
 ///
-pub async fn game_master(
-    control: watch::Sender<game::LobbyControl>,
-    settings: Settings,
-    mut phase: watch::Receiver<game::GamePhase<impl rand::Rng + Send + Sync + 'static>>,
-    roster: Arc<RwLock<player::Roster>>,
+pub async fn game_master<R>(
+    registry: Arc<room::Registry<R>>,
     mut listener: Option<net::UnixListener>,
-) -> Result<(), WrappedErr> {
+    mut remote: Option<(net::TcpListener, Arc<Tokens>)>,
+) -> Result<(), WrappedErr>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
     use tokio::signal::unix;
 
     use error::{TryExt, WrappedErr as E};
 
-    let central = Arc::new(RwLock::new(Central {control: control.into(), settings}));
+    let mut updates = registry.subscribe();
     let mut sigusr1 = unix::signal(unix::SignalKind::user_defined1())
         .map_err(|e| E::new("Could not create SIGUSR1 listener", e))?;
     let mut sigterm = unix::signal(unix::SignalKind::terminate())
         .map_err(|e| E::new("Could not create SIGTERM listener", e))?;
 
-    loop {
+    while !registry.is_shut_down().await {
         tokio::select!{
             c = accept(listener.as_mut()) => if let Some(conn) = c.or_warn("Could not accept GM conn") {
                 log::info!("Spawning new GM console");
                 let (reader, writer) = conn.into_split();
-                let central = central.clone();
-                let phase = phase.clone();
-                let roster = roster.clone();
-                tokio::spawn(async move { serve(reader, writer, central, phase, roster).await });
+                let registry = registry.clone();
+                tokio::spawn(async move { serve(reader, writer, registry, Session::new(None)).await });
+            },
+            c = accept_remote(remote.as_mut()) => if let Some((conn, tokens)) = c.or_warn("Could not accept GM conn") {
+                log::info!("Spawning new remote GM console for {:?}", conn.peer_addr().ok());
+                let (reader, writer) = conn.into_split();
+                let registry = registry.clone();
+                tokio::spawn(async move { serve(reader, writer, registry, Session::new(Some(tokens))).await });
             },
-            r = phase.changed() => r.map_err(|e| E::new("Phase channel closed", e))?,
+            r = updates.changed() => r.map_err(|e| E::new("Room updates channel closed", e))?,
             s = sigusr1.recv() => if s.is_some() {
-                log::info!("Starting game");
-                let mut central = central.write().await;
-                let msg = central.settings.as_game_control();
-                central.control.send_regular(msg).await.or_err("Could not start game");
+                for room in registry.rooms().await.into_iter().filter(|r| r.in_lobby()) {
+                    log::info!("Starting game in room {}", room.id());
+                    room.start().await.or_err("Could not start game");
+                }
             },
             s = sigterm.recv() => if s.is_some() {
-                log::info!("Ending game");
-                central
-                    .write()
-                    .await
-                    .control
-                    .send_regular(game::GameControl::EndOfGame)
-                    .await
-                    .or_err("Could not end game");
+                log::info!("Ending all games");
+                registry.shutdown().await;
             },
         }
     }
+
+    Ok(())
+}
+
+
+/// Serve the HTTP admin API
+///
+/// This function accepts connections via the given `listener` and serves a
+/// single request over each of them. It only returns if an error occurs.
+///
+pub async fn http_admin<R>(
+    listener: net::TcpListener,
+    registry: Arc<room::Registry<R>>,
+) -> Result<(), WrappedErr>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    use error::TryExt;
+
+    loop {
+        if let Some((stream, peer)) = listener.accept().await.or_warn("Could not accept admin conn") {
+            log::debug!("Serving admin request from {}", peer);
+            let registry = registry.clone();
+            tokio::spawn(async move {
+                http::serve_connection(stream, registry).await.or_warn("Could not serve admin request")
+            });
+        }
+    }
 }
 
 
@@ -83,39 +125,205 @@ async fn accept(
 }
 
 
+/// Accept a connection from a remote game master
+///
+async fn accept_remote(
+    listener: Option<&mut (net::TcpListener, Arc<Tokens>)>
+) -> io::Result<(net::TcpStream, Arc<Tokens>)> {
+    if let Some((listener, tokens)) = listener {
+        listener.accept().await.map(|(s, _)| (s, tokens.clone()))
+    } else {
+        futures::future::pending().await
+    }
+}
+
+
 /// Serve a game master console via the given reader and writer
 ///
-async fn serve(
+/// Replies are rendered in the connection's current `Format`, which may be
+/// switched via the `format` command. Events of subscribed rooms are sent as
+/// they occur, in between replies. Line editing and completion are available
+/// after switching to interactive mode via the `interactive` command. The
+/// connection is closed after too many failed authentication attempts.
+///
+async fn serve<R>(
     reader: impl io::AsyncRead + Unpin,
     writer: impl io::AsyncWrite + Unpin,
-    central: Arc<RwLock<Central>>,
-    mut phase: watch::Receiver<game::GamePhase<impl rand::Rng>>,
-    roster: Arc<RwLock<player::Roster>>,
-) {
-    use futures::SinkExt;
-    use io::AsyncBufReadExt;
-
+    registry: Arc<room::Registry<R>>,
+    mut session: Session,
+)
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
     use error::TryExt;
 
-    let mut commands = io::BufReader::new(reader).lines();
-    let mut out = codec::FramedWrite::new(writer, codec::LinesCodec::new());
+    let mut editor = editor::LineEditor::new(reader, writer);
 
-    while !phase.borrow().is_end_of_game() {
-        tokio::select!{
-            line = commands.next_line() => if let Some(line) = line.or_err("Could not get line").flatten() {
-                if match process_line(line.as_ref(), &mut out, &central, &phase, &roster).await {
-                    Ok(()) => out.send("OK").await.or_err("Could not send msg to GM"),
-                    Err(e) => {
-                        let msg = e.to_string();
-                        out.send(msg).await.or_err("Could not report error")
+    loop {
+        let input = if let Some(input) = editor.next_input() {
+            input
+        } else {
+            editor.flush().await.or_err("Could not send echo to GM");
+            tokio::select! {
+                r = editor.read() => match r.or_err("Could not read from GM") {
+                    Some(true) => continue,
+                    Some(false) => if let Some(line) = editor.finish() {
+                        Input::Line(line)
+                    } else {
+                        break
                     },
-                }.is_none() {
-                    break
-                }
+                    None => break,
+                },
+                event = session.next_event() => {
+                    let line = session.format.render_event(&event);
+                    if editor.write_lines(&[line]).await.or_err("Could not send event to GM").is_none() {
+                        break
+                    }
+                    continue
+                },
+            }
+        };
+
+        let lines = match input {
+            Input::Line(line) => {
+                let reply = process_line(line.as_ref(), &registry, &mut session).await;
+                editor.set_interactive(session.interactive);
+                session.format.render(&reply)
+            },
+            Input::Complete(text) => {
+                let (word, candidates) = completions(text.as_ref(), &registry, &session).await;
+                editor.complete(word, &candidates);
+                continue
             },
-            r = phase.changed() => if r.or_warn("Phase channel closed").is_none() {
+            Input::End => break,
+            Input::TooLong => {
+                log::warn!("Closing GM console after overly long line");
                 break
             },
+        };
+        if editor.write_lines(&lines).await.or_err("Could not send reply to GM").is_none() {
+            break
+        }
+        if session.failed_auths >= MAX_AUTH_ATTEMPTS {
+            log::warn!("Closing GM console after {} failed authentication attempts", session.failed_auths);
+            break
+        }
+    }
+}
+
+
+/// State of a single game master console
+///
+struct Session {
+    /// Room selected via `use`
+    current: Option<u32>,
+    /// Format of replies and events
+    format: Format,
+    /// Whether line editing is enabled
+    interactive: bool,
+    /// Rooms subscribed to via `subscribe`
+    subscriptions: Vec<(u32, broadcast::Receiver<game::Report>)>,
+    /// Tokens accepted via `auth`, if authentication is required
+    tokens: Option<Arc<Tokens>>,
+    /// Role of the game master, if authenticated
+    role: Option<Role>,
+    /// Number of failed authentication attempts
+    failed_auths: u8,
+}
+
+impl Session {
+    /// Create a new session
+    ///
+    /// If `tokens` are given, the game master needs to authenticate before
+    /// issuing most commands. Otherwise, the session permits all commands.
+    ///
+    fn new(tokens: Option<Arc<Tokens>>) -> Self {
+        let role = if tokens.is_some() { None } else { Some(Role::Full) };
+        Self {
+            current: None,
+            format: Default::default(),
+            interactive: false,
+            subscriptions: Default::default(),
+            tokens,
+            role,
+            failed_auths: 0,
+        }
+    }
+
+    /// Authenticate using the given token
+    ///
+    fn authenticate(&mut self, token: &str) -> Result<Role, CommandError> {
+        use error::{NoneError as N, WrappedErr as E};
+
+        let role = match &self.tokens {
+            Some(tokens) => tokens.role(token),
+            None => return Ok(Role::Full),
+        };
+        if let Some(role) = role {
+            self.role = Some(role);
+            Ok(role)
+        } else {
+            self.failed_auths = self.failed_auths.saturating_add(1);
+            Err(CommandError::new(Code::Forbidden, E::new("Invalid token", N)))
+        }
+    }
+
+    /// Check whether the session permits the command with the given word
+    ///
+    fn check_permission(&self, command: &str) -> Result<(), CommandError> {
+        use error::{NoneError as N, WrappedErr as E};
+
+        match self.role {
+            Some(role) if role.permits(command) => Ok(()),
+            Some(_) => Err(CommandError::new(Code::Forbidden, E::new("Permission denied", N))),
+            None if UNAUTHENTICATED_COMMANDS.contains(&command) => Ok(()),
+            None => Err(CommandError::new(Code::Forbidden, E::new("Not authenticated, use 'auth <token>'", N))),
+        }
+    }
+
+    /// Subscribe to the reports of the given room
+    ///
+    /// Subscribing to a room more than once has no effect.
+    ///
+    fn subscribe(&mut self, room: &room::Room<impl rand::Rng>) {
+        if !self.subscriptions.iter().any(|(id, _)| *id == room.id()) {
+            self.subscriptions.push((room.id(), room.subscribe()))
+        }
+    }
+
+    /// Cancel the subscription to the room with the given id
+    ///
+    fn unsubscribe(&mut self, room: u32) {
+        self.subscriptions.retain(|(id, _)| *id != room)
+    }
+
+    /// Wait for the next event in any of the subscribed rooms
+    ///
+    /// Subscriptions to rooms which ceased to exist are dropped. If there are
+    /// no subscriptions, this function never returns.
+    ///
+    async fn next_event(&mut self) -> Value {
+        use futures::future::{pending, select_all};
+
+        use broadcast::error::RecvError;
+
+        loop {
+            if self.subscriptions.is_empty() {
+                pending::<()>().await
+            }
+
+            let (res, index, _) = select_all(self.subscriptions.iter_mut().map(|(_, r)| Box::pin(r.recv()))).await;
+            let room = self.subscriptions[index].0;
+            match res {
+                Ok(report) => return report_value(room, report),
+                Err(RecvError::Lagged(missed)) => return Value::Record(vec![
+                    ("event", "lagged".into()),
+                    ("room", u64::from(room).into()),
+                    ("missed", missed.into()),
+                ]),
+                Err(RecvError::Closed) => {
+                    self.subscriptions.remove(index);
+                },
+            }
         }
     }
 }
@@ -123,19 +331,125 @@ async fn serve(
 
 /// Process a single command line
 ///
-async fn process_line(
+/// Commands not addressing a specific room or the set of rooms as a whole are
+/// applied to the `current` room. If no room was selected explicitly, the first
+/// room is used.
+///
+async fn process_line<R>(
     command: &str,
-    out: &mut codec::FramedWrite<impl io::AsyncWrite + Unpin, codec::LinesCodec>,
-    central: &Arc<RwLock<Central>>,
-    phase: &watch::Receiver<game::GamePhase<impl rand::Rng>>,
-    roster: &Arc<RwLock<player::Roster>>,
-) -> Result<(), WrappedErr> {
-    use std::ops::Deref;
+    registry: &Arc<room::Registry<R>>,
+    session: &mut Session,
+) -> Result<Value, CommandError>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    use error::{NoneError as N, WrappedErr as E};
 
-    use futures::{SinkExt, stream::iter};
+    fn parse_id<'a>(words: &mut impl Iterator<Item = &'a str>) -> Result<u32, CommandError> {
+        words.next().and_then(|s| s.parse().ok()).ok_or_else(|| invalid("Expected room number"))
+    }
+
+    let no_such_room = || CommandError::new(Code::NoSuchRoom, E::new("No such room", N));
+
+    let mut words = command.split_whitespace();
+    let command_word = match words.clone().next() {
+        Some("room") => words.clone().nth(2),
+        word => word,
+    };
+    if let Some(word) = command_word {
+        session.check_permission(word)?;
+    }
+
+    match words.clone().next() {
+        Some("rooms") => {
+            let mut entries = Vec::new();
+            for r in registry.rooms().await {
+                let phase = r.phase().borrow().to_string();
+                entries.push(Value::Record(vec![
+                    ("id", u64::from(r.id()).into()),
+                    ("name", r.name().into()),
+                    ("phase", phase.into()),
+                    ("players", (r.player_count().await as u64).into()),
+                ]));
+            }
+            Ok(Value::List(entries))
+        },
+        Some("room") => {
+            words.next();
+            let room = registry.get(parse_id(&mut words)?).await.ok_or_else(no_such_room)?;
+            process_room_command(words, &room, session).await
+        },
+        Some("use") => {
+            words.next();
+            let id = parse_id(&mut words)?;
+            registry.get(id).await.ok_or_else(no_such_room)?;
+            session.current = Some(id);
+            Ok(Value::Null)
+        },
+        Some("create") => {
+            words.next();
+            let name = words.next().ok_or_else(|| invalid("Expected room name"))?;
+            let room = registry.create(name).await.map_err(|e| E::new("Could not create room", e))?;
+            Ok(u64::from(room.id()).into())
+        },
+        Some("format") => {
+            words.next();
+            session.format = words
+                .next()
+                .and_then(|s| s.parse().ok())
+                .ok_or_else(|| invalid("Expected 'text' or 'json'"))?;
+            Ok(Value::Null)
+        },
+        Some("interactive") => {
+            words.next();
+            session.interactive = match words.next() {
+                Some("on") => true,
+                Some("off") => false,
+                _ => return Err(invalid("Expected on or off")),
+            };
+            Ok(Value::Null)
+        },
+        Some("auth") => {
+            words.next();
+            let token = words.next().ok_or_else(|| invalid("Expected token"))?;
+            Ok(session.authenticate(token)?.to_string().into())
+        },
+        Some("help") => {
+            words.next();
+            match words.next() {
+                Some(name) => command_info(name)
+                    .map(|(usage, description)| Value::List(vec![usage.into(), description.into()]))
+                    .ok_or_else(unknown_command),
+                None => {
+                    let width = GLOBAL_COMMANDS.iter().chain(ROOM_COMMANDS).map(|(u, _)| u.len()).max().unwrap_or(0);
+                    let lines = GLOBAL_COMMANDS
+                        .iter()
+                        .chain(ROOM_COMMANDS)
+                        .map(|(usage, description)| format!("{:<2$}  {}", usage, description, width).into())
+                        .collect();
+                    Ok(Value::List(lines))
+                },
+            }
+        },
+        Some(_) => {
+            let room = if let Some(id) = session.current {
+                registry.get(id).await
+            } else {
+                registry.rooms().await.into_iter().next()
+            }.ok_or_else(no_such_room)?;
+            process_room_command(words, &room, session).await
+        },
+        None => Ok(Value::Null),
+    }
+}
 
-    use error::{NoneError as N, WrappedErr as E};
 
+/// Process a command addressing a single room
+///
+async fn process_room_command<'a>(
+    mut words: impl Iterator<Item = &'a str>,
+    room: &room::Room<impl rand::Rng>,
+    session: &mut Session,
+) -> Result<Value, CommandError> {
     fn parse_bool(input: &str) -> Option<bool> {
         match input {
             "true"  | "t" => Some(true),
@@ -144,53 +458,73 @@ async fn process_line(
         }
     }
 
-    let mut words = command.split_whitespace();
+    fn parse_switch(input: Option<&str>) -> Result<bool, CommandError> {
+        match input {
+            Some("on") => Ok(true),
+            Some("off") => Ok(false),
+            _ => Err(invalid("Expected on or off")),
+        }
+    }
+
+    fn switch(value: bool) -> Value {
+        if value { "on" } else { "off" }.into()
+    }
+
+    let central = room.central();
     match words.next() {
         Some("players") => {
-            let entries: Vec<_> = roster
+            let entries = room
+                .roster()
                 .read()
                 .await
                 .iter()
                 .enumerate()
-                .map(|(n, p)| Ok(format!("{} {} {} {}", n, p.name(), p.is_connected(), p.addr())))
+                .map(|(n, p)| Value::Record(vec![
+                    ("num", (n as u64).into()),
+                    ("name", p.name().into()),
+                    ("connected", p.is_connected().into()),
+                    ("addr", p.addr().to_string().into()),
+                ]))
                 .collect();
-            out.send_all(&mut iter(entries)).await.map_err(|e| E::new("Could not report result", e))
+            Ok(Value::List(entries))
         },
         Some("accept") => {
-            let v = words.next().and_then(parse_bool).ok_or_else(|| E::new("Expected 'true' or 'false'", N))?;
-            central.write().await.accept_players(v)
+            let v = words.next().and_then(parse_bool).ok_or_else(|| invalid("Expected 'true' or 'false'"))?;
+            central.write().await.accept_players(v)?;
+            Ok(Value::Null)
         },
         Some("restrict") => {
-            let num = words.next().and_then(|s| s.parse().ok()).ok_or_else(|| E::new("Expected number", N))?;
-            central.write().await.set_max_players(num)
+            let num = words.next().and_then(|s| s.parse().ok()).ok_or_else(|| invalid("Expected number"))?;
+            central.write().await.set_max_players(num)?;
+            Ok(Value::Null)
         },
         Some("kick") => {
             let num: usize = words
                 .next()
                 .and_then(|s| s.parse().ok())
-                .ok_or_else(|| E::new("Expected number", N))?;
+                .ok_or_else(|| invalid("Expected number"))?;
             log::info!("Kicking player");
-            roster.read().await.get(num).map(|p| p.kick()); // TODO: check return value?
-            Ok(())
+            room.roster().read().await.get(num).map(|p| p.kick()); // TODO: check return value?
+            Ok(Value::Null)
         },
-        Some("status") => {
-            let status = match phase.borrow().deref() {
-                game::GamePhase::Lobby{..}      => "lobby".to_string(),
-                game::GamePhase::Waiting{..}    => "waiting".to_string(),
-                game::GamePhase::Round{num, ..} => format!("round {}", num),
-                game::GamePhase::End            => "end".to_string(),
-            };
-            out.send(status).await.map_err(|e| E::new("Could not report result", e))
+        Some("status") => Ok(room.phase().borrow().to_string().into()),
+        Some("subscribe") => {
+            session.subscribe(room);
+            Ok(Value::Null)
+        },
+        Some("unsubscribe") => {
+            session.unsubscribe(room.id());
+            Ok(Value::Null)
         },
         Some("start") => {
-            log::info!("Starting game");
-            let mut central = central.write().await;
-            let msg = central.settings.as_game_control();
-            central.control.send_regular(msg).await
+            log::info!("Starting game in room {}", room.id());
+            room.start().await?;
+            Ok(Value::Null)
         },
         Some("end") => {
-            log::info!("Ending game");
-            central.write().await.control.send_regular(game::GameControl::EndOfGame).await
+            log::info!("Ending game in room {}", room.id());
+            room.end().await?;
+            Ok(Value::Null)
         },
         Some("set") => {
             let updated = match words.next() {
@@ -198,207 +532,268 @@ async fn process_line(
                     let num = words
                         .next()
                         .and_then(|s| s.parse().ok())
-                        .ok_or_else(|| E::new("Expected number", N))?;
+                        .ok_or_else(|| invalid("Expected number"))?;
                     central.write().await.set_virus_count(num)
                 },
                 Some("ticks") => {
                     let num = words
                         .next()
                         .and_then(|s| s.parse().ok())
-                        .ok_or_else(|| E::new("Expected number", N))?;
+                        .ok_or_else(|| invalid("Expected number"))?;
                     central.write().await.set_tick_duration(Duration::from_millis(num))
                 },
-                _ => Err(E::new("No such value", N)),
+                Some("bracket") => {
+                    let num = words
+                        .next()
+                        .and_then(|s| s.parse().ok())
+                        .ok_or_else(|| invalid("Expected number"))?;
+                    central.write().await.set_match_size(num)
+                },
+                Some("rule") => {
+                    let condition = words
+                        .next()
+                        .ok_or_else(|| invalid("Expected win condition"))?
+                        .parse()
+                        .map_err(|e| invalid_value("Invalid win condition", e))?;
+                    central.write().await.set_win_condition(condition)
+                },
+                Some("field") => {
+                    let dimensions = words
+                        .next()
+                        .ok_or_else(|| invalid("Expected field dimensions"))?
+                        .parse()
+                        .map_err(|e| invalid_value("Invalid field dimensions", e))?;
+                    central.write().await.set_dimensions(dimensions)
+                },
+                Some("colours") => {
+                    let palette = words
+                        .next()
+                        .ok_or_else(|| invalid("Expected number of colours"))?
+                        .parse()
+                        .map_err(|e| invalid_value("Invalid number of colours", e))?;
+                    central.write().await.set_palette(palette)
+                },
+                Some("match") => {
+                    let match_length = words
+                        .next()
+                        .ok_or_else(|| invalid("Expected match length"))?
+                        .parse()
+                        .map_err(|e| invalid_value("Invalid match length", e))?;
+                    central.write().await.set_match_length(match_length)
+                },
+                Some("diagonal") => {
+                    let diagonal = parse_switch(words.next())?;
+                    central.write().await.set_diagonal(diagonal)
+                },
+                Some("effects") => {
+                    let effects = parse_switch(words.next())?;
+                    central.write().await.set_effects(effects)
+                },
+                Some("specials") => {
+                    let specials = parse_switch(words.next())?;
+                    central.write().await.set_specials(specials)
+                },
+                _ => return Err(invalid("No such value")),
             }?;
             if updated {
-                Ok(())
+                Ok(Value::Null)
             } else {
-                out.send("Value will be sent when game starts")
-                    .await
-                    .map_err(|e| E::new("Could not report", e))
+                Ok("Value will be sent when game starts".into())
             }
         },
-        Some("get") => match words.next() {
-            Some("virs") => out
-                .send(central.read().await.settings.virus_count.to_string())
-                .await
-                .map_err(|e| E::new("Could not report result", e)),
-            Some("ticks") => out
-                .send(central.read().await.settings.tick_duration.as_millis().to_string())
-                .await
-                .map_err(|e| E::new("Could not report result", e)),
-            _ => Err(E::new("No such value", N)),
+        Some("get") => {
+            let settings = &central.read().await.settings;
+            match words.next() {
+                Some("virs") => Ok(u64::from(settings.virus_count).into()),
+                Some("ticks") => Ok((settings.tick_duration.as_millis() as u64).into()),
+                Some("bracket") => Ok((settings.match_size as u64).into()),
+                Some("rule") => Ok(settings.win_condition.to_string().into()),
+                Some("field") => Ok(settings.dimensions.to_string().into()),
+                Some("colours") => Ok(u64::from(settings.palette.len()).into()),
+                Some("match") => Ok((usize::from(settings.match_length) as u64).into()),
+                Some("diagonal") => Ok(switch(settings.diagonal)),
+                Some("effects") => Ok(switch(settings.effects)),
+                Some("specials") => Ok(switch(settings.specials)),
+                _ => Err(invalid("No such value")),
+            }
         },
-        None => Ok(()),
-        _ => Err(E::new("No such command", N)),
+        None => Ok(Value::Null),
+        _ => Err(unknown_command()),
     }
 }
 
 
-/// Utility struct for central objects shared between all consoles
+/// Determine completions for the last word of the given text
 ///
-struct Central {
-    pub control: ControlSender,
-    pub settings: Settings,
-}
-
-impl Central {
-    /// Set and send accept player setting
-    ///
-    /// This function returns an error if `control` is not a
-    /// `ControlSender::Lobby`.
-    ///
-    pub fn accept_players(&mut self, accept: bool) -> Result<(), WrappedErr> {
-        self.settings.accept_players = accept;
-        self.send_lobby_settings()
+/// This function returns the word to complete along with all candidates
+/// starting with that word. Command words, arguments with a fixed set of values
+/// as well as room and player numbers are completed.
+///
+async fn completions<'t, R>(
+    text: &'t str,
+    registry: &Arc<room::Registry<R>>,
+    session: &Session,
+) -> (&'t str, Vec<String>)
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    fn names(commands: &[(&str, &str)]) -> Vec<String> {
+        commands.iter().filter_map(|(usage, _)| usage.split_whitespace().next()).map(Into::into).collect()
     }
 
-    /// Set and send max player setting
-    ///
-    /// This function returns an error if `control` is not a
-    /// `ControlSender::Lobby`.
-    ///
-    pub fn set_max_players(&mut self, max_players: u8) -> Result<(), WrappedErr> {
-        self.settings.max_players = max_players;
-        self.send_lobby_settings()
+    fn values(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
     }
 
-    /// Set and send virus count setting
-    ///
-    /// This function returns an error if `control` is not a
-    /// `ControlSender::Regular`.
-    ///
-    pub fn set_virus_count(&mut self, virus_count: u8) -> Result<bool, WrappedErr> {
-        self.settings.virus_count = virus_count;
-        self.send_game_settings()
-    }
+    let (head, word) = text.split_at(text.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0));
+    let words: Vec<_> = head.split_whitespace().collect();
+    let (room, addressed, words) = match words.as_slice() {
+        ["room", id, rest @ ..] => (id.parse().ok(), true, rest),
+        rest => (session.current, false, rest),
+    };
+
+    let candidates = match words {
+        [] if addressed => names(ROOM_COMMANDS),
+        [] => names(GLOBAL_COMMANDS).into_iter().chain(names(ROOM_COMMANDS)).collect(),
+        ["room"] | ["use"] => registry.rooms().await.iter().map(|r| r.id().to_string()).collect(),
+        ["help"] => names(GLOBAL_COMMANDS).into_iter().chain(names(ROOM_COMMANDS)).collect(),
+        ["format"] => values(&["text", "json"]),
+        ["interactive"] => values(&["on", "off"]),
+        ["accept"] => values(&["true", "false"]),
+        ["set"] | ["get"] => values(PROPERTIES),
+        ["set", "diagonal"] | ["set", "effects"] | ["set", "specials"] => values(&["on", "off"]),
+        ["kick"] => {
+            let room = if let Some(id) = room {
+                registry.get(id).await
+            } else {
+                registry.rooms().await.into_iter().next()
+            };
+            match room {
+                Some(room) => (0..room.roster().read().await.len()).map(|n| n.to_string()).collect(),
+                None => Default::default(),
+            }
+        },
+        _ => Default::default(),
+    };
+    (word, candidates.into_iter().filter(|c| c.starts_with(word)).collect())
+}
 
-    /// Set and send tock duration setting
-    ///
-    /// This function returns an error if `control` is not a
-    /// `ControlSender::Regular`.
-    ///
-    pub fn set_tick_duration(&mut self, duration: Duration) -> Result<bool, WrappedErr> {
-        self.settings.tick_duration = duration;
-        self.send_game_settings()
-    }
 
-    /// Send the current lobby settings
-    ///
-    /// Send the current lobby settings via the control channel. This function
-    /// returns an error if `control` is not a `ControlSender::Lobby`.
-    ///
-    pub fn send_lobby_settings(&mut self) -> Result<(), WrappedErr> {
-        self.control
-            .as_lobby_sender()
-            .ok_or_else(|| error::WrappedErr::new("Not in lobby phase", error::NoneError))?
-            .send(self.settings.as_lobby_control())
-            .map_err(|e| error::WrappedErr::new("Could not send new settings", e))
-    }
+/// Retrieve the usage and description of the command with the given name
+///
+fn command_info(name: &str) -> Option<(&'static str, &'static str)> {
+    GLOBAL_COMMANDS
+        .iter()
+        .chain(ROOM_COMMANDS)
+        .find(|(usage, _)| usage.split_whitespace().next() == Some(name))
+        .copied()
+}
 
-    /// Send the current game settings
-    ///
-    /// Send the current game settings via the control channel. This function
-    /// returns an error if `control` is not a `ControlSender::Regular`.
-    ///
-    pub fn send_game_settings(&mut self) -> Result<bool, error::WrappedErr> {
-        if let Some (sender) = self.control.as_regular_sender() {
-            sender
-                .send(self.settings.as_game_control())
-                .map(|_| true)
-                .map_err(|e| error::WrappedErr::new("Could not send new settings", e))
-        } else {
-            Ok(false)
-        }
-    }
+
+/// Convert a report from the room with the given id to an event
+///
+fn report_value(room: u32, report: game::Report) -> Value {
+    use game::Report as R;
+
+    let player = |name: String| ("player", Value::from(name));
+    let (event, fields) = match report {
+        R::Connected{addr}              => ("connected", vec![("addr", addr.to_string().into())]),
+        R::Registered{player: p}        => ("registered", vec![player(p)]),
+        R::Left{player: p}              => ("left", vec![player(p)]),
+        R::Phase{phase}                 => ("phase", vec![("phase", phase.into())]),
+        R::RoundStart{num, players}     => ("round_start", vec![
+            ("num", u64::from(num).into()),
+            ("players", Value::List(players.into_iter().map(Into::into).collect())),
+        ]),
+        R::RoundEnd{num, ranking}       => ("round_end", vec![
+            ("num", u64::from(num).into()),
+            ("ranking", Value::List(ranking
+                .into_iter()
+                .map(|(p, score)| Value::Record(vec![player(p), ("score", u64::from(score).into())]))
+                .collect()
+            )),
+        ]),
+        R::Capsules{player: p, count}   => ("capsules", vec![player(p), ("count", u64::from(count).into())]),
+        R::Garbage{player: p, rows}     => ("garbage", vec![player(p), ("rows", u64::from(rows).into())]),
+        R::Score{player: p, viruses}    => ("score", vec![player(p), ("viruses", u64::from(viruses).into())]),
+        R::Defeat{player: p}            => ("defeat", vec![player(p)]),
+        R::RoundWon{num, player: p}     => ("round_won", vec![("num", u64::from(num).into()), player(p)]),
+        R::MatchWon{player: p}          => ("match_won", vec![player(p)]),
+        R::TournamentWon{player: p}     => ("tournament_won", vec![player(p)]),
+    };
+
+    let mut record = vec![("event", event.into()), ("room", u64::from(room).into())];
+    record.extend(fields);
+    Value::Record(record)
 }
 
 
-/// Game settings
+/// Create an error for an unrecognized command word
 ///
-#[derive(Clone, Default, Debug)]
-pub struct Settings {
-    pub accept_players: bool,
-    pub max_players: u8,
-    pub virus_count: u8,
-    pub tick_duration: Duration,
+fn unknown_command() -> CommandError {
+    CommandError::new(
+        Code::UnknownCommand,
+        error::WrappedErr::new("No such command, see 'help' for a list", error::NoneError),
+    )
 }
 
-impl Settings {
-    /// Create a LobbyControl message reflecting the relevant settings
-    pub fn as_lobby_control(&self) -> game::LobbyControl {
-        game::LobbyControl::Settings{
-            registration_acceptance: self.accept_players,
-            max_players: self.max_players,
-        }
-    }
 
-    /// Create a GameControl message reflecting the relevant settings
-    fn as_game_control(&self) -> game::GameControl {
-        game::GameControl::Settings{viruses: self.virus_count, tick: self.tick_duration}
-    }
+/// Create an error for a missing or malformed argument
+///
+fn invalid(msg: &'static str) -> CommandError {
+    CommandError::new(Code::InvalidArgument, error::WrappedErr::new(msg, error::NoneError))
 }
 
 
-/// Common sender for both lobby and "regular" control
+/// Create an error for an argument which could not be parsed
 ///
-enum ControlSender {
-    Lobby(watch::Sender<game::LobbyControl>),
-    Regular(watch::Sender<game::GameControl>),
+fn invalid_value(msg: &'static str, err: impl std::error::Error + Send + 'static) -> CommandError {
+    CommandError::new(Code::InvalidArgument, error::WrappedErr::new(msg, err))
 }
 
-impl ControlSender {
-    /// Send a regular control message, switching if necessary
-    ///
-    /// If the sender is a `Lobby`, this function will issue a game start
-    /// message and re-initialize the sender as a `Regular` with an appropriate
-    /// sender. Otherwise, the given contol message will be just sent over the
-    /// existing channel.
-    ///
-    pub async fn send_regular(
-        &mut self,
-        message: game::GameControl,
-    ) -> Result<(), error::WrappedErr> {
-
-        match self {
-            Self::Lobby(old) => {
-                let (sender, receiver) = watch::channel(message);
-                old.send(game::LobbyControl::GameStart(receiver))
-                    .map_err(|e| error::WrappedErr::new("Could not send game start message", e))?;
-                old.closed().await;
-                *self = Self::Regular(sender);
-                Ok(())
-            },
-            Self::Regular(sender) => sender
-                .send(message)
-                .map_err(|e| error::WrappedErr::new("Could not send control message", e)),
-        }
-    }
 
-    /// Retrieve a reference of the inner lobby control sender, if any
-    ///
-    pub fn as_lobby_sender(&self) -> Option<&watch::Sender<game::LobbyControl>> {
-        if let Self::Lobby(sender) = self {
-            Some(sender)
-        } else {
-            None
-        }
-    }
+/// Usage and description of commands not addressing a single room
+///
+const GLOBAL_COMMANDS: &[(&str, &str)] = &[
+    ("rooms", "List all rooms"),
+    ("room <room num> <command>", "Apply a command to the given room"),
+    ("use <room num>", "Select the room for subsequent commands"),
+    ("create <name>", "Create a new room"),
+    ("format <text|json>", "Select the format of replies"),
+    ("interactive <on|off>", "Switch line editing, history and completion on or off"),
+    ("help [command]", "List all commands or describe the given one"),
+    ("auth <token>", "Authenticate as a remote game master"),
+];
+
+
+/// Usage and description of commands addressing a single room
+///
+const ROOM_COMMANDS: &[(&str, &str)] = &[
+    ("players", "List the registered players"),
+    ("accept <true|false>", "Control whether new registrations are accepted"),
+    ("restrict <num>", "Restrict the number of registered players"),
+    ("kick <player num>", "Terminate the connection to the given player"),
+    ("status", "Print the current phase"),
+    ("subscribe", "Subscribe to the room's events"),
+    ("unsubscribe", "Cancel the subscription to the room's events"),
+    ("start", "Start the game"),
+    ("end", "End the game and remove the room"),
+    ("set <property> <value>", "Set the given property"),
+    ("get <property>", "Print the value of the given property"),
+];
+
+
+/// Properties which may be accessed via `set` and `get`
+///
+const PROPERTIES: &[&str] = &[
+    "virs", "ticks", "bracket", "rule", "field", "colours", "match", "diagonal", "effects", "specials",
+];
 
-    /// Retrieve a reference of the inner regular game control sender, if any
-    ///
-    pub fn as_regular_sender(&self) -> Option<&watch::Sender<game::GameControl>> {
-        if let Self::Regular(sender) = self {
-            Some(sender)
-        } else {
-            None
-        }
-    }
-}
 
-impl From<watch::Sender<game::LobbyControl>> for ControlSender {
-    fn from(sender: watch::Sender<game::LobbyControl>) -> Self {
-        Self::Lobby(sender)
-    }
-}
+/// Commands permitted before authentication
+///
+const UNAUTHENTICATED_COMMANDS: &[&str] = &["auth", "help", "format", "interactive"];
 
+
+/// Maximum number of failed authentication attempts per connection
+///
+const MAX_AUTH_ATTEMPTS: u8 = 3;
@@ -0,0 +1,127 @@
+//! Access control for remote game master consoles
+
+use std::fmt;
+
+
+/// Role of a game master
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Only commands inspecting rooms and games are permitted
+    ReadOnly,
+    /// All commands are permitted
+    Full,
+}
+
+impl Role {
+    /// Determine whether the role permits the command with the given word
+    ///
+    pub fn permits(self, command: &str) -> bool {
+        match self {
+            Self::ReadOnly  => READ_ONLY_COMMANDS.contains(&command),
+            Self::Full      => true,
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReadOnly  => write!(f, "read"),
+            Self::Full      => write!(f, "full"),
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "read" => Ok(Self::ReadOnly),
+            "full" => Ok(Self::Full),
+            _ => Err(()),
+        }
+    }
+}
+
+
+/// Set of access tokens, each associated with a role
+///
+/// Tokens are parsed from a text with one token per line, followed by the
+/// associated role (`read` or `full`). Empty lines and lines starting with `#`
+/// are ignored.
+///
+#[derive(Clone, Debug, Default)]
+pub struct Tokens {
+    tokens: Vec<(String, Role)>,
+}
+
+impl Tokens {
+    /// Retrieve the role associated with the given token
+    ///
+    pub fn role(&self, token: &str) -> Option<Role> {
+        self.tokens.iter().find(|(t, _)| constant_time_eq(t.as_bytes(), token.as_bytes())).map(|(_, r)| *r)
+    }
+}
+
+impl std::str::FromStr for Tokens {
+    type Err = TokensError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = s
+            .lines()
+            .enumerate()
+            .map(|(n, l)| (n, l.trim()))
+            .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'))
+            .map(|(n, l)| {
+                let mut words = l.split_whitespace();
+                match (words.next(), words.next().map(str::parse), words.next()) {
+                    (Some(token), Some(Ok(role)), None) => Ok((token.to_string(), role)),
+                    _ => Err(TokensError {line: n + 1}),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if tokens.is_empty() {
+            Err(TokensError {line: 0})
+        } else {
+            Ok(Self {tokens})
+        }
+    }
+}
+
+
+/// Error indicating a malformed token entry
+///
+/// The line number is `0` if no token was found at all.
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TokensError {
+    line: usize,
+}
+
+impl std::error::Error for TokensError {}
+
+impl fmt::Display for TokensError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "Expected at least one token")
+        } else {
+            write!(f, "Expected '<token> <read|full>' in line {}", self.line)
+        }
+    }
+}
+
+
+/// Compare two byte strings in time only depending on their lengths
+///
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+
+/// Commands permitted for the read-only role
+///
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "rooms", "use", "format", "interactive", "help", "auth", "players", "status", "get", "subscribe", "unsubscribe",
+];
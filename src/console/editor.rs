@@ -0,0 +1,421 @@
+//! Line input for game master consoles
+//!
+//! Commands are read line by line. In interactive mode, which is intended for
+//! clients using a TTY in raw mode, input is echoed and may be edited. Previous
+//! lines may be recalled and words may be completed. Lines are limited in
+//! length, as input is processed before any authentication took place.
+
+use std::collections::VecDeque;
+
+use tokio::io;
+
+
+/// Line editor
+///
+/// The editor reads raw input from the reader and writes echo and output lines
+/// to the writer. Reading input via [read](Self::read) is cancellation safe.
+/// Input read is then processed via [next_input](Self::next_input).
+///
+pub struct LineEditor<R, W> {
+    read: R,
+    write: W,
+    raw: VecDeque<u8>,
+    interactive: bool,
+    line: Vec<char>,
+    cursor: usize,
+    utf8: Vec<u8>,
+    escape: Option<Vec<u8>>,
+    skip_lf: bool,
+    history: VecDeque<String>,
+    recalled: Option<(usize, Vec<char>)>,
+    prompt_shown: bool,
+    echo: Vec<u8>,
+}
+
+impl<R: io::AsyncRead + Unpin, W: io::AsyncWrite + Unpin> LineEditor<R, W> {
+    /// Create a new line editor in non-interactive mode
+    ///
+    pub fn new(read: R, write: W) -> Self {
+        Self {
+            read,
+            write,
+            raw: Default::default(),
+            interactive: false,
+            line: Default::default(),
+            cursor: 0,
+            utf8: Default::default(),
+            escape: None,
+            skip_lf: false,
+            history: Default::default(),
+            recalled: None,
+            prompt_shown: false,
+            echo: Default::default(),
+        }
+    }
+
+    /// Switch interactive mode on or off
+    ///
+    pub fn set_interactive(&mut self, interactive: bool) {
+        self.interactive = interactive
+    }
+
+    /// Read raw input
+    ///
+    /// This function returns `false` if the end of the input was reached.
+    ///
+    pub async fn read(&mut self) -> io::Result<bool> {
+        use io::AsyncReadExt;
+
+        let mut buf = [0u8; 256];
+        let len = self.read.read(&mut buf).await?;
+        self.raw.extend(&buf[..len]);
+        Ok(len > 0)
+    }
+
+    /// Process raw input read so far
+    ///
+    /// This function returns `None` if all raw input was consumed without
+    /// yielding any `Input`.
+    ///
+    pub fn next_input(&mut self) -> Option<Input> {
+        while let Some(byte) = self.raw.pop_front() {
+            let res = if self.interactive {
+                self.process_interactive(byte)
+            } else {
+                self.process_plain(byte)
+            };
+            if res.is_some() {
+                return res
+            }
+        }
+        None
+    }
+
+    /// Retrieve any unterminated line left at the end of the input
+    ///
+    pub fn finish(&mut self) -> Option<String> {
+        if self.line.is_empty() && self.utf8.is_empty() {
+            None
+        } else {
+            Some(self.take_line())
+        }
+    }
+
+    /// Complete the word before the cursor
+    ///
+    /// The word before the cursor is expected to be a prefix of every one of
+    /// the `candidates`. If there is only one, the word is completed. If there
+    /// are multiple candidates, the word is extended by their common prefix or,
+    /// if there is no common prefix, the candidates are listed.
+    ///
+    pub fn complete(&mut self, word: &str, candidates: &[String]) {
+        let common = candidates.iter().skip(1).fold(candidates.first().map(String::as_str), |c, s| c.map(|c| {
+            let len = c.chars().zip(s.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a.len_utf8()).sum();
+            &c[..len]
+        }));
+
+        match (common, candidates.len()) {
+            (Some(common), 1) => {
+                self.insert(common.chars().skip(word.chars().count()).chain(std::iter::once(' ')))
+            },
+            (Some(common), _) if common.len() > word.len() => {
+                self.insert(common.chars().skip(word.chars().count()))
+            },
+            (_, 0) => self.echo.push(BELL),
+            _ => {
+                self.echo.extend(b"\r\n");
+                self.echo.extend(candidates.join("  ").as_bytes());
+                self.echo.extend(b"\r\n");
+                self.redraw();
+            },
+        }
+    }
+
+    /// Write echo resulting from processed input
+    ///
+    pub async fn flush(&mut self) -> io::Result<()> {
+        use io::AsyncWriteExt;
+
+        let echo = std::mem::take(&mut self.echo);
+        self.write.write_all(&echo).await?;
+        self.write.flush().await
+    }
+
+    /// Write lines of output
+    ///
+    /// In interactive mode, the prompt and the line currently edited are
+    /// redrawn after the output.
+    ///
+    pub async fn write_lines(&mut self, lines: &[String]) -> io::Result<()> {
+        let newline: &[u8] = if self.interactive {
+            if self.prompt_shown {
+                self.echo.extend(b"\r\x1b[K");
+            }
+            b"\r\n"
+        } else {
+            b"\n"
+        };
+        lines.iter().for_each(|l| {
+            self.echo.extend(l.as_bytes());
+            self.echo.extend(newline);
+        });
+        if self.interactive {
+            self.redraw();
+        }
+        self.flush().await
+    }
+
+    /// Process a single byte in non-interactive mode
+    ///
+    fn process_plain(&mut self, byte: u8) -> Option<Input> {
+        let skip_lf = std::mem::replace(&mut self.skip_lf, byte == b'\r');
+        match byte {
+            b'\n' if skip_lf => None,
+            b'\n' | b'\r' => Some(Input::Line(self.take_line())),
+            _ if self.utf8.len() >= MAX_LINE_LEN => Some(Input::TooLong),
+            byte => {
+                self.utf8.push(byte);
+                None
+            },
+        }
+    }
+
+    /// Process a single byte in interactive mode
+    ///
+    fn process_interactive(&mut self, byte: u8) -> Option<Input> {
+        let skip_lf = std::mem::replace(&mut self.skip_lf, byte == b'\r');
+
+        if let Some(mut escape) = self.escape.take() {
+            escape.push(byte);
+            match escape.as_slice() {
+                [b'['] | [b'O'] => self.escape = Some(escape),
+                [b'[', params @ .., final_byte] if !(0x40..=0x7e).contains(final_byte) &&
+                    params.len() < MAX_ESCAPE_LEN => self.escape = Some(escape),
+                [b'[', b'A'] | [b'O', b'A'] => self.recall(true),
+                [b'[', b'B'] | [b'O', b'B'] => self.recall(false),
+                [b'[', b'C'] | [b'O', b'C'] => self.move_cursor(self.cursor + 1),
+                [b'[', b'D'] | [b'O', b'D'] => self.move_cursor(self.cursor.saturating_sub(1)),
+                [b'[', b'H'] | [b'O', b'H'] | [b'[', b'1', b'~'] => self.move_cursor(0),
+                [b'[', b'F'] | [b'O', b'F'] | [b'[', b'4', b'~'] => self.move_cursor(self.line.len()),
+                [b'[', b'3', b'~'] => self.delete(self.cursor),
+                _ => (),
+            }
+            return None
+        }
+
+        if !self.utf8.is_empty() || byte >= 0x80 {
+            self.utf8.push(byte);
+            match std::str::from_utf8(&self.utf8) {
+                Ok(s) => {
+                    let chars: Vec<_> = s.chars().collect();
+                    self.utf8.clear();
+                    self.insert(chars.into_iter());
+                },
+                Err(e) if e.error_len().is_some() => self.utf8.clear(),
+                Err(_) => (),
+            }
+            return None
+        }
+
+        match byte {
+            b'\n' if skip_lf => (),
+            b'\r' | b'\n' => {
+                self.echo.extend(b"\r\n");
+                self.prompt_shown = false;
+                let line = self.take_line();
+                if !line.trim().is_empty() && secret_start(&line).is_none() && self.history.back() != Some(&line) {
+                    self.history.push_back(line.clone());
+                    if self.history.len() > MAX_HISTORY_LEN {
+                        self.history.pop_front();
+                    }
+                }
+                return Some(Input::Line(line))
+            },
+            b'\t' => return Some(Input::Complete(self.line[..self.cursor].iter().collect())),
+            CTRL_A => self.move_cursor(0),
+            CTRL_E => self.move_cursor(self.line.len()),
+            CTRL_C => {
+                self.echo.extend(b"^C\r\n");
+                self.take_line();
+                self.redraw();
+            },
+            CTRL_D if self.line.is_empty() => return Some(Input::End),
+            CTRL_D => self.delete(self.cursor),
+            CTRL_K => {
+                self.line.truncate(self.cursor);
+                self.redraw();
+            },
+            CTRL_U => {
+                self.line.drain(..self.cursor);
+                self.cursor = 0;
+                self.redraw();
+            },
+            BACKSPACE | DELETE if self.cursor > 0 => self.delete(self.cursor - 1),
+            ESCAPE => self.escape = Some(Vec::new()),
+            byte if byte >= b' ' => self.insert(std::iter::once(byte as char)),
+            _ => (),
+        }
+        None
+    }
+
+    /// Take the current line, leaving an empty one
+    ///
+    fn take_line(&mut self) -> String {
+        let mut line: String = self.line.drain(..).collect();
+        line.push_str(&String::from_utf8_lossy(&self.utf8));
+        self.utf8.clear();
+        self.cursor = 0;
+        self.recalled = None;
+        line
+    }
+
+    /// Insert characters at the cursor
+    ///
+    /// Characters which would make the line exceed `MAX_LINE_LEN` are dropped.
+    ///
+    fn insert(&mut self, chars: impl Iterator<Item = char>) {
+        let tail = self.line.split_off(self.cursor);
+        let room = MAX_LINE_LEN.saturating_sub(self.line.len() + tail.len());
+        let mut chars = chars.peekable();
+        self.line.extend(chars.by_ref().take(room));
+        if chars.peek().is_some() {
+            self.echo.push(BELL);
+        }
+        self.cursor = self.line.len();
+        self.line.extend(tail);
+        self.redraw();
+    }
+
+    /// Delete the character at the given position
+    ///
+    fn delete(&mut self, pos: usize) {
+        if pos < self.line.len() {
+            self.line.remove(pos);
+            self.cursor = pos;
+            self.redraw();
+        }
+    }
+
+    /// Move the cursor to the given position, if it lies within the line
+    ///
+    fn move_cursor(&mut self, pos: usize) {
+        if pos <= self.line.len() {
+            self.cursor = pos;
+            self.redraw();
+        }
+    }
+
+    /// Replace the line with an older or newer entry from the history
+    ///
+    /// The line edited before the first recall is restored when moving past
+    /// the newest entry.
+    ///
+    fn recall(&mut self, older: bool) {
+        let len = self.history.len();
+        let (index, draft) = match (self.recalled.take(), older) {
+            (None, true) if len > 0 => (len - 1, self.line.clone()),
+            (None, _) => return,
+            (Some((0, draft)), true) => (0, draft),
+            (Some((index, draft)), true) => (index - 1, draft),
+            (Some((index, draft)), false) if index + 1 < len => (index + 1, draft),
+            (Some((_, draft)), false) => {
+                self.line = draft;
+                self.cursor = self.line.len();
+                self.redraw();
+                return
+            },
+        };
+        self.line = self.history[index].chars().collect();
+        self.cursor = self.line.len();
+        self.recalled = Some((index, draft));
+        self.redraw();
+    }
+
+    /// Redraw the prompt and the current line
+    ///
+    /// Secrets, i.e. the token passed to `auth`, are masked.
+    ///
+    fn redraw(&mut self) {
+        if !self.interactive {
+            return
+        }
+
+        let line: String = self.line.iter().collect();
+        let secret = secret_start(&line).unwrap_or(line.len());
+        let (visible, masked) = line.split_at(secret);
+
+        self.echo.extend(b"\r\x1b[K");
+        self.echo.extend(PROMPT.as_bytes());
+        self.echo.extend(visible.as_bytes());
+        self.echo.extend(masked.chars().map(|c| if c.is_whitespace() { b' ' } else { b'*' }));
+        let back = self.line.len() - self.cursor;
+        if back > 0 {
+            self.echo.extend(format!("\x1b[{}D", back).as_bytes());
+        }
+        self.prompt_shown = true;
+    }
+}
+
+
+/// Input resulting from processing raw input
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Input {
+    /// A complete line
+    Line(String),
+    /// A request for completing the given text before the cursor
+    Complete(String),
+    /// A request for ending the session
+    End,
+    /// A line exceeding `MAX_LINE_LEN`
+    TooLong,
+}
+
+
+/// Determine the start of any secret within the given line
+///
+/// If the line is an `auth` command, this function returns the byte offset of
+/// the token following the command.
+///
+fn secret_start(line: &str) -> Option<usize> {
+    let start = line.len() - line.trim_start().len();
+    let rest = line[start..].strip_prefix("auth")?;
+    if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        Some(line.len() - rest.len())
+    } else {
+        None
+    }
+}
+
+
+/// Prompt displayed in interactive mode
+///
+const PROMPT: &str = "> ";
+
+
+/// Maximum length of a line, in bytes in non-interactive mode and in characters
+/// in interactive mode
+///
+const MAX_LINE_LEN: usize = 1024;
+
+
+/// Maximum number of lines retained in the history
+///
+const MAX_HISTORY_LEN: usize = 100;
+
+
+/// Maximum number of parameter bytes of an escape sequence
+///
+const MAX_ESCAPE_LEN: usize = 8;
+
+
+const BELL: u8 = 0x07;
+const BACKSPACE: u8 = 0x08;
+const CTRL_A: u8 = 0x01;
+const CTRL_C: u8 = 0x03;
+const CTRL_D: u8 = 0x04;
+const CTRL_E: u8 = 0x05;
+const CTRL_K: u8 = 0x0b;
+const CTRL_U: u8 = 0x15;
+const DELETE: u8 = 0x7f;
+const ESCAPE: u8 = 0x1b;
@@ -0,0 +1,235 @@
+//! HTTP front-end for game master commands
+//!
+//! Requests are translated to console commands, which are processed like
+//! commands issued via the game master console. Replies are presented in the
+//! JSON format. In addition, metrics are served in the Prometheus text format.
+
+use std::fmt;
+use std::sync::Arc;
+
+use tokio::io;
+
+use crate::error;
+use crate::room;
+
+use super::reply::{Code, CommandError, Format, Value};
+
+
+/// Serve a single HTTP request via the given stream
+///
+/// The connection is closed after the response was sent or if the request
+/// could not be read within a reasonable time.
+///
+pub async fn serve_connection<R>(
+    stream: impl io::AsyncRead + io::AsyncWrite + Unpin,
+    registry: Arc<room::Registry<R>>,
+) -> io::Result<()>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    let mut stream = io::BufStream::new(stream);
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream))
+        .await
+        .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+    let reply = match request {
+        Ok(request) if request.method == "GET" && request.path == "/metrics" => {
+            let body = crate::metrics::METRICS.render();
+            return respond(&mut stream, "200 OK", "text/plain; version=0.0.4", body.as_ref()).await
+        },
+        Ok(request) => process_request(&request, &registry).await,
+        Err(e) => Err(e),
+    };
+
+    let status = match &reply {
+        Ok(_) => "200 OK",
+        Err(e) => match e.code() {
+            Code::UnknownCommand | Code::NoSuchRoom => "404 Not Found",
+            Code::InvalidArgument                   => "400 Bad Request",
+            Code::Failed                            => "409 Conflict",
+            Code::Forbidden                         => "403 Forbidden",
+        },
+    };
+    let body = Format::Json.render(&reply).concat() + "\n";
+    respond(&mut stream, status, "application/json", body.as_ref()).await
+}
+
+
+/// Send a response with the given status and body and close the stream
+///
+async fn respond(
+    stream: &mut (impl io::AsyncWrite + Unpin),
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    use io::AsyncWriteExt;
+
+    let head = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len(),
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+
+/// Process a single request
+///
+/// Requests addressing a specific room via a path starting with
+/// `/rooms/<room num>` are applied to that room, other requests to the first
+/// room.
+///
+async fn process_request<R>(
+    request: &Request,
+    registry: &Arc<room::Registry<R>>,
+) -> Result<Value, CommandError>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    let segments: Vec<_> = request.path.split('/').filter(|s| !s.is_empty()).collect();
+    let (room, segments) = match segments.as_slice() {
+        ["rooms", id, rest @ ..] if !rest.is_empty() => (Some(*id), rest),
+        rest => (None, rest),
+    };
+
+    let body = request.body.trim();
+    if body.contains(char::is_whitespace) {
+        return Err(super::invalid("Expected a single value"))
+    }
+
+    let command = match (request.method.as_ref(), segments) {
+        ("GET",     ["status"]) if room.is_none()   => return Ok(status(registry).await),
+        ("GET",     ["rooms"]) if room.is_none()    => "rooms".to_string(),
+        ("GET",     ["players"])                    => "players".to_string(),
+        ("DELETE",  ["players", num])               => format!("kick {}", num),
+        ("GET",     ["phase"])                      => "status".to_string(),
+        ("PUT",     ["accept"])                     => format!("accept {}", body),
+        ("PUT",     ["restrict"])                   => format!("restrict {}", body),
+        ("POST",    ["start"])                      => "start".to_string(),
+        ("POST",    ["end"])                        => "end".to_string(),
+        ("GET",     ["settings", name])             => format!("get {}", name),
+        ("PUT",     ["settings", name])             => format!("set {} {}", name, body),
+        _ => return Err(CommandError::new(
+            Code::UnknownCommand,
+            error::WrappedErr::new("No such endpoint", error::NoneError),
+        )),
+    };
+    let command = match room {
+        Some(id) => format!("room {} {}", id, command),
+        None => command,
+    };
+
+    super::process_line(command.as_ref(), registry, &mut super::Session::new(None)).await
+}
+
+
+/// Create a snapshot of all rooms
+///
+/// The snapshot includes each room's phase and registered players along with
+/// their scores.
+///
+async fn status<R>(registry: &Arc<room::Registry<R>>) -> Value
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    let mut rooms = Vec::new();
+    for room in registry.rooms().await {
+        let phase = room.phase().borrow().to_string();
+        let players = room
+            .roster()
+            .read()
+            .await
+            .iter()
+            .enumerate()
+            .map(|(n, p)| Value::Record(vec![
+                ("num", (n as u64).into()),
+                ("name", p.name().into()),
+                ("connected", p.is_connected().into()),
+                ("score", u64::from(p.score()).into()),
+                ("wins", u64::from(p.wins()).into()),
+            ]))
+            .collect();
+        rooms.push(Value::Record(vec![
+            ("id", u64::from(room.id()).into()),
+            ("name", room.name().into()),
+            ("phase", phase.into()),
+            ("players", Value::List(players)),
+        ]));
+    }
+    Value::Record(vec![("rooms", Value::List(rooms))])
+}
+
+
+/// Read a single request
+///
+/// This function returns an error if the request could not be read and an
+/// `Err` in an `Ok` if the request is malformed.
+///
+async fn read_request(
+    stream: &mut (impl io::AsyncBufRead + Unpin),
+) -> io::Result<Result<Request, CommandError>> {
+    use io::{AsyncBufReadExt, AsyncReadExt};
+
+    let mut line = String::new();
+    stream.take(MAX_LINE_LEN).read_line(&mut line).await?;
+    let mut words = line.split_whitespace();
+    let (method, path) = match (words.next(), words.next(), words.next()) {
+        (Some(method), Some(path), Some(version)) if version.starts_with("HTTP/1.") => (method, path),
+        _ => return Ok(Err(super::invalid("Malformed request line"))),
+    };
+    let path = path.split('?').next().unwrap_or_default();
+
+    let mut len = 0;
+    loop {
+        let mut header = String::new();
+        if stream.take(MAX_LINE_LEN).read_line(&mut header).await? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into())
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                len = match value.trim().parse() {
+                    Ok(len) if len <= MAX_BODY_LEN => len,
+                    _ => return Ok(Err(super::invalid("Invalid content length"))),
+                };
+            }
+        }
+    }
+
+    let mut body = vec![0; len];
+    stream.read_exact(&mut body).await?;
+    let body = match String::from_utf8(body) {
+        Ok(body) => body,
+        Err(e) => return Ok(Err(super::invalid_value("Invalid request body", e))),
+    };
+
+    Ok(Ok(Request {method: method.to_string(), path: path.to_string(), body}))
+}
+
+
+/// An HTTP request
+///
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    body: String,
+}
+
+
+/// Maximum length of the request line and of headers
+///
+const MAX_LINE_LEN: u64 = 1024;
+
+
+/// Maximum length of a request body
+///
+const MAX_BODY_LEN: usize = 256;
+
+
+/// Time after which a connection is closed if the request wasn't read
+///
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
@@ -0,0 +1,266 @@
+//! Replies to game master commands
+
+use std::fmt;
+
+use crate::error::WrappedErr;
+
+
+/// Output format of a game master console
+///
+/// The format determines how replies are presented to the game master. It is
+/// chosen per connection.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Ad-hoc text lines followed by `OK`, or an error message
+    #[default]
+    Text,
+    /// A single JSON object per command
+    Json,
+}
+
+impl Format {
+    /// Render the reply to a command as a sequence of lines
+    ///
+    pub fn render(self, reply: &Result<Value, CommandError>) -> Vec<String> {
+        match (self, reply) {
+            (Self::Text, Ok(value)) => {
+                let mut lines = value.text_lines();
+                lines.push("OK".to_string());
+                lines
+            },
+            (Self::Text, Err(e)) => vec![e.to_string()],
+            (Self::Json, Ok(value)) => {
+                let mut line = r#"{"status":"ok","data":"#.to_string();
+                value.write_json(&mut line);
+                line.push_str(r#","error":null}"#);
+                vec![line]
+            },
+            (Self::Json, Err(e)) => {
+                let mut line = r#"{"status":"error","data":null,"error":{"code":"#.to_string();
+                write_json_str(&mut line, e.code().as_str());
+                line.push_str(r#","message":"#);
+                write_json_str(&mut line, &e.to_string());
+                line.push_str("}}");
+                vec![line]
+            },
+        }
+    }
+
+    /// Render an event reported to the game master as a single line
+    ///
+    pub fn render_event(self, event: &Value) -> String {
+        match self {
+            Self::Text => format!("event {}", event),
+            Self::Json => {
+                let mut line = String::new();
+                event.write_json(&mut line);
+                line
+            },
+        }
+    }
+}
+
+impl std::str::FromStr for Format {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+
+/// Data returned by a command
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(u64),
+    String(String),
+    List(Vec<Value>),
+    /// Named fields, presented in the given order
+    Record(Vec<(&'static str, Value)>),
+}
+
+impl Value {
+    /// Render the value as text lines
+    ///
+    /// `Null` yields no lines at all and lists yield one line per item. Record
+    /// fields are separated by spaces.
+    ///
+    pub fn text_lines(&self) -> Vec<String> {
+        match self {
+            Self::Null => Default::default(),
+            Self::List(items) => items.iter().map(ToString::to_string).collect(),
+            v => vec![v.to_string()],
+        }
+    }
+
+    /// Append the JSON representation of this value to a string
+    ///
+    pub fn write_json(&self, out: &mut String) {
+        match self {
+            Self::Null => out.push_str("null"),
+            Self::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+            Self::Number(n) => out.push_str(&n.to_string()),
+            Self::String(s) => write_json_str(out, s),
+            Self::List(items) => {
+                out.push('[');
+                items.iter().enumerate().for_each(|(n, v)| {
+                    if n > 0 {
+                        out.push(',')
+                    }
+                    v.write_json(out)
+                });
+                out.push(']');
+            },
+            Self::Record(fields) => {
+                out.push('{');
+                fields.iter().enumerate().for_each(|(n, (k, v))| {
+                    if n > 0 {
+                        out.push(',')
+                    }
+                    write_json_str(out, k);
+                    out.push(':');
+                    v.write_json(out)
+                });
+                out.push('}');
+            },
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let join = |f: &mut fmt::Formatter<'_>, items: &mut dyn Iterator<Item = &Value>| {
+            items.enumerate().try_for_each(|(n, v)| if n > 0 { write!(f, " {}", v) } else { v.fmt(f) })
+        };
+
+        match self {
+            Self::Null => Ok(()),
+            Self::Bool(b) => b.fmt(f),
+            Self::Number(n) => n.fmt(f),
+            Self::String(s) => s.fmt(f),
+            Self::List(items) => join(f, &mut items.iter()),
+            Self::Record(fields) => join(f, &mut fields.iter().map(|(_, v)| v)),
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Self::Bool(b)
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Self {
+        Self::Number(n)
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Self::String(s)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+}
+
+
+/// Append a string as a JSON string literal
+///
+pub fn write_json_str(out: &mut String, s: &str) {
+    out.push('"');
+    s.chars().for_each(|c| match c {
+        '"'  => out.push_str("\\\""),
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        c if c < ' ' || c == '\x7f' => out.push_str(&format!("\\u{:04x}", c as u32)),
+        c => out.push(c),
+    });
+    out.push('"');
+}
+
+
+/// Error code reported alongside a failed command
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Code {
+    /// The command word was not recognized
+    UnknownCommand,
+    /// An argument was missing or malformed
+    InvalidArgument,
+    /// The addressed room does not exist
+    NoSuchRoom,
+    /// The command was understood but could not be carried out
+    Failed,
+    /// The game master is not authenticated or lacks permission
+    Forbidden,
+}
+
+impl Code {
+    /// Retrieve the identifier reported to the game master
+    ///
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UnknownCommand    => "unknown_command",
+            Self::InvalidArgument   => "invalid_argument",
+            Self::NoSuchRoom        => "no_such_room",
+            Self::Failed            => "failed",
+            Self::Forbidden         => "forbidden",
+        }
+    }
+}
+
+
+/// Error resulting from a game master command
+///
+#[derive(Debug)]
+pub struct CommandError {
+    code: Code,
+    inner: WrappedErr,
+}
+
+impl CommandError {
+    /// Create a new error with the given code
+    ///
+    pub fn new(code: Code, inner: WrappedErr) -> Self {
+        Self {code, inner}
+    }
+
+    /// Retrieve the error code
+    ///
+    pub fn code(&self) -> Code {
+        self.code
+    }
+}
+
+impl From<WrappedErr> for CommandError {
+    fn from(inner: WrappedErr) -> Self {
+        Self::new(Code::Failed, inner)
+    }
+}
+
+impl std::error::Error for CommandError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.inner.source()
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
@@ -0,0 +1,382 @@
+//! Game master console tests
+
+use super::*;
+
+use auth::Role;
+
+
+#[quickcheck]
+fn json_string_roundtrip(input: String) -> bool {
+    let mut out = String::new();
+    reply::write_json_str(&mut out, &input);
+    !out.chars().any(|c| c < ' ') && unescape_json_str(&out).as_deref() == Some(input.as_str())
+}
+
+
+#[quickcheck]
+fn text_record_fields(fields: Vec<u64>) -> bool {
+    let expected: Vec<_> = fields.iter().map(ToString::to_string).collect();
+    let record = Value::Record(fields.into_iter().map(|n| ("n", n.into())).collect());
+    record.text_lines() == [expected.join(" ")]
+}
+
+
+#[test]
+fn render_replies() {
+    use error::{NoneError as N, WrappedErr as E};
+
+    let value = Value::List(vec![
+        Value::Record(vec![("num", 0.into()), ("name", "alice".into()), ("connected", true.into())]),
+        Value::Record(vec![("num", 1.into()), ("name", "b\"ob".into()), ("connected", false.into())]),
+    ]);
+    let err = CommandError::new(Code::NoSuchRoom, E::new("No such room", N));
+
+    assert_eq!(Format::Text.render(&Ok(value.clone())), ["0 alice true", "1 b\"ob false", "OK"]);
+    assert_eq!(Format::Text.render(&Ok(Value::Null)), ["OK"]);
+    assert_eq!(Format::Text.render(&Err(err)), ["No such room"]);
+    assert_eq!(
+        Format::Json.render(&Ok(value)),
+        [concat!(
+            r#"{"status":"ok","data":[{"num":0,"name":"alice","connected":true},"#,
+            r#"{"num":1,"name":"b\"ob","connected":false}],"error":null}"#,
+        )],
+    );
+    let err = CommandError::new(Code::NoSuchRoom, E::new("No such room", N));
+    assert_eq!(
+        Format::Json.render(&Err(err)),
+        [r#"{"status":"error","data":null,"error":{"code":"no_such_room","message":"No such room"}}"#],
+    );
+}
+
+
+#[tokio::test]
+async fn serve_json_format() -> Result<(), Box<dyn std::error::Error>> {
+    use io::{AsyncBufReadExt, AsyncWriteExt};
+
+    let registry = Arc::new(room::Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 2));
+    registry.create("main").await?;
+
+    let (console, client) = io::duplex(1024);
+    let (reader, writer) = io::split(console);
+    tokio::spawn(serve(reader, writer, registry, Session::new(None)));
+
+    let (client_read, mut client_write) = io::split(client);
+    let mut lines = io::BufReader::new(client_read).lines();
+
+    client_write.write_all(b"rooms\nformat json\nrooms\nget colours\nroom 7 status\nfoo\nformat xml\n").await?;
+    let mut replies = Vec::new();
+    for _ in 0..8 {
+        replies.push(lines.next_line().await?.ok_or("Console closed")?);
+    }
+
+    assert_eq!(replies, [
+        "1 main lobby 0",
+        "OK",
+        r#"{"status":"ok","data":null,"error":null}"#,
+        r#"{"status":"ok","data":[{"id":1,"name":"main","phase":"lobby","players":0}],"error":null}"#,
+        &format!(r#"{{"status":"ok","data":{},"error":null}}"#, crate::util::Palette::default()),
+        r#"{"status":"error","data":null,"error":{"code":"no_such_room","message":"No such room"}}"#,
+        r#"{"status":"error","data":null,"error":{"code":"unknown_command","message":"No such command, see 'help' for a list"}}"#,
+        r#"{"status":"error","data":null,"error":{"code":"invalid_argument","message":"Expected 'text' or 'json'"}}"#,
+    ]);
+    Ok(())
+}
+
+
+#[tokio::test]
+async fn serve_subscription() -> Result<(), Box<dyn std::error::Error>> {
+    use io::{AsyncBufReadExt, AsyncWriteExt};
+
+    let registry = Arc::new(room::Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 2));
+    registry.create("main").await?;
+
+    let (console, client) = io::duplex(1024);
+    let (reader, writer) = io::split(console);
+    tokio::spawn(serve(reader, writer, registry, Session::new(None)));
+
+    let (client_read, mut client_write) = io::split(client);
+    let mut lines = io::BufReader::new(client_read).lines();
+
+    client_write.write_all(b"format json\nsubscribe\nend\n").await?;
+    let mut replies = Vec::new();
+    for _ in 0..4 {
+        replies.push(lines.next_line().await?.ok_or("Console closed")?);
+    }
+
+    assert_eq!(replies, [
+        r#"{"status":"ok","data":null,"error":null}"#,
+        r#"{"status":"ok","data":null,"error":null}"#,
+        r#"{"status":"ok","data":null,"error":null}"#,
+        r#"{"event":"phase","room":1,"phase":"end"}"#,
+    ]);
+    Ok(())
+}
+
+
+#[quickcheck]
+fn editor_plain_lines(lines: Vec<String>) -> bool {
+    let lines: Vec<_> = lines.into_iter().map(|l| l.replace(['\r', '\n'], "")).collect();
+    let input = lines.iter().map(|l| l.clone() + "\r\n").collect::<String>();
+    let mut editor = editor::LineEditor::new(input.as_bytes(), Vec::new());
+    while futures::executor::block_on(editor.read()).unwrap_or(false) {}
+
+    let inputs: Vec<_> = std::iter::from_fn(|| editor.next_input()).collect();
+    inputs == lines.into_iter().map(Input::Line).collect::<Vec<_>>() && editor.finish().is_none()
+}
+
+
+#[tokio::test]
+async fn editor_interactive() -> Result<(), Box<dyn std::error::Error>> {
+    let input: &[u8] = b"abc\x7fd\x1b[D\x1b[DX\rxyz\x15\x1b[A\x1b[A\x1b[B\x1b[Bq\x1b[A\r\x03ki\t\x04";
+    let mut editor = editor::LineEditor::new(input, Vec::new());
+    editor.set_interactive(true);
+    while editor.read().await? {}
+
+    assert_eq!(editor.next_input(), Some(Input::Line("aXbd".into())));
+    assert_eq!(editor.next_input(), Some(Input::Line("aXbd".into())));
+    assert_eq!(editor.next_input(), Some(Input::Complete("ki".into())));
+    editor.complete("ki", &["kick".into()]);
+    assert_eq!(editor.next_input(), None);
+    assert_eq!(editor.finish(), Some("kick ".into()));
+    Ok(())
+}
+
+
+#[tokio::test]
+async fn editor_line_limit() -> Result<(), Box<dyn std::error::Error>> {
+    let input = vec![b'x'; 4096];
+    let mut editor = editor::LineEditor::new(input.as_ref(), Vec::new());
+    while editor.read().await? {}
+    assert_eq!(editor.next_input(), Some(Input::TooLong));
+
+    let input = [b"a".repeat(4096), b"\r".to_vec()].concat();
+    let mut editor = editor::LineEditor::new(input.as_ref(), Vec::new());
+    editor.set_interactive(true);
+    while editor.read().await? {}
+    match editor.next_input() {
+        Some(Input::Line(line)) => assert!(line.len() < input.len() - 1),
+        i => panic!("Unexpected input: {:?}", i),
+    }
+    Ok(())
+}
+
+
+#[tokio::test]
+async fn editor_auth_secret() -> Result<(), Box<dyn std::error::Error>> {
+    let input: &[u8] = b"auth secret\r\x1b[A";
+    let mut echo = Vec::new();
+    let mut editor = editor::LineEditor::new(input, &mut echo);
+    editor.set_interactive(true);
+    while editor.read().await? {}
+
+    assert_eq!(editor.next_input(), Some(Input::Line("auth secret".into())));
+    assert_eq!(editor.next_input(), None);
+    assert_eq!(editor.finish(), None);
+    editor.flush().await?;
+    drop(editor);
+    assert!(!String::from_utf8_lossy(&echo).contains("secret"));
+    Ok(())
+}
+
+
+#[tokio::test]
+async fn serve_help_and_completion() -> Result<(), Box<dyn std::error::Error>> {
+    use io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+
+    let registry = Arc::new(room::Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 2));
+    registry.create("main").await?;
+
+    let (console, client) = io::duplex(1024);
+    let (reader, writer) = io::split(console);
+    tokio::spawn(serve(reader, writer, registry, Session::new(None)));
+
+    let (client_read, mut client_write) = io::split(client);
+    let mut client_read = io::BufReader::new(client_read);
+
+    client_write.write_all(b"help kick\nhelp foo\nhelp\n").await?;
+    let mut replies = Vec::new();
+    for _ in 0..4 {
+        let mut line = String::new();
+        client_read.read_line(&mut line).await?;
+        replies.push(line);
+    }
+    assert_eq!(replies, [
+        "kick <player num>\n",
+        "Terminate the connection to the given player\n",
+        "OK\n",
+        "No such command, see 'help' for a list\n",
+    ]);
+    let mut listing = String::new();
+    while !listing.ends_with("\nOK\n") {
+        client_read.read_line(&mut listing).await?;
+    }
+    assert_eq!(listing.lines().count(), GLOBAL_COMMANDS.len() + ROOM_COMMANDS.len() + 1);
+
+    client_write.write_all(b"interactive on\r").await?;
+    let mut echo = [0; 256];
+    let len = client_read.read(&mut echo).await?;
+    assert_eq!(&echo[..len], b"OK\r\n\r\x1b[K> ");
+
+    client_write.write_all(b"set dia\t").await?;
+    let mut echo = Vec::new();
+    while !echo.ends_with(b"set diagonal ") {
+        echo.push(client_read.read_u8().await?);
+    }
+    Ok(())
+}
+
+
+#[test]
+fn parse_tokens() {
+    let tokens: Tokens = "# comment\n\nsecret full\n  peek read  \n".parse().expect("Could not parse tokens");
+    assert_eq!(tokens.role("secret"), Some(Role::Full));
+    assert_eq!(tokens.role("peek"), Some(Role::ReadOnly));
+    assert_eq!(tokens.role("secre"), None);
+    assert_eq!(tokens.role("full"), None);
+
+    assert!("secret\n".parse::<Tokens>().is_err());
+    assert!("secret admin\n".parse::<Tokens>().is_err());
+    assert!("# nothing\n".parse::<Tokens>().is_err());
+}
+
+
+#[tokio::test]
+async fn serve_authentication() -> Result<(), Box<dyn std::error::Error>> {
+    use io::{AsyncBufReadExt, AsyncWriteExt};
+
+    let registry = Arc::new(room::Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 2));
+    registry.create("main").await?;
+    let tokens = Arc::new("secret full\npeek read\n".parse()?);
+
+    let (console, client) = io::duplex(1024);
+    let (reader, writer) = io::split(console);
+    tokio::spawn(serve(reader, writer, registry, Session::new(Some(tokens))));
+
+    let (client_read, mut client_write) = io::split(client);
+    let mut lines = io::BufReader::new(client_read).lines();
+
+    client_write.write_all(concat!(
+        "format json\nrooms\nauth peek\nrooms\nroom 1 start\nauth secret\nroom 1 status\n",
+        "auth wrong\nauth wrong\nauth wrong\nrooms\n",
+    ).as_bytes()).await?;
+    let mut replies = Vec::new();
+    while let Some(line) = lines.next_line().await? {
+        replies.push(line);
+    }
+
+    assert_eq!(replies, [
+        r#"{"status":"ok","data":null,"error":null}"#,
+        r#"{"status":"error","data":null,"error":{"code":"forbidden","message":"Not authenticated, use 'auth <token>'"}}"#,
+        r#"{"status":"ok","data":"read","error":null}"#,
+        r#"{"status":"ok","data":[{"id":1,"name":"main","phase":"lobby","players":0}],"error":null}"#,
+        r#"{"status":"error","data":null,"error":{"code":"forbidden","message":"Permission denied"}}"#,
+        r#"{"status":"ok","data":"full","error":null}"#,
+        r#"{"status":"ok","data":"lobby","error":null}"#,
+        r#"{"status":"error","data":null,"error":{"code":"forbidden","message":"Invalid token"}}"#,
+        r#"{"status":"error","data":null,"error":{"code":"forbidden","message":"Invalid token"}}"#,
+        r#"{"status":"error","data":null,"error":{"code":"forbidden","message":"Invalid token"}}"#,
+    ]);
+    Ok(())
+}
+
+
+#[tokio::test]
+async fn http_requests() -> Result<(), Box<dyn std::error::Error>> {
+    let registry = Arc::new(room::Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 2));
+    registry.create("main").await?;
+
+    let status = http_request(&registry, "GET /status HTTP/1.1\r\n\r\n").await?;
+    assert_eq!(status, [
+        "HTTP/1.1 200 OK",
+        r#"{"status":"ok","data":{"rooms":[{"id":1,"name":"main","phase":"lobby","players":[]}]},"error":null}"#,
+    ]);
+
+    let set = "PUT /rooms/1/settings/virs HTTP/1.1\r\nContent-Length: 2\r\n\r\n42";
+    assert_eq!(http_request(&registry, set).await?[0], "HTTP/1.1 200 OK");
+    assert_eq!(
+        http_request(&registry, "GET /settings/virs HTTP/1.1\r\n\r\n").await?,
+        ["HTTP/1.1 200 OK", r#"{"status":"ok","data":42,"error":null}"#],
+    );
+
+    let set = "PUT /settings/virs HTTP/1.1\r\nContent-Length: 4\r\n\r\n4 2 ";
+    assert_eq!(http_request(&registry, set).await?[0], "HTTP/1.1 400 Bad Request");
+    assert_eq!(
+        http_request(&registry, "POST /rooms/7/start HTTP/1.1\r\n\r\n").await?[0],
+        "HTTP/1.1 404 Not Found",
+    );
+    assert_eq!(
+        http_request(&registry, "POST /rooms/1/subscribe HTTP/1.1\r\n\r\n").await?[0],
+        "HTTP/1.1 404 Not Found",
+    );
+    assert_eq!(http_request(&registry, "nonsense\r\n\r\n").await?[0], "HTTP/1.1 400 Bad Request");
+
+    let metrics = http_request(&registry, "GET /metrics HTTP/1.1\r\n\r\n").await?;
+    assert_eq!(metrics[0], "HTTP/1.1 200 OK");
+    assert!(metrics[1].contains("# TYPE dr_w_falls_players_connected gauge"));
+    Ok(())
+}
+
+
+#[tokio::test]
+async fn http_idle_client() -> Result<(), Box<dyn std::error::Error>> {
+    use io::AsyncWriteExt;
+
+    let registry = Arc::new(room::Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 2));
+    let (server, mut client) = io::duplex(1024);
+    client.write_all(b"GET /status HTTP/1.1\r\n").await?;
+
+    let res = tokio::time::timeout(
+        std::time::Duration::from_secs(10),
+        http::serve_connection(server, registry),
+    ).await?;
+    assert_eq!(res.err().map(|e| e.kind()), Some(io::ErrorKind::TimedOut));
+    Ok(())
+}
+
+
+/// Issue an HTTP request, returning the status line and body
+///
+async fn http_request(
+    registry: &Arc<room::Registry<rand_pcg::Pcg64Mcg>>,
+    request: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    use io::{AsyncReadExt, AsyncWriteExt};
+
+    let (server, mut client) = io::duplex(1024);
+    let server = tokio::spawn(http::serve_connection(server, registry.clone()));
+
+    client.write_all(request.as_bytes()).await?;
+    let mut response = String::new();
+    client.read_to_string(&mut response).await?;
+    server.await??;
+
+    let (head, body) = response.split_once("\r\n\r\n").ok_or("Malformed response")?;
+    let status = head.lines().next().ok_or("Missing status line")?;
+    Ok(vec![status.to_string(), body.trim_end().to_string()])
+}
+
+
+/// Decode a JSON string literal
+///
+fn unescape_json_str(literal: &str) -> Option<String> {
+    let mut chars = literal.strip_prefix('"')?.strip_suffix('"')?.chars();
+    let mut res = String::new();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                'u' => {
+                    let code: String = chars.by_ref().take(4).collect();
+                    std::char::from_u32(u32::from_str_radix(&code, 16).ok()?)?
+                },
+                c => c,
+            },
+            '"' => return None,
+            c => c,
+        };
+        res.push(c);
+    }
+    Some(res)
+}
@@ -1,23 +1,27 @@
 //! Display rendering utilities
 
 mod area;
+mod bracket;
 mod commands;
 mod display;
 mod dynamic_text;
 mod field;
 mod input;
 mod scores;
+mod screen;
 mod static_text;
+mod theme;
 
 #[cfg(test)]
 pub mod tests;
 
 
-pub use area::Area;
+pub use area::{Area, Repaint};
+pub use bracket::{Bracket, Match as BracketMatch};
 pub use commands::DrawHandle;
-pub use display::Display;
+pub use display::{Display, RenderOptions};
 pub use dynamic_text::DynamicText;
-pub use field::{FieldUpdater, PlayField};
+pub use field::{FieldUpdater, Glyphs, PlayField};
 pub use input::LineInput;
 pub use scores::{Entry as ScoreBoardEntry, ScoreBoard};
 pub use static_text::StaticText;
@@ -45,6 +45,18 @@ pub trait Entity {
 }
 
 
+/// Placed entity which may be repainted from its current state
+///
+pub trait Repaint: Sync {
+    /// Create instructions for repainting the entity
+    ///
+    /// The returned draw commands reproduce everything the entity drew so far
+    /// which is still visible, e.g. after a terminal was garbled.
+    ///
+    fn repaint(&self) -> Vec<DrawCommand<'static>>;
+}
+
+
 /// Instructions for drawing the entity's initial state
 ///
 #[derive(Debug)]
@@ -0,0 +1,188 @@
+//! Tournament bracket entity
+
+use tokio::io::AsyncWrite;
+
+use crate::player;
+use super::area;
+use super::commands::{self, DrawCommand as DC, DrawHandle};
+
+
+/// Representation of a tournament bracket
+///
+/// The bracket is displayed as a list of stages, each listing its matches. If
+/// not all lines fit, the earliest stages are omitted. If not even the latest
+/// stage fits, only its matches starting from the first undecided one are
+/// listed.
+///
+/// An instance of this type itself is useless unless it is placed in an `Area`.
+///
+pub struct Bracket {
+    max_rows: u16,
+    cols: u16,
+}
+
+impl Bracket {
+    /// Create a new bracket display covering the given number of rows and columns
+    ///
+    pub fn new(max_rows: u16, cols: u16) -> Self {
+        Self {max_rows, cols}
+    }
+}
+
+impl area::Entity for Bracket {
+    type PlacedEntity = BracketUpdater;
+
+    fn rows(&self) -> u16 {
+        self.max_rows
+    }
+
+    fn cols(&self) -> u16 {
+        self.cols
+    }
+
+    fn init(&self, _: (u16, u16)) -> area::PlacedInit<'_> {
+        Vec::new().into()
+    }
+
+    fn place(self, (base_row, base_col): (u16, u16)) -> Self::PlacedEntity {
+        BracketUpdater {base_row, base_col, rows: self.max_rows, cols: self.cols, cmds: Default::default()}
+    }
+}
+
+
+/// Handle for updating a bracket entity
+///
+/// The updater remembers the draw commands of the last update for repainting
+/// the bracket.
+///
+pub struct BracketUpdater {
+    base_row: u16,
+    base_col: u16,
+    rows: u16,
+    cols: u16,
+    cmds: Vec<DC<'static>>,
+}
+
+impl BracketUpdater {
+    /// Update the bracket
+    ///
+    /// The bracket will be redrawn from the given stages. Winners of matches
+    /// are displayed in bold, defeated players faint. Players matching the
+    /// predicate provided by `highlight` will be underlined.
+    ///
+    pub async fn update<'e, M: Match + 'e>(
+        &mut self,
+        draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
+        stages: impl IntoIterator<Item = &'e [M]>,
+        highlight: impl Fn(&player::Tag) -> bool,
+    ) -> std::io::Result<()> {
+        use futures::stream::iter;
+        use futures::SinkExt;
+
+        use commands::{Intensity, SGR, SinkProxy};
+
+        let cols = self.cols as usize;
+
+        // Each line is a list of text segments with the intensity and underline
+        // with which they are to be displayed. Lines are grouped by stage, each
+        // group starting with the stage's header. For each stage, we also keep
+        // track of the line of the first undecided match.
+        type Line = Vec<(String, Option<Intensity>, bool)>;
+        let mut groups: Vec<(Vec<Line>, Option<usize>)> = Vec::new();
+        for (n, stage) in stages.into_iter().enumerate() {
+            let mut lines = vec![vec![(format!("Stage {}", n + 1), None, true)]];
+            let mut current = None;
+            for m in stage {
+                if m.winner().is_none() && current.is_none() {
+                    current = Some(lines.len());
+                }
+                let players = m.players();
+                let name_width = (cols.saturating_sub(1 + SEPARATOR.len() * players.len().saturating_sub(1)))
+                    / players.len().max(1);
+                let mut line = vec![(" ".to_string(), None, false)];
+                for (i, p) in players.iter().enumerate() {
+                    if i > 0 {
+                        line.push((SEPARATOR.to_string(), None, false));
+                    }
+                    let intensity = match m.winner() {
+                        Some(w) if w == p   => Some(Intensity::Bold),
+                        Some(_)             => Some(Intensity::Faint),
+                        None                => None,
+                    };
+                    let name: String = p.name().chars().take(name_width).collect();
+                    line.push((name, intensity, highlight(p)));
+                }
+                lines.push(line);
+            }
+            groups.push((lines, current));
+        }
+
+        // We display as many of the latest stages as fit. If not even the
+        // latest stage fits, we display its header followed by its matches,
+        // starting with the first undecided one.
+        let max_lines = self.rows as usize;
+        let mut lines = Vec::new();
+        while let Some((stage, current)) = groups.pop() {
+            if lines.len() + stage.len() <= max_lines {
+                lines.splice(0..0, stage);
+            } else {
+                if lines.is_empty() {
+                    let skip = current
+                        .unwrap_or(stage.len())
+                        .saturating_sub(1)
+                        .min(stage.len().saturating_sub(max_lines));
+                    let mut stage = stage.into_iter();
+                    lines.extend(stage.next());
+                    lines.extend(stage.skip(skip));
+                }
+                break
+            }
+        }
+
+        let mut rows = (self.base_row..self.base_row + self.rows).map(|r| DC::SetPos(r, self.base_col));
+        let mut cmds = Vec::new();
+        for (pos, line) in rows.by_ref().zip(lines) {
+            cmds.push(pos);
+            let mut len = 0;
+            for (text, intensity, underline) in line {
+                len += text.chars().count();
+                cmds.push(intensity.into());
+                cmds.push(SGR::Underline(underline).into());
+                cmds.push(text.into());
+            }
+            cmds.push(SGR::Reset.into());
+            cmds.push(" ".repeat(cols.saturating_sub(len)).into());
+        }
+        for pos in rows {
+            cmds.push(pos);
+            cmds.push(" ".repeat(cols).into());
+        }
+
+        self.cmds = cmds;
+        draw_handle.as_sink().send_all(&mut iter(self.cmds.iter().cloned().map(Ok).collect::<Vec<_>>())).await
+    }
+}
+
+impl area::Repaint for BracketUpdater {
+    fn repaint(&self) -> Vec<DC<'static>> {
+        self.cmds.clone()
+    }
+}
+
+
+/// A match within a bracket
+///
+pub trait Match {
+    /// Players participating in the match
+    ///
+    fn players(&self) -> &[player::Tag];
+
+    /// The winner of the match, if it was decided
+    ///
+    fn winner(&self) -> Option<&player::Tag>;
+}
+
+
+/// Separator placed between the players of a match
+///
+const SEPARATOR: &str = " vs ";
@@ -1,12 +1,14 @@
 //! Draw commands and related types
 
 use std::borrow::Cow;
+use std::fmt;
 
 use tokio::io::AsyncWrite;
 use tokio::sync::OwnedMutexGuard;
 use tokio_util::codec;
 
 use crate::util;
+use super::screen;
 
 #[cfg(test)]
 use quickcheck::{Arbitrary, Gen};
@@ -19,7 +21,7 @@ use quickcheck::{Arbitrary, Gen};
 /// to code outside the `display` module.
 ///
 pub struct DrawHandle<'a, W: AsyncWrite + Send + Unpin + 'static> {
-    write: Option<OwnedMutexGuard<codec::FramedWrite<W, ANSIEncoder>>>,
+    write: Option<OwnedMutexGuard<screen::Screen<W>>>,
     termination_seq: &'a [DrawCommand<'static>],
 }
 
@@ -33,7 +35,9 @@ impl<'a, W: AsyncWrite + Send + Unpin + 'static> Drop for DrawHandle<'a, W> {
         let cmds: Vec<_> = self.termination_seq.iter().cloned().map(Ok).collect();
         let mut write = self.write.take().expect("Write in DrawHandle must not be None");
         tokio::runtime::Handle::current().spawn(async move {
-            write.send_all(&mut iter(cmds)).await.or_warn("Failed to send termination sequence")
+            let start = std::time::Instant::now();
+            write.send_all(&mut iter(cmds)).await.or_warn("Failed to send termination sequence");
+            crate::metrics::METRICS.flush_latency.observe(start.elapsed().as_secs_f64());
         });
     }
 }
@@ -45,7 +49,7 @@ impl<'a, W: AsyncWrite + Send + Unpin + 'static> Drop for DrawHandle<'a, W> {
 /// it will issue the given termination sequence.
 ///
 pub fn draw_handle<'a, W: AsyncWrite + Send + Unpin>(
-    write: OwnedMutexGuard<codec::FramedWrite<W, ANSIEncoder>>,
+    write: OwnedMutexGuard<screen::Screen<W>>,
     termination_seq: &'a [DrawCommand<'static>],
 ) -> DrawHandle<'a, W> {
     DrawHandle {write: Some(write), termination_seq}
@@ -68,7 +72,7 @@ pub trait SinkProxy {
 }
 
 impl<'a, W: AsyncWrite + Send + Unpin> SinkProxy for DrawHandle<'a, W> {
-    type Sink = codec::FramedWrite<W, ANSIEncoder>;
+    type Sink = screen::Screen<W>;
 
     fn as_sink(&mut self) -> &mut Self::Sink {
         self.write.as_mut().expect("Write in DrawHandle must not be None")
@@ -101,7 +105,7 @@ impl codec::Encoder<DrawCommand<'_>> for ANSIEncoder {
         match cmd {
             DC::ClearScreen    => dst.put_slice(b"\x1b[2J"),
             DC::SetPos(r, c)   => dst.put_slice(format!("\x1b[{};{}H", r + 1, c + 1).as_bytes()),
-            DC::Format(param)  => dst.put_slice(format!("\x1b[{}m", param.code()).as_bytes()),
+            DC::Format(param)  => dst.put_slice(format!("\x1b[{}m", param).as_bytes()),
             DC::Text(s)        => dst.put_slice(s.as_bytes()),
             DC::ShowCursor(true)    => dst.put_slice(b"\x1b[?25h"),
             DC::ShowCursor(false)   => dst.put_slice(b"\x1b[?25l"),
@@ -215,10 +219,14 @@ pub enum SGR {
     ///
     /// A value of `None` will reset the colour to the default.
     BGColour(Option<(Colour, Brightness)>),
+    /// Set the foreground colour to a 256-colour palette entry or an RGB value
+    FGExtended(ExtendedColour),
+    /// Set the background colour to a 256-colour palette entry or an RGB value
+    BGExtended(ExtendedColour),
 }
 
 impl SGR {
-    /// Determine the code number for the SGR parameter
+    /// Determine the (leading) code number for the SGR parameter
     ///
     fn code(&self) -> u8 {
         use Intensity as Int;
@@ -238,6 +246,17 @@ impl SGR {
             Self::FGColour(None)              => 39,
             Self::BGColour(Some((col, br)))   => 40 + col.code_off() + br.code_off(),
             Self::BGColour(None)              => 49,
+            Self::FGExtended(_)               => 38,
+            Self::BGExtended(_)               => 48,
+        }
+    }
+}
+
+impl fmt::Display for SGR {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FGExtended(c) | Self::BGExtended(c) => write!(f, "{};{}", self.code(), c),
+            _ => write!(f, "{}", self.code()),
         }
     }
 }
@@ -283,6 +302,8 @@ impl Arbitrary for SGR {
             Self::Strike(Arbitrary::arbitrary(g)),
             Self::FGColour(Arbitrary::arbitrary(g)),
             Self::BGColour(Arbitrary::arbitrary(g)),
+            Self::FGExtended(Arbitrary::arbitrary(g)),
+            Self::BGExtended(Arbitrary::arbitrary(g)),
         ];
         *g.choose(&opts).unwrap()
     }
@@ -295,6 +316,8 @@ impl Arbitrary for SGR {
             Self::Strike(v)     => Box::new(v.shrink().map(Self::Strike)),
             Self::FGColour(v)   => Box::new(v.shrink().map(Self::FGColour)),
             Self::BGColour(v)   => Box::new(v.shrink().map(Self::BGColour)),
+            Self::FGExtended(v) => Box::new(v.shrink().map(Self::FGExtended)),
+            Self::BGExtended(v) => Box::new(v.shrink().map(Self::BGExtended)),
             _ => Box::new(std::iter::empty()),
         }
     }
@@ -353,9 +376,12 @@ impl Colour {
 impl From<util::Colour> for Colour {
     fn from(colour: util::Colour) -> Self {
         match colour {
-            util::Colour::Red    => Self::Red,
-            util::Colour::Yellow => Self::Yellow,
-            util::Colour::Blue   => Self::Blue,
+            util::Colour::Red     => Self::Red,
+            util::Colour::Yellow  => Self::Yellow,
+            util::Colour::Blue    => Self::Blue,
+            util::Colour::Green   => Self::Green,
+            util::Colour::Magenta => Self::Magenta,
+            util::Colour::Cyan    => Self::Cyan,
         }
     }
 }
@@ -368,6 +394,9 @@ impl std::convert::TryFrom<Colour> for util::Colour {
             Colour::Red     => Ok(Self::Red),
             Colour::Yellow  => Ok(Self::Yellow),
             Colour::Blue    => Ok(Self::Blue),
+            Colour::Green   => Ok(Self::Green),
+            Colour::Magenta => Ok(Self::Magenta),
+            Colour::Cyan    => Ok(Self::Cyan),
             c               => Err(c),
         }
     }
@@ -390,6 +419,43 @@ impl Arbitrary for Colour {
 }
 
 
+/// Representation of colours beyond the basic ones
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExtendedColour {
+    /// Entry of the 256-colour palette
+    Indexed(u8),
+    /// 24-bit RGB value
+    Rgb(u8, u8, u8),
+}
+
+impl fmt::Display for ExtendedColour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Indexed(n)    => write!(f, "5;{}", n),
+            Self::Rgb(r, g, b)  => write!(f, "2;{};{};{}", r, g, b),
+        }
+    }
+}
+
+#[cfg(test)]
+impl Arbitrary for ExtendedColour {
+    fn arbitrary(g: &mut Gen) -> Self {
+        if bool::arbitrary(g) {
+            Self::Indexed(Arbitrary::arbitrary(g))
+        } else {
+            Self::Rgb(Arbitrary::arbitrary(g), Arbitrary::arbitrary(g), Arbitrary::arbitrary(g))
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        match self {
+            Self::Indexed(n)    => Box::new(n.shrink().map(Self::Indexed)),
+            Self::Rgb(r, g, b)  => Box::new((*r, *g, *b).shrink().map(|(r, g, b)| Self::Rgb(r, g, b))),
+        }
+    }
+}
+
 
 /// Representation of brightness
 ///
@@ -4,10 +4,10 @@ use std::sync::Arc;
 
 use tokio::io::AsyncWrite;
 use tokio::sync::Mutex;
-use tokio_util::codec::FramedWrite;
 
 use super::area;
 use super::commands::{self, DrawCommand, DrawHandle, SGR};
+use super::screen::Screen;
 
 
 /// Representation of a display
@@ -16,10 +16,11 @@ use super::commands::{self, DrawCommand, DrawHandle, SGR};
 /// terminal.
 ///
 pub struct Display<W: AsyncWrite + Send + Unpin + 'static> {
-    write: Arc<Mutex<FramedWrite<W, commands::ANSIEncoder>>>,
+    write: Arc<Mutex<Screen<W>>>,
     rows: u16,
     cols: u16,
     termination_seq: [DrawCommand<'static>; 3],
+    options: RenderOptions,
 }
 
 impl<W: AsyncWrite + Send + Unpin + 'static> Display<W> {
@@ -31,13 +32,13 @@ impl<W: AsyncWrite + Send + Unpin + 'static> Display<W> {
     /// second to last row will host the resting position.
     ///
     pub fn new(write: W, rows: u16, cols: u16) -> Self {
-        let write = Arc::new(FramedWrite::new(write, commands::ANSIEncoder::new()).into());
+        let write = Arc::new(Screen::new(write, rows, cols).into());
         let termination_seq = [
             SGR::Reset.into(),
             DrawCommand::SetPos(rows.saturating_sub(2), 0),
             DrawCommand::ShowCursor(true),
         ];
-        Self {write, rows, cols, termination_seq}
+        Self {write, rows, cols, termination_seq, options: Default::default()}
     }
 
     /// Retrieve an area covering the non-reserved portion of the display
@@ -63,7 +64,8 @@ impl<W: AsyncWrite + Send + Unpin + 'static> Display<W> {
     /// Retrieve a star handle for updating screen contents
     ///
     /// The returned handle may be used to update items placed on a previously
-    /// retrieved area for the same display.
+    /// retrieved area for the same display. Text drawn via the handle will be
+    /// formatted according to the display's theme unless specified otherwise.
     ///
     pub async fn handle(&mut self) -> std::io::Result<DrawHandle<'_, W>> {
         use futures::SinkExt;
@@ -74,7 +76,32 @@ impl<W: AsyncWrite + Send + Unpin + 'static> Display<W> {
             self.write.clone().lock_owned().await,
             self.termination_seq.as_ref(),
         );
-        handle.as_sink().send(DrawCommand::ShowCursor(false)).await.map(|_| handle)
+        let cmds = [Ok(DrawCommand::ShowCursor(false)), Ok(self.options.theme.text().into())];
+        handle.as_sink().send_all(&mut futures::stream::iter(cmds)).await.map(|_| handle)
+    }
+
+    /// Redraw the entire display
+    ///
+    /// The screen is cleared and all contents currently placed are drawn
+    /// again. This may be used for restoring a garbled terminal. The given
+    /// `entities` repaint themselves from their current state. Anything else,
+    /// e.g. static text, is restored from the screen buffer.
+    ///
+    pub async fn redraw(&mut self, entities: &[&dyn area::Repaint]) -> std::io::Result<()> {
+        use futures::SinkExt;
+
+        use commands::SinkProxy;
+
+        let text = self.options.theme.text();
+        let mut handle = self.handle().await?;
+        handle.as_sink().invalidate();
+
+        let cmds: Vec<_> = entities
+            .iter()
+            .flat_map(|e| vec![SGR::Reset.into(), text.into()].into_iter().chain(e.repaint()))
+            .map(Ok)
+            .collect();
+        handle.as_sink().send_all(&mut futures::stream::iter(cmds)).await
     }
 
     /// Retrieve the number of rows
@@ -90,5 +117,36 @@ impl<W: AsyncWrite + Send + Unpin + 'static> Display<W> {
     pub fn cols(&self) -> u16 {
         self.cols
     }
+
+    /// Retrieve the rendering options chosen for this display
+    ///
+    pub fn options(&self) -> RenderOptions {
+        self.options
+    }
+
+    /// Retrieve a mutable reference to this display's rendering options
+    ///
+    /// Changes only affect entities placed afterwards.
+    ///
+    pub fn options_mut(&mut self) -> &mut RenderOptions {
+        &mut self.options
+    }
+}
+
+
+/// Rendering options of a display
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct RenderOptions {
+    /// Glyphs used for capsule elements and viruses
+    pub glyphs: super::field::Glyphs,
+    /// Colour theme
+    pub theme: super::theme::Theme,
+    /// Whether to draw using non-ASCII characters
+    ///
+    /// If set, the output includes UTF-8 encoded box-drawing characters and
+    /// block elements.
+    ///
+    pub unicode: bool,
 }
 
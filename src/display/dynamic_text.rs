@@ -47,25 +47,28 @@ impl area::Entity for DynamicText {
     }
 
     fn place(self, (base_row, base_col): (u16, u16)) -> Self::PlacedEntity {
-        TextUpdater {base_row, base_col, rows: self.rows, cols: self.cols}
+        TextUpdater {base_row, base_col, rows: self.rows, cols: self.cols, lines: Default::default()}
     }
 }
 
 
 /// Handle for updating a specific text field
 ///
+/// The updater remembers the current contents for repainting the text field.
+///
 pub struct TextUpdater {
     base_row: u16,
     base_col: u16,
     rows: NonZeroU16,
     cols: NonZeroU16,
+    lines: Vec<String>,
 }
 
 impl TextUpdater {
     /// Clear the entire field
     ///
     pub async fn clear(
-        &self,
+        &mut self,
         draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
     ) -> std::io::Result<()> {
         self.update(draw_handle, std::iter::empty::<&'static str>()).await
@@ -80,26 +83,22 @@ impl TextUpdater {
     /// not contain `'\r'` or `'\n'`.
     ///
     pub async fn update(
-        &self,
+        &mut self,
         draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
         lines: impl IntoIterator<Item = impl std::fmt::Display>,
     ) -> std::io::Result<()> {
-        use std::iter::once;
-
         use futures::SinkExt;
         use futures::stream::iter;
 
         use commands::SinkProxy;
 
-        let mut rows = self.row_pos();
-
-        let mut cmds: Vec<_> = rows
-            .by_ref()
-            .zip(lines)
-            .flat_map(|(p, l)| once(p).chain(once(format!("{0:^1$}", l, self.cols.get() as usize).into())))
-            .map(Ok)
+        let cols = self.cols.get() as usize;
+        self.lines = lines
+            .into_iter()
+            .take(self.rows.get() as usize)
+            .map(|l| format!("{0:^1$}", l, cols))
             .collect();
-        cmds.extend(rows.flat_map(|p| once(p).chain(self.empty_row())).map(Ok));
+        let cmds: Vec<_> = self.cmds().into_iter().map(Ok).collect();
         draw_handle.as_sink().send_all(&mut iter(cmds)).await
     }
 
@@ -112,20 +111,38 @@ impl TextUpdater {
     /// not contain `'\r'` or `'\n'`.
     ///
     pub async fn update_single(
-        &self,
+        &mut self,
         draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
         line: impl std::fmt::Display,
     ) -> std::io::Result<()> {
         self.update(draw_handle, std::iter::once(line)).await
     }
 
+    /// Generate draw commands for the current contents
+    ///
+    /// Rows for which there is no content are filled with space characters.
+    ///
+    fn cmds(&self) -> Vec<DC<'static>> {
+        use std::iter::once;
+
+        let mut rows = self.row_pos();
+
+        let mut cmds: Vec<_> = rows
+            .by_ref()
+            .zip(self.lines.iter())
+            .flat_map(|(p, l)| once(p).chain(once(l.clone().into())))
+            .collect();
+        cmds.extend(rows.flat_map(|p| once(p).chain(self.empty_row())));
+        cmds
+    }
+
     /// Generate draw commands setting the cursor to rows' starting position
     ///
     /// The returned iterator will yield `SetPos` draw commands with coordniates
     /// of the text field rows' starting positions. The positions are yielded
     /// ordered from the top to bottom row.
     ///
-    fn row_pos(&self) -> impl Iterator<Item = DC> {
+    fn row_pos(&self) -> impl Iterator<Item = DC<'static>> {
         let base_row = self.base_row;
         let base_col = self.base_col;
         (0..self.rows.get()).map(move |r| DC::SetPos(r + base_row, base_col))
@@ -133,8 +150,13 @@ impl TextUpdater {
 
     /// Generate draw commands for filling a row with space characters
     ///
-    fn empty_row(&self) -> impl Iterator<Item = DC> {
+    fn empty_row(&self) -> impl Iterator<Item = DC<'static>> {
         std::iter::repeat(" ".into()).take(self.cols.get() as usize)
     }
 }
 
+impl area::Repaint for TextUpdater {
+    fn repaint(&self) -> Vec<DC<'static>> {
+        self.cmds()
+    }
+}
@@ -1,25 +1,60 @@
 //! Play field entity
 
+use std::collections::HashMap;
+
 use futures::SinkExt;
 use tokio::io::AsyncWrite;
 
 use crate::util;
 use super::area;
-use super::commands::{Colour, DrawCommand as DC, DrawHandle, SinkProxy};
+use super::commands::{DrawCommand as DC, DrawHandle, SinkProxy};
+use super::theme::Theme;
 
 
 /// Representation of a play field entity
 ///
 /// An instance of this type itself is useless unless it is placed in an `Area`.
 ///
+/// If `effects` are enabled, eliminations are animated and chains are
+/// indicated. Otherwise, eliminated tiles are cleared immediately.
+///
+/// By default, the field is drawn using only ASCII characters. Optionally, the
+/// bottle may be drawn using box-drawing characters and capsule elements and
+/// viruses using block elements. Capsule elements bound to one another are
+/// then drawn as halves of a pill.
+///
 #[derive(Default)]
-pub struct PlayField;
+pub struct PlayField {
+    dimensions: util::Dimensions,
+    effects: bool,
+    glyphs: Glyphs,
+    theme: Theme,
+    unicode: bool,
+}
 
 impl PlayField {
-    /// Create a new play field
+    /// Create a new play field with the given dimensions
+    ///
+    pub fn new(dimensions: util::Dimensions, effects: bool) -> Self {
+        Self {dimensions, effects, glyphs: Default::default(), theme: Default::default(), unicode: false}
+    }
+
+    /// Change the glyphs used for capsule elements and viruses
+    ///
+    pub fn glyphs(self, glyphs: Glyphs) -> Self {
+        Self {glyphs, ..self}
+    }
+
+    /// Change the colour theme
+    ///
+    pub fn theme(self, theme: Theme) -> Self {
+        Self {theme, ..self}
+    }
+
+    /// Change whether the field is drawn using non-ASCII characters
     ///
-    pub fn new() -> Self {
-        Self {}
+    pub fn unicode(self, unicode: bool) -> Self {
+        Self {unicode, ..self}
     }
 }
 
@@ -27,140 +62,215 @@ impl area::Entity for PlayField {
     type PlacedEntity = FieldUpdater;
 
     fn rows(&self) -> u16 {
-        util::FIELD_HEIGHT as u16 + 3
+        self.dimensions.height() as u16 + 3
     }
 
     fn cols(&self) -> u16 {
-        2 * util::FIELD_WIDTH as u16 + 2
+        2 * self.dimensions.width() as u16 + 2
     }
 
-    fn init(&self, (base_row, base_col): (u16, u16)) -> area::PlacedInit {
-        let inlet = "/    \\";
-        let inlet_col = util::FIELD_WIDTH as u16 - (inlet.len() as u16 / 2);
-
-        // Upper part of inlet
-        let mut res = vec![
-            DC::SetPos(base_row, base_col + 1 + inlet_col),
-            "\\    /".into(),
-        ];
-
-        let left_wall = base_col;
-        let right_wall = base_col + 1 + 2*(util::FIELD_WIDTH as u16);
-
-        let element_top_row = base_row + 2;
-
-        // Bottle ceiling, with lower part of inlet
-        res.push(DC::SetPos(base_row + 1, base_col + 1));
-        res.extend((0..inlet_col).map(|_| "_".into()));
-        res.push(inlet.into());
-        res.extend(((inlet_col + inlet.len() as u16)..(2 * util::FIELD_WIDTH as u16)).map(|_| "_".into()));
-        res.push(DC::SetPos(element_top_row, base_col));
-        res.push("/".into());
-        res.push(DC::SetPos(element_top_row, right_wall));
-        res.push("\\".into());
-
-        // Left and right wall
-        (1..util::FIELD_HEIGHT.into())
-            .map(|row| row + element_top_row)
-            .for_each(|row| res.extend([
-                DC::SetPos(row, left_wall),
-                "|".into(),
-                DC::SetPos(row, right_wall),
-                "|".into(),
-            ].iter().cloned()));
-
-        // Bottle floor
-        res.push(DC::SetPos(base_row + 2 + util::FIELD_HEIGHT as u16, base_col));
-        res.push("\\".into());
-        res.extend((0..util::FIELD_WIDTH).map(|_| "__".into()));
-        res.push("/".into());
-
-        res.into()
+    fn init(&self, pos: (u16, u16)) -> area::PlacedInit {
+        bottle_cmds(self.dimensions, self.theme, self.unicode, pos).into()
     }
 
     fn place(self, (base_row, base_col): (u16, u16)) -> Self::PlacedEntity {
-        FieldUpdater {base_row, base_col}
+        FieldUpdater {
+            base_row,
+            base_col,
+            dimensions: self.dimensions,
+            effects: self.effects,
+            glyphs: self.glyphs,
+            theme: self.theme,
+            unicode: self.unicode,
+            tiles: Default::default(),
+            next: Default::default(),
+            chain: Default::default(),
+        }
     }
 }
 
 
 /// Handle for updating the play field entity
 ///
+/// The updater remembers the contents of the field's tiles, the next capsule
+/// elements and the chain indication for repainting the field.
+///
 pub struct FieldUpdater {
     base_row: u16,
     base_col: u16,
+    dimensions: util::Dimensions,
+    effects: bool,
+    glyphs: Glyphs,
+    theme: Theme,
+    unicode: bool,
+    tiles: HashMap<util::Position, [DC<'static>; 2]>,
+    next: Vec<DC<'static>>,
+    chain: String,
 }
 
 impl FieldUpdater {
     /// Place viruses in the field
     ///
     /// For each of the items in `viruses`, one virus will be placed in the
-    /// field, at the given position and with the given colour.
+    /// field, at the given position and with the given colour. The virus' glyph
+    /// is determined by `vir_sym` and the field's glyph set.
     ///
     pub async fn place_viruses(
-        &self,
+        &mut self,
         draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
         viruses: impl IntoIterator<Item=(util::Position, util::Colour)>,
         vir_sym: VirusSym,
     ) -> std::io::Result<()> {
-        use std::iter::once;
-
-        use futures::stream::iter;
-
-        let cmds: Vec<_> = viruses.into_iter().flat_map(|(pos, col)|
-            once(self.transform(pos))
-                .chain(once(Colour::from(col).into()))
-                .chain(once(vir_sym.symbol().into()))
-        ).map(Ok).collect();
-        draw_handle.as_sink().send_all(&mut iter(cmds)).await
+        let tiles = viruses
+            .into_iter()
+            .map(|(pos, col)| (pos, Some([self.theme.virus(col).into(), self.virus_glyph(col, vir_sym).into()])))
+            .collect();
+        self.draw_tiles(draw_handle, tiles).await
     }
 
     /// Place the next capsule elements in the appropriate position
     ///
     pub async fn place_next_elements(
-        &self,
+        &mut self,
         draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
-        capsule: &[util::Colour; 2],
+        capsule: &[crate::field::Appearance; 2],
     ) -> std::io::Result<()> {
         let row = self.base_row + 1;
-        let col = self.base_col + 1 + util::FIELD_WIDTH as u16 - 2;
-
-        let cmds = [
-            Ok(DC::SetPos(row, col)),
-            Ok(Colour::from(capsule[0]).into()),
-            Ok("()".into()),
-            Ok(Colour::from(capsule[1]).into()),
-            Ok("()".into()),
-        ];
+        let col = self.base_col + 1 + self.dimensions.width() as u16 - 2;
+
+        let [a, b] = self.element_cmds(capsule[0]);
+        let [c, d] = self.element_cmds(capsule[1]);
+        self.next = vec![DC::SetPos(row, col), a, b, c, d];
+        let cmds: Vec<_> = self.next.iter().cloned().map(Ok).collect();
         draw_handle.as_sink().send_all(&mut futures::stream::iter(cmds)).await
     }
 
     /// Process field updates
     ///
     /// Each item in `updates` will be processed in order: if the update carries
-    /// a colour, a capsule element of the given colour will be placed at the
-    /// given position. Otherwise, any element at the given position will be
-    /// erased.
+    /// an appearance, a capsule element looking accordingly will be placed at
+    /// the given position. Otherwise, any element at the given position will
+    /// be erased.
     ///
     pub async fn update(
-        &self,
+        &mut self,
         draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
         updates: impl IntoIterator<Item=crate::field::Update>,
     ) -> std::io::Result<()> {
-        use std::iter::once;
+        let tiles = updates
+            .into_iter()
+            .map(|(pos, appearance)| (pos, appearance.map(|a| self.element_cmds(a))))
+            .collect();
+        self.draw_tiles(draw_handle, tiles).await
+    }
+
+    /// Retrieve the number of frames of the elimination animation
+    ///
+    /// If effects are disabled, this function returns `0`.
+    ///
+    pub fn explosion_frames(&self) -> usize {
+        if self.effects {
+            EXPLOSION_SYMS.len()
+        } else {
+            0
+        }
+    }
+
+    /// Draw a frame of the elimination animation
+    ///
+    /// The symbol for the given `frame` will be drawn at each of the given
+    /// positions. Frames past the last one will clear the tiles.
+    ///
+    pub async fn explode(
+        &mut self,
+        draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
+        positions: impl IntoIterator<Item=util::Position>,
+        frame: usize,
+    ) -> std::io::Result<()> {
+        let tile = EXPLOSION_SYMS.get(frame).map(|sym| [self.theme.text().into(), (*sym).into()]);
+        let tiles = positions.into_iter().map(|pos| (pos, tile.clone())).collect();
+        self.draw_tiles(draw_handle, tiles).await
+    }
+
+    /// Indicate the current chain
+    ///
+    /// Chains of at least two rows are indicated as "+N" in the inlet. For
+    /// shorter chains, any indication is removed. If effects are disabled, this
+    /// function does nothing.
+    ///
+    pub async fn show_chain(
+        &mut self,
+        draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
+        chain: u32,
+    ) -> std::io::Result<()> {
+        if !self.effects {
+            return Ok(())
+        }
 
-        use futures::stream::iter;
+        self.chain = if chain >= 2 {
+            format!("{:^4}", format!("+{}", chain))
+        } else {
+            "    ".to_owned()
+        };
+        let cmds = self.chain_cmds().iter().cloned().map(Ok).collect::<Vec<_>>();
+        draw_handle.as_sink().send_all(&mut futures::stream::iter(cmds)).await
+    }
 
-        let cmds: Vec<_> = updates.into_iter().flat_map(move |(pos, col)| {
-            let sym = if col.is_some() {
-                "()"
+    /// Draw the field's contents after rising by one row
+    ///
+    /// The `contents` are expected to cover every tile of the field after it
+    /// was raised. Tiles outside the bottom row are updated at once. If effects
+    /// are enabled, the tiles of the bottom row are cleared and their positions
+    /// are returned, from left to right, to be revealed one after another.
+    /// Otherwise, the bottom row is updated right away.
+    ///
+    pub async fn raise(
+        &mut self,
+        draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
+        contents: impl IntoIterator<Item=crate::field::Update>,
+    ) -> std::io::Result<Vec<util::Position>> {
+        if !self.effects {
+            return self.update(draw_handle, contents).await.map(|_| Default::default())
+        }
+
+        let bottom = self.dimensions.bottom_row();
+        let (row, rest): (Vec<_>, Vec<_>) = contents.into_iter().partition(|(p, _)| p.0 == bottom);
+        self.update(draw_handle, rest).await?;
+
+        let mut row: Vec<_> = row.into_iter().map(|(p, _)| p).collect();
+        row.sort_by_key(|p| p.1);
+        self.update(draw_handle, row.iter().map(|p| (*p, None))).await?;
+        Ok(row)
+    }
+
+    /// Draw the given tiles and remember them for repainting
+    ///
+    /// Tiles for which no draw commands are given are cleared.
+    ///
+    async fn draw_tiles(
+        &mut self,
+        draw_handle: &mut DrawHandle<'_, impl AsyncWrite + Send + Unpin>,
+        tiles: Vec<(util::Position, Option<[DC<'static>; 2]>)>,
+    ) -> std::io::Result<()> {
+        let mut cmds = Vec::new();
+        for (pos, tile) in tiles {
+            cmds.push(Ok(self.transform(pos)));
+            if let Some(tile) = tile {
+                cmds.extend(tile.iter().cloned().map(Ok));
+                self.tiles.insert(pos, tile);
             } else {
-                "  "
-            };
-            once(self.transform(pos)).chain(col.map(|c| Colour::from(c).into())).chain(once(sym.into()))
-        }).map(Ok).collect();
+                cmds.push(Ok("  ".into()));
+                self.tiles.remove(&pos);
+            }
+        }
+        draw_handle.as_sink().send_all(&mut futures::stream::iter(cmds)).await
+    }
 
-        draw_handle.as_sink().send_all(&mut iter(cmds)).await
+    /// Create the draw commands for the chain indication
+    ///
+    fn chain_cmds(&self) -> [DC<'static>; 3] {
+        let col = self.base_col + self.dimensions.width() as u16 - 1;
+        [DC::SetPos(self.base_row, col), self.theme.highlight().into(), self.chain.clone().into()]
     }
 
     /// Transform field positions to display positions
@@ -171,6 +281,258 @@ impl FieldUpdater {
             self.base_col + 1 + 2 * usize::from(col) as u16,
         )
     }
+
+    /// Create the draw commands for a capsule element with the given appearance
+    ///
+    /// Regular elements and bombs are drawn in their colour according to the
+    /// theme, rainbow elements are drawn in the theme's text colour since they
+    /// match any colour.
+    /// Distinct glyph shapes take precedence over non-ASCII glyphs.
+    ///
+    fn element_cmds(&self, appearance: crate::field::Appearance) -> [DC<'static>; 2] {
+        use crate::field::Special;
+        use util::Direction as Dir;
+
+        let glyph = match (appearance.special, self.glyphs, self.unicode) {
+            (Some(Special::Rainbow), ..) => return [self.theme.text().into(), "{}".into()],
+            (None, Glyphs::Uniform, true) => match appearance.partner {
+                None                => "\u{2590}\u{258C}",
+                Some(Dir::Right)    => "\u{2590}\u{2588}",
+                Some(Dir::Left)     => "\u{2588}\u{258C}",
+                Some(Dir::Below)    => "\u{259F}\u{2599}",
+                Some(Dir::Above)    => "\u{259C}\u{259B}",
+            },
+            (special, glyphs, _) => glyphs.element(appearance.colour, special == Some(Special::Bomb)),
+        };
+        [self.theme.capsule(appearance.colour).into(), glyph.into()]
+    }
+
+    /// Retrieve the glyph for a virus of the given colour
+    ///
+    /// Distinct glyph shapes take precedence over non-ASCII glyphs.
+    ///
+    fn virus_glyph(&self, colour: util::Colour, sym: VirusSym) -> &'static str {
+        match (self.glyphs, self.unicode, sym) {
+            (Glyphs::Uniform, true, VirusSym::A)    => "\u{259A}\u{259E}",
+            (Glyphs::Uniform, true, VirusSym::B)    => "\u{259E}\u{259A}",
+            (glyphs, ..)                            => glyphs.virus(colour, sym),
+        }
+    }
+}
+
+
+impl area::Repaint for FieldUpdater {
+    fn repaint(&self) -> Vec<DC<'static>> {
+        let mut res = bottle_cmds(self.dimensions, self.theme, self.unicode, (self.base_row, self.base_col));
+        self.tiles.iter().for_each(|(pos, tile)| {
+            res.push(self.transform(*pos));
+            res.extend(tile.iter().cloned());
+        });
+        res.extend(self.next.iter().cloned());
+        res.extend(self.chain_cmds().iter().cloned());
+        res
+    }
+}
+
+
+/// Create the draw commands for a bottle of the given dimensions
+///
+/// The function expects the first element of `pos` to contain the topmost row
+/// and the second to contain the leftmost column of the area reserved for the
+/// play field.
+///
+fn bottle_cmds(
+    dimensions: util::Dimensions,
+    theme: Theme,
+    unicode: bool,
+    (base_row, base_col): (u16, u16),
+) -> Vec<DC<'static>> {
+    let width = dimensions.width() as u16;
+    let height = dimensions.height() as u16;
+
+    let bottle = if unicode { &UNICODE_BOTTLE } else { &ASCII_BOTTLE };
+    let inlet_col = width - (INLET_WIDTH / 2);
+
+    // Upper part of inlet
+    let mut res = vec![
+        theme.border().into(),
+        DC::SetPos(base_row, base_col + 1 + inlet_col),
+        bottle.inlet[0].into(),
+    ];
+
+    let left_wall = base_col;
+    let right_wall = base_col + 1 + 2*width;
+
+    let element_top_row = base_row + 2;
+    let shoulder_row = if bottle.shoulders_in_ceiling { base_row + 1 } else { element_top_row };
+
+    // Bottle ceiling, with lower part of inlet
+    res.push(DC::SetPos(base_row + 1, base_col + 1));
+    res.extend((0..inlet_col).map(|_| bottle.ceiling.into()));
+    res.push(bottle.inlet[1].into());
+    res.extend(((inlet_col + INLET_WIDTH)..(2 * width)).map(|_| bottle.ceiling.into()));
+    res.push(DC::SetPos(shoulder_row, left_wall));
+    res.push(bottle.shoulders[0].into());
+    res.push(DC::SetPos(shoulder_row, right_wall));
+    res.push(bottle.shoulders[1].into());
+
+    // Left and right wall
+    ((shoulder_row + 1)..(element_top_row + height))
+        .for_each(|row| res.extend([
+            DC::SetPos(row, left_wall),
+            bottle.wall.into(),
+            DC::SetPos(row, right_wall),
+            bottle.wall.into(),
+        ].iter().cloned()));
+
+    // Bottle floor
+    res.push(DC::SetPos(base_row + 2 + height, base_col));
+    res.push(bottle.floor[0].into());
+    res.extend((0..width).map(|_| bottle.floor[1].into()));
+    res.push(bottle.floor[2].into());
+    res.push(theme.text().into());
+
+    res
+}
+
+
+/// Characters making up a bottle
+///
+struct Bottle {
+    /// Upper and lower part of the inlet, each `INLET_WIDTH` characters wide
+    inlet: [&'static str; 2],
+    /// Ceiling left and right of the inlet
+    ceiling: &'static str,
+    /// Left and right shoulder
+    shoulders: [&'static str; 2],
+    /// Whether the shoulders are drawn in the ceiling's row
+    ///
+    /// Otherwise, they are drawn in the row below the ceiling.
+    ///
+    shoulders_in_ceiling: bool,
+    /// Left and right walls
+    wall: &'static str,
+    /// Left corner, floor below a single tile and right corner
+    floor: [&'static str; 3],
+}
+
+
+/// Bottle drawn using only ASCII characters
+///
+const ASCII_BOTTLE: Bottle = Bottle {
+    inlet: ["\\    /", "/    \\"],
+    ceiling: "_",
+    shoulders: ["/", "\\"],
+    shoulders_in_ceiling: false,
+    wall: "|",
+    floor: ["\\", "__", "/"],
+};
+
+
+/// Bottle drawn using box-drawing characters
+///
+const UNICODE_BOTTLE: Bottle = Bottle {
+    inlet: ["\u{2502}    \u{2502}", "\u{256F}    \u{2570}"],
+    ceiling: "\u{2500}",
+    shoulders: ["\u{256D}", "\u{256E}"],
+    shoulders_in_ceiling: true,
+    wall: "\u{2502}",
+    floor: ["\u{2570}", "\u{2500}\u{2500}", "\u{256F}"],
+};
+
+
+/// Width of the inlet in characters
+///
+const INLET_WIDTH: u16 = 6;
+
+
+/// Symbols of the individual frames of the elimination animation
+///
+const EXPLOSION_SYMS: [&str; 3] = ["**", "++", ".."];
+
+
+/// Glyph set for capsule elements and viruses
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Glyphs {
+    /// The same glyphs for all colours
+    ///
+    /// Elements are drawn as `()`, bombs as `[]` and viruses as `><` or `--`.
+    /// Only their colour tells them apart.
+    ///
+    #[default]
+    Uniform,
+    /// Distinct glyph shapes for each colour
+    ///
+    /// Each colour is associated with a distinct pair of characters, e.g. `()`
+    /// for red and `<>` for yellow. Bombs are drawn as `[` followed by the
+    /// colour's second character and viruses as that character followed or
+    /// preceded by `-`.
+    ///
+    Shapes,
+}
+
+impl Glyphs {
+    /// Return the other glyph set
+    ///
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Uniform   => Self::Shapes,
+            Self::Shapes    => Self::Uniform,
+        }
+    }
+
+    /// Retrieve the glyph for a (possibly bomb) capsule element of the given colour
+    ///
+    pub fn element(self, colour: util::Colour, bomb: bool) -> &'static str {
+        use util::Colour as C;
+
+        match (self, colour, bomb) {
+            (Self::Uniform, _,          false)  => "()",
+            (Self::Uniform, _,          true)   => "[]",
+            (Self::Shapes,  C::Red,     false)  => "()",
+            (Self::Shapes,  C::Red,     true)   => "[)",
+            (Self::Shapes,  C::Yellow,  false)  => "<>",
+            (Self::Shapes,  C::Yellow,  true)   => "[>",
+            (Self::Shapes,  C::Blue,    false)  => "##",
+            (Self::Shapes,  C::Blue,    true)   => "[#",
+            (Self::Shapes,  C::Green,   false)  => "%%",
+            (Self::Shapes,  C::Green,   true)   => "[%",
+            (Self::Shapes,  C::Magenta, false)  => "&&",
+            (Self::Shapes,  C::Magenta, true)   => "[&",
+            (Self::Shapes,  C::Cyan,    false)  => "==",
+            (Self::Shapes,  C::Cyan,    true)   => "[=",
+        }
+    }
+
+    /// Retrieve the glyph for a virus of the given colour
+    ///
+    pub fn virus(self, colour: util::Colour, sym: VirusSym) -> &'static str {
+        use util::Colour as C;
+
+        match (self, colour, sym) {
+            (Self::Uniform, _,          sym)            => sym.symbol(),
+            (Self::Shapes,  C::Red,     VirusSym::A)    => ")-",
+            (Self::Shapes,  C::Red,     VirusSym::B)    => "-)",
+            (Self::Shapes,  C::Yellow,  VirusSym::A)    => ">-",
+            (Self::Shapes,  C::Yellow,  VirusSym::B)    => "->",
+            (Self::Shapes,  C::Blue,    VirusSym::A)    => "#-",
+            (Self::Shapes,  C::Blue,    VirusSym::B)    => "-#",
+            (Self::Shapes,  C::Green,   VirusSym::A)    => "%-",
+            (Self::Shapes,  C::Green,   VirusSym::B)    => "-%",
+            (Self::Shapes,  C::Magenta, VirusSym::A)    => "&-",
+            (Self::Shapes,  C::Magenta, VirusSym::B)    => "-&",
+            (Self::Shapes,  C::Cyan,    VirusSym::A)    => "=-",
+            (Self::Shapes,  C::Cyan,    VirusSym::B)    => "-=",
+        }
+    }
+}
+
+#[cfg(test)]
+impl quickcheck::Arbitrary for Glyphs {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        *g.choose(&[Self::Uniform, Self::Shapes]).unwrap()
+    }
 }
 
 
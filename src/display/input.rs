@@ -49,6 +49,8 @@ impl area::Entity for LineInput {
 
 /// Handle for updating a line input field
 ///
+/// The updater's value is used for repainting the field.
+///
 pub struct InputUpdater {
     base_row: u16,
     base_col: u16,
@@ -153,3 +155,16 @@ impl InputUpdater {
     }
 }
 
+impl area::Repaint for InputUpdater {
+    fn repaint(&self) -> Vec<DC<'static>> {
+        let mut res = vec![
+            DC::SetPos(self.base_row, self.base_col),
+            SGR::Blink(false).into(),
+            self.value.clone().into(),
+        ];
+        if self.value.len() < self.max_length.get().into() {
+            res.extend([SGR::Blink(true).into(), "_".into()].iter().cloned());
+        }
+        res
+    }
+}
@@ -7,6 +7,7 @@ use tokio::io::AsyncWrite;
 use crate::player;
 use super::area;
 use super::commands::{self, DrawCommand as DC, DrawHandle};
+use super::theme::Theme;
 
 
 /// Representation of a score board
@@ -16,6 +17,8 @@ use super::commands::{self, DrawCommand as DC, DrawHandle};
 pub struct ScoreBoard {
     max_rows: u16,
     show_scores: bool,
+    round_label: &'static str,
+    theme: Theme,
 }
 
 impl ScoreBoard {
@@ -24,7 +27,7 @@ impl ScoreBoard {
     /// By default, the score board will include the scores.
     ///
     pub fn new(max_rows: u16) -> Self {
-        Self {max_rows, show_scores: true}
+        Self {max_rows, show_scores: true, round_label: "Round", theme: Default::default()}
     }
 
     /// Change whether scores are shown
@@ -33,6 +36,35 @@ impl ScoreBoard {
         Self {show_scores, ..self}
     }
 
+    /// Change the label of the round score column
+    ///
+    pub fn round_label(self, round_label: &'static str) -> Self {
+        Self {round_label, ..self}
+    }
+
+    /// Change the colour theme
+    ///
+    pub fn theme(self, theme: Theme) -> Self {
+        Self {theme, ..self}
+    }
+
+    /// Create the draw commands for the score board's header
+    ///
+    fn header(&self, (base_row, base_col): (u16, u16)) -> Vec<DC<'static>> {
+        let mut res = vec![DC::SetPos(base_row, base_col + Self::NAME_COL), "Player".into()];
+
+        if self.show_scores {
+            res.extend([
+                DC::SetPos(base_row, base_col + Self::TOTAL_SCORE_COL),
+                "Total".into(),
+                DC::SetPos(base_row, base_col + Self::ROUND_SCORE_COL),
+                self.round_label.into(),
+            ].iter().cloned())
+        }
+
+        res
+    }
+
     const ENUM_COL: u16 = 0;
     const NAME_COL: u16 = 4;
     const TOTAL_SCORE_COL: u16 = 24;
@@ -51,27 +83,19 @@ impl area::Entity for ScoreBoard {
         Self::WIDTH
     }
 
-    fn init(&self, (base_row, base_col): (u16, u16)) -> area::PlacedInit {
-        let mut res = vec![DC::SetPos(base_row, base_col + Self::NAME_COL), "Player".into()];
-
-        if self.show_scores {
-            res.extend([
-                DC::SetPos(base_row, base_col + Self::TOTAL_SCORE_COL),
-                "Total".into(),
-                DC::SetPos(base_row, base_col + Self::ROUND_SCORE_COL),
-                "Round".into(),
-            ].iter().cloned())
-        }
-
-        res.into()
+    fn init(&self, pos: (u16, u16)) -> area::PlacedInit {
+        self.header(pos).into()
     }
 
     fn place(self, (base_row, base_col): (u16, u16)) -> Self::PlacedEntity {
         BoardUpdater {
+            header: self.header((base_row, base_col)),
             row_hashes: vec![Default::default(); self.max_rows as usize].into(),
+            row_cmds: vec![Default::default(); self.max_rows as usize].into(),
             base_row,
             base_col,
             show_scores: self.show_scores,
+            theme: self.theme,
         }
     }
 }
@@ -79,11 +103,17 @@ impl area::Entity for ScoreBoard {
 
 /// Handle for updating a score board entity
 ///
+/// The updater remembers the draw commands of each row for repainting the
+/// score board.
+///
 pub struct BoardUpdater {
+    header: Vec<DC<'static>>,
     row_hashes: Box<[u64]>,
+    row_cmds: Box<[Vec<DC<'static>>]>,
     base_row: u16,
     base_col: u16,
     show_scores: bool,
+    theme: Theme,
 }
 
 impl BoardUpdater {
@@ -91,7 +121,7 @@ impl BoardUpdater {
     ///
     /// The score board will be updated to reflect the given entries. Any entry
     /// matching the predicate provided by `highlight` will be highlightted
-    /// visually.
+    /// visually, using the theme's highlight colour.
     ///
     pub async fn update<'e, E: Entry + 'e>(
         &mut self,
@@ -119,14 +149,16 @@ impl BoardUpdater {
             move |row| (base_row + row as u16, base_col).into()
         };
         let show_scores = self.show_scores;
+        let theme = self.theme;
 
         // We'll ultimately iterate over all rows in the table and each of those
         // will have a hash assoziated with it which we might need to modify.
         let mut hashes = self
             .row_hashes
             .iter_mut()
+            .zip(self.row_cmds.iter_mut())
             .enumerate()
-            .map(|(row, hash)| (row + 1, hash));
+            .map(|(row, (hash, row_cmds))| (row + 1, hash, row_cmds));
 
         // First, we update the entries which do not match the hash. Regardless
         // of what entries will end being updated, the `zip` will cause as many
@@ -136,7 +168,7 @@ impl BoardUpdater {
         let mut cmds: Vec<_> = hashes
             .by_ref()
             .zip(entries.into_iter())
-            .filter_map(|((row, old_hash), entry)| {
+            .filter_map(|((row, old_hash, row_cmds), entry)| {
                 // First, we need to prepare the details and decide whether or
                 // not we need to draw an update for the entry.
                 let details = entry.details();
@@ -147,12 +179,12 @@ impl BoardUpdater {
 
                 if new_hash != *old_hash {
                     *old_hash = new_hash;
-                    Some((row, details, highlight(entry.tag())))
+                    Some((row, row_cmds, details, highlight(entry.tag())))
                 } else {
                     None
                 }
             })
-            .flat_map(|(row, entry, highlight)| {
+            .flat_map(|(row, row_cmds, entry, highlight)| {
                 // We then translate the details for each entry needing an
                 // update into a sequence of draw commands.
                 let intensity = if highlight {
@@ -163,9 +195,16 @@ impl BoardUpdater {
                     Some(Intensity::Faint)
                 };
 
+                let colour = if highlight {
+                    theme.highlight()
+                } else {
+                    theme.text()
+                };
+
                 let mut res = vec![
                     row_pos(row),
                     intensity.into(),
+                    colour.into(),
                     SGR::Strike(!entry.connected).into(),
                     format!("{0:1$} {2:3$}", row, NUM_WIDTH - 1, entry.name, NAME_WIDTH).into(),
                 ];
@@ -178,6 +217,7 @@ impl BoardUpdater {
                         ROUND_SCORE_WIDTH,
                     ).into())
                 }
+                *row_cmds = res.clone();
                 res
             })
             .map(Ok)
@@ -186,10 +226,13 @@ impl BoardUpdater {
         // We might have fewer entries than before. We thus need to clear all of
         // the remaining rows which were previously filled.
         cmds.extend(hashes
-            .filter(|(_, hash)| **hash != Default::default())
-            .flat_map(|(row, hash)| {
+            .filter(|(_, hash, _)| **hash != Default::default())
+            .flat_map(|(row, hash, row_cmds)| {
                 *hash = Default::default();
-                std::iter::once(row_pos(row)).chain((0..ScoreBoard::WIDTH).map(|_| " ".into()))
+                *row_cmds = std::iter::once(row_pos(row))
+                    .chain((0..ScoreBoard::WIDTH).map(|_| " ".into()))
+                    .collect();
+                row_cmds.clone()
             })
             .map(Ok)
         );
@@ -198,6 +241,15 @@ impl BoardUpdater {
 }
 
 
+impl area::Repaint for BoardUpdater {
+    fn repaint(&self) -> Vec<DC<'static>> {
+        let mut res = self.header.clone();
+        res.extend(self.row_cmds.iter().flatten().cloned());
+        res
+    }
+}
+
+
 /// Scoreboard entry
 ///
 pub trait Entry {
@@ -0,0 +1,363 @@
+//! Screen buffer
+
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Sink;
+use tokio::io::AsyncWrite;
+use tokio_util::codec::FramedWrite;
+
+use crate::metrics::METRICS;
+
+use super::commands::{ANSIEncoder, Brightness, Colour, DrawCommand, ExtendedColour, Intensity, SGR};
+
+
+/// Screen buffer
+///
+/// This sink records the intended contents of a terminal's cells as well as the
+/// cursor and SGR state. When flushed, only the differences between the
+/// intended contents and those previously written are sent to the terminal via
+/// the wrapped writer. Cursor movements and SGR changes are merged in the
+/// process.
+///
+/// The screen is flushed whenever the sink is, e.g. as part of `SinkExt::send`
+/// and when a `DrawHandle` is dropped. Cells outside the screen are not
+/// recorded. The screen is assumed to be empty initially.
+///
+pub struct Screen<W: AsyncWrite + Unpin> {
+    write: FramedWrite<W, ANSIEncoder>,
+    rows: u16,
+    cols: u16,
+    intended: Box<[Cell]>,
+    displayed: Box<[Cell]>,
+    cursor: (u16, u16),
+    rendition: Rendition,
+    show_cursor: bool,
+    clear: bool,
+    damage: std::ops::Range<usize>,
+    dirty: bool,
+    terminal: Terminal,
+    pending: VecDeque<DrawCommand<'static>>,
+    written: u64,
+}
+
+impl<W: AsyncWrite + Unpin> Screen<W> {
+    /// Create a new screen of the given size writing to the given `write`
+    ///
+    pub fn new(write: W, rows: u16, cols: u16) -> Self {
+        let cells = vec![Cell::default(); rows as usize * cols as usize].into_boxed_slice();
+        Self {
+            write: FramedWrite::new(write, ANSIEncoder::new()),
+            rows,
+            cols,
+            intended: cells.clone(),
+            displayed: cells,
+            cursor: (0, 0),
+            rendition: Default::default(),
+            show_cursor: true,
+            clear: false,
+            damage: 0..0,
+            dirty: false,
+            terminal: Default::default(),
+            pending: Default::default(),
+            written: 0,
+        }
+    }
+
+    /// Retrieve a reference to the underlying writer
+    ///
+    #[cfg(test)]
+    pub fn get_ref(&self) -> &W {
+        self.write.get_ref()
+    }
+
+    /// Invalidate the terminal's contents
+    ///
+    /// The next flush will clear the terminal and redraw all intended contents,
+    /// disregarding any knowledge about the terminal's state.
+    ///
+    pub fn invalidate(&mut self) {
+        self.clear = true;
+        self.damage = 0..self.intended.len();
+        self.dirty = true;
+        self.terminal = Default::default();
+    }
+
+    /// Record a draw command
+    ///
+    fn apply(&mut self, cmd: DrawCommand) {
+        match cmd {
+            DrawCommand::ClearScreen    => {
+                self.intended.iter_mut().for_each(|c| *c = Default::default());
+                self.clear = true;
+                self.damage = 0..self.intended.len();
+            },
+            DrawCommand::SetPos(r, c)   => self.cursor = (r, c),
+            DrawCommand::Format(sgr)    => self.rendition.apply(sgr),
+            DrawCommand::Text(text)     => text.chars().for_each(|ch| {
+                let (row, col) = self.cursor;
+                if let Some(i) = self.index(row, col) {
+                    self.intended[i] = Cell {ch, rendition: self.rendition};
+                    self.damage = if self.damage.is_empty() {
+                        i..i + 1
+                    } else {
+                        self.damage.start.min(i)..self.damage.end.max(i + 1)
+                    };
+                }
+                self.cursor.1 = col.saturating_add(1);
+            }),
+            DrawCommand::ShowCursor(v)  => self.show_cursor = v,
+        }
+        self.dirty = true;
+    }
+
+    /// Compute the commands bringing the terminal to the intended state
+    ///
+    fn render(&mut self) -> Vec<DrawCommand<'static>> {
+        let mut res = Vec::new();
+
+        if self.clear {
+            self.terminal.transition(&mut res, Default::default());
+            res.push(DrawCommand::ClearScreen);
+            self.displayed.iter_mut().for_each(|c| *c = Default::default());
+            self.clear = false;
+        }
+
+        let cols = self.cols as usize;
+        for i in std::mem::replace(&mut self.damage, 0..0) {
+            let cell = self.intended[i];
+            if cell == self.displayed[i] {
+                continue
+            }
+            let (row, col) = ((i / cols) as u16, (i % cols) as u16);
+
+            // Moving the cursor by re-sending a few unchanged cells is cheaper
+            // than an explicit cursor movement.
+            let gap = match self.terminal.cursor {
+                Some((r, c)) if r == row && c < col && col - c <= MAX_GAP => {
+                    let gap = &self.intended[i - (col - c) as usize..i];
+                    Some(gap).filter(|g| g.iter().all(|g| Some(g.rendition) == self.terminal.rendition))
+                },
+                _ => None,
+            };
+            if let Some(gap) = gap {
+                gap.iter().for_each(|g| push_char(&mut res, g.ch));
+            } else if self.terminal.cursor != Some((row, col)) {
+                res.push(DrawCommand::SetPos(row, col));
+            }
+
+            self.terminal.transition(&mut res, cell.rendition);
+            push_char(&mut res, cell.ch);
+            self.terminal.cursor = Some((row, col + 1)).filter(|(_, c)| *c < self.cols);
+            self.displayed[i] = cell;
+        }
+
+        let (row, col) = self.cursor;
+        if row < self.rows && col < self.cols && self.terminal.cursor != Some(self.cursor) {
+            res.push(DrawCommand::SetPos(row, col));
+            self.terminal.cursor = Some(self.cursor);
+        }
+        self.terminal.transition(&mut res, self.rendition);
+        if self.terminal.show_cursor != Some(self.show_cursor) {
+            res.push(DrawCommand::ShowCursor(self.show_cursor));
+            self.terminal.show_cursor = Some(self.show_cursor);
+        }
+
+        res
+    }
+
+    /// Determine the index of the cell at the given position
+    ///
+    fn index(&self, row: u16, col: u16) -> Option<usize> {
+        if row < self.rows && col < self.cols {
+            Some(row as usize * self.cols as usize + col as usize)
+        } else {
+            None
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> Drop for Screen<W> {
+    fn drop(&mut self) {
+        METRICS.connection_bytes.observe(self.written as f64);
+    }
+}
+
+impl<W: AsyncWrite + Unpin> Sink<DrawCommand<'_>> for Screen<W> {
+    type Error = std::io::Error;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: DrawCommand<'_>) -> Result<(), Self::Error> {
+        self.get_mut().apply(item);
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if this.dirty {
+            let cmds = this.render();
+            this.pending.extend(cmds);
+            this.dirty = false;
+        }
+
+        while !this.pending.is_empty() {
+            futures::ready!(Sink::<DrawCommand>::poll_ready(Pin::new(&mut this.write), cx))?;
+            let cmd = this.pending.pop_front().expect("No pending command");
+            let len = this.write.write_buffer().len();
+            Pin::new(&mut this.write).start_send(cmd)?;
+            let written = this.write.write_buffer().len().saturating_sub(len) as u64;
+            this.written += written;
+            METRICS.bytes_written.add(written);
+        }
+        Sink::<DrawCommand>::poll_flush(Pin::new(&mut this.write), cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        futures::ready!(Sink::<DrawCommand>::poll_flush(self.as_mut(), cx))?;
+        Sink::<DrawCommand>::poll_close(Pin::new(&mut self.get_mut().write), cx)
+    }
+}
+
+
+/// Append a character to the given commands, merging it with preceding text
+///
+fn push_char(cmds: &mut Vec<DrawCommand<'static>>, ch: char) {
+    if let Some(DrawCommand::Text(text)) = cmds.last_mut() {
+        text.to_mut().push(ch)
+    } else {
+        cmds.push(ch.to_string().into())
+    }
+}
+
+
+/// Maximum number of unchanged cells re-sent instead of moving the cursor
+///
+const MAX_GAP: u16 = 4;
+
+
+/// Known state of the terminal
+///
+/// `None` represents an unknown state.
+///
+#[derive(Default)]
+struct Terminal {
+    cursor: Option<(u16, u16)>,
+    rendition: Option<Rendition>,
+    show_cursor: Option<bool>,
+}
+
+impl Terminal {
+    /// Append SGRs changing the terminal's rendition to the given one
+    ///
+    fn transition(&mut self, cmds: &mut Vec<DrawCommand<'static>>, to: Rendition) {
+        let from = match self.rendition {
+            Some(from) if from == to                    => return,
+            Some(from) if to != Rendition::default()    => from,
+            _ => {
+                cmds.push(SGR::Reset.into());
+                Default::default()
+            },
+        };
+
+        if from.intensity != to.intensity {
+            if from.intensity.is_some() && to.intensity.is_some() {
+                cmds.push(SGR::Intensity(None).into());
+            }
+            cmds.push(SGR::Intensity(to.intensity).into());
+        }
+        if from.underline != to.underline {
+            cmds.push(SGR::Underline(to.underline).into());
+        }
+        if from.blink != to.blink {
+            cmds.push(SGR::Blink(to.blink).into());
+        }
+        if from.strike != to.strike {
+            cmds.push(SGR::Strike(to.strike).into());
+        }
+        if from.fg != to.fg {
+            cmds.push(match to.fg {
+                Some(Ink::Extended(c))  => SGR::FGExtended(c),
+                basic                   => SGR::FGColour(basic.and_then(Ink::basic)),
+            }.into());
+        }
+        if from.bg != to.bg {
+            cmds.push(match to.bg {
+                Some(Ink::Extended(c))  => SGR::BGExtended(c),
+                basic                   => SGR::BGColour(basic.and_then(Ink::basic)),
+            }.into());
+        }
+
+        self.rendition = Some(to);
+    }
+}
+
+
+/// Contents of a single cell
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Cell {
+    ch: char,
+    rendition: Rendition,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {ch: ' ', rendition: Default::default()}
+    }
+}
+
+
+/// Graphic rendition
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+struct Rendition {
+    intensity: Option<Intensity>,
+    underline: bool,
+    blink: bool,
+    strike: bool,
+    fg: Option<Ink>,
+    bg: Option<Ink>,
+}
+
+impl Rendition {
+    /// Apply a change in the form of an SGR to this rendition
+    ///
+    fn apply(&mut self, sgr: SGR) {
+        match sgr {
+            SGR::Reset          => *self = Default::default(),
+            SGR::Intensity(v)   => self.intensity = v,
+            SGR::Underline(v)   => self.underline = v,
+            SGR::Blink(v)       => self.blink = v,
+            SGR::Strike(v)      => self.strike = v,
+            SGR::FGColour(v)    => self.fg = v.map(|(c, b)| Ink::Basic(c, b)),
+            SGR::BGColour(v)    => self.bg = v.map(|(c, b)| Ink::Basic(c, b)),
+            SGR::FGExtended(v)  => self.fg = Some(Ink::Extended(v)),
+            SGR::BGExtended(v)  => self.bg = Some(Ink::Extended(v)),
+        }
+    }
+}
+
+
+/// Foreground or background colour
+///
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Ink {
+    Basic(Colour, Brightness),
+    Extended(ExtendedColour),
+}
+
+impl Ink {
+    /// Retrieve the basic colour, if this is one
+    ///
+    fn basic(self) -> Option<(Colour, Brightness)> {
+        match self {
+            Self::Basic(c, b)   => Some((c, b)),
+            Self::Extended(_)   => None,
+        }
+    }
+}
+
@@ -31,7 +31,7 @@ fn score_board_initial_content(
     if area.row_b <= rows && area.col_b <= cols && orig.iter().all(ScoreBoardEntry::acceptable_for_tests) {
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
+            let mut handle = handle_from_vt(writer, &[]).await;
             area.instantiate(&mut handle)
                 .place_center(board)
                 .await?
@@ -92,7 +92,7 @@ fn score_board_updated_content(
     if area.row_b <= rows && area.col_b <= cols && acceptable {
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
+            let mut handle = handle_from_vt(writer, &[]).await;
             let mut board = area.instantiate(&mut handle).place_center(board).await?;
             board.update(&mut handle, orig1.iter(), |_| false).await?;
             board.update(&mut handle, orig2.iter(), |_| false).await?;
@@ -147,8 +147,8 @@ fn dynamic_text(
 
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
-            let text = area.instantiate(&mut handle).place_center(
+            let mut handle = handle_from_vt(writer, &[]).await;
+            let mut text = area.instantiate(&mut handle).place_center(
                 dynamic_text::DynamicText::new(
                     area.rows().try_into().unwrap(),
                     area.cols().try_into().unwrap(),
@@ -173,13 +173,13 @@ fn play_field_init(rows: u8, cols: u8, base_row: u8, base_col: u8) -> std::io::R
     let rows: u16 = rows.into();
     let cols: u16 = cols.into();
 
-    let field = field::PlayField::new();
+    let field = field::PlayField::default();
     let area = Area::new_for_placement(base_row, base_col, &field);
 
     if area.row_b <= rows && area.col_b <= cols {
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            area.instantiate(handle_from_bare(VTWriter::from(writer), &[]).await)
+            area.instantiate(handle_from_vt(writer, &[]).await)
                 .place_center(field)
                 .await?;
             let res = (area.row_a..area.row_b)
@@ -218,6 +218,109 @@ fn play_field_init(rows: u8, cols: u8, base_row: u8, base_col: u8) -> std::io::R
 }
 
 
+#[quickcheck]
+fn play_field_init_unicode(rows: u8, cols: u8, base_row: u8, base_col: u8) -> std::io::Result<TestResult> {
+    let rows: u16 = rows.into();
+    let cols: u16 = cols.into();
+
+    let field = field::PlayField::default().unicode(true);
+    let area = Area::new_for_placement(base_row, base_col, &field);
+
+    if area.row_b <= rows && area.col_b <= cols {
+        tokio::runtime::Runtime::new()?.block_on(async {
+            let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
+            area.instantiate(handle_from_vt(writer, &[]).await)
+                .place_center(field)
+                .await?;
+            let walls = "\u{2502}                \u{2502}";
+            let expected = [
+                "      \u{2502}    \u{2502}      ",
+                "\u{256D}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{256F}    \u{2570}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{256E}",
+            ].iter()
+                .cloned()
+                .chain(std::iter::repeat_n(walls, 16))
+                .map(ToString::to_string)
+                .chain(std::iter::once(format!("\u{2570}{}\u{256F}", "\u{2500}".repeat(16))));
+            let res = (area.row_a..area.row_b)
+                .map(|r| vt_state
+                    .borrow()
+                    .chars_at(r, area.col_a)
+                    .take(area.cols().into())
+                    .collect::<String>()
+                )
+                .eq(expected);
+            Ok(TestResult::from_bool(res))
+        })
+    } else {
+        Ok(TestResult::discard())
+    }
+}
+
+
+#[quickcheck]
+fn play_field_pills(
+    rows: u8,
+    cols: u8,
+    base_row: u8,
+    base_col: u8,
+    colour: crate::util::Colour,
+) -> std::io::Result<TestResult> {
+    use crate::util::Direction as Dir;
+
+    let rows: u16 = rows.into();
+    let cols: u16 = cols.into();
+
+    let field = field::PlayField::default().unicode(true);
+    let area = Area::new_for_placement(base_row, base_col, &field);
+
+    let element = |partner| Some(crate::field::Appearance::from(colour).with_partner(partner));
+    let updates = [
+        ((0, 0), element(Some(Dir::Right))),
+        ((0, 1), element(Some(Dir::Left))),
+        ((2, 0), element(Some(Dir::Below))),
+        ((3, 0), element(Some(Dir::Above))),
+        ((5, 0), element(None)),
+    ];
+    let expected = [
+        "\u{2590}\u{2588}\u{2588}\u{258C}",
+        "",
+        "\u{259F}\u{2599}",
+        "\u{259C}\u{259B}",
+        "",
+        "\u{2590}\u{258C}",
+    ];
+
+    if area.row_b <= rows && area.col_b <= cols {
+        tokio::runtime::Runtime::new()?.block_on(async {
+            use std::convert::TryInto;
+
+            let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
+            let mut handle = handle_from_vt(writer, &[]).await;
+            let updates: Vec<_> = updates
+                .iter()
+                .map(|((r, c), a)| (((*r).try_into().unwrap(), (*c).try_into().unwrap()), *a))
+                .collect();
+            area.instantiate(&mut handle)
+                .place_center(field)
+                .await?
+                .update(&mut handle, updates)
+                .await?;
+
+            let res = expected.iter().enumerate().all(|(r, e)| vt_state
+                .borrow()
+                .chars_at(area.row_a + 2 + r as u16, area.col_a + 1)
+                .take(16)
+                .collect::<String>()
+                .trim_end() == *e
+            );
+            Ok(TestResult::from_bool(res))
+        })
+    } else {
+        Ok(TestResult::discard())
+    }
+}
+
+
 #[quickcheck]
 fn play_field_virs(
     rows: u8,
@@ -226,19 +329,20 @@ fn play_field_virs(
     base_col: u8,
     viruses: std::collections::HashMap<crate::util::Position, crate::util::Colour>,
     vir_sym: field::VirusSym,
+    glyphs: field::Glyphs,
 ) -> std::io::Result<TestResult> {
     use std::convert::TryInto;
 
     let rows: u16 = rows.into();
     let cols: u16 = cols.into();
 
-    let field = field::PlayField::new();
+    let field = field::PlayField::default().glyphs(glyphs);
     let area = Area::new_for_placement(base_row, base_col, &field);
 
     if area.row_b <= rows && area.col_b <= cols {
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
+            let mut handle = handle_from_vt(writer, &[]).await;
             area.instantiate(&mut handle)
                 .place_center(field)
                 .await?
@@ -247,13 +351,15 @@ fn play_field_virs(
 
             // Read back viruses into a map of positions
             let tiles: std::collections::HashMap<_, _> = tile_contents(&vt_state.borrow(), area)
-                .filter(|(_, [a, b])| a.data != 0x20 || b.data != 0x20)
+                .filter(|(_, [a, b])| a.data != ' ' || b.data != ' ')
                 .collect();
 
             let correct_syms = tiles
-                .values()
-                .all(|[a, b]| vir_sym.symbol().chars().eq([a.data as char, b.data as char]) &&
-                    a.format == b.format
+                .iter()
+                .all(|(p, [a, b])| viruses
+                    .get(p)
+                    .map(|c| glyphs.virus(*c, vir_sym).chars().eq([a.data, b.data]))
+                    .unwrap_or(false) && a.format == b.format
                 );
             let virus_match = viruses == tiles
                 .into_iter()
@@ -279,17 +385,17 @@ fn play_field_next(
     let rows: u16 = rows.into();
     let cols: u16 = cols.into();
 
-    let field = field::PlayField::new();
+    let field = field::PlayField::default();
     let area = Area::new_for_placement(base_row, base_col, &field);
 
     if area.row_b <= rows && area.col_b <= cols {
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
+            let mut handle = handle_from_vt(writer, &[]).await;
             area.instantiate(&mut handle)
                 .place_center(field)
                 .await?
-                .place_next_elements(&mut handle, &[colour_a, colour_b])
+                .place_next_elements(&mut handle, &[colour_a.into(), colour_b.into()])
                 .await?;
 
             let res = vt_state
@@ -311,24 +417,25 @@ fn play_field_update(
     cols: u8,
     base_row: u8,
     base_col: u8,
-    updates: Vec<crate::field::Update>,
+    updates: Vec<(crate::util::Position, Option<crate::util::Colour>)>,
+    glyphs: field::Glyphs,
 ) -> std::io::Result<TestResult> {
     use std::convert::TryInto;
 
     let rows: u16 = rows.into();
     let cols: u16 = cols.into();
 
-    let field = field::PlayField::new();
+    let field = field::PlayField::default().glyphs(glyphs);
     let area = Area::new_for_placement(base_row, base_col, &field);
 
     if area.row_b <= rows && area.col_b <= cols {
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
+            let mut handle = handle_from_vt(writer, &[]).await;
             area.instantiate(&mut handle)
                 .place_center(field)
                 .await?
-                .update(&mut handle, updates.clone())
+                .update(&mut handle, updates.iter().map(|(p, c)| (*p, c.map(Into::into))))
                 .await?;
 
             let elements: std::collections::HashMap<_, _> = updates
@@ -343,12 +450,16 @@ fn play_field_update(
                 });
 
             let tiles: std::collections::HashMap<_, _> = tile_contents(&vt_state.borrow(), area)
-                .filter(|(_, [a, b])| a.data != 0x20 || b.data != 0x20)
+                .filter(|(_, [a, b])| a.data != ' ' || b.data != ' ')
                 .collect();
 
             let correct_syms = tiles
-                .values()
-                .all(|[a, b]| a.data == 0x28 && b.data == 0x29 && a.format == b.format);
+                .iter()
+                .all(|(p, [a, b])| elements
+                    .get(p)
+                    .map(|c| glyphs.element(*c, false).chars().eq([a.data, b.data]))
+                    .unwrap_or(false) && a.format == b.format
+                );
             let element_match = elements == tiles
                 .into_iter()
                 .filter_map(|(p, [a, ..])| a.format.fg_colour.and_then(|(c, _)| c.try_into().ok()).map(|c| (p, c)))
@@ -361,6 +472,105 @@ fn play_field_update(
 }
 
 
+#[quickcheck]
+fn play_field_explosion(
+    rows: u8,
+    cols: u8,
+    base_row: u8,
+    base_col: u8,
+    positions: Vec<crate::util::Position>,
+) -> std::io::Result<TestResult> {
+    let rows: u16 = rows.into();
+    let cols: u16 = cols.into();
+
+    let field = field::PlayField::new(Default::default(), true);
+    let area = Area::new_for_placement(base_row, base_col, &field);
+
+    if area.row_b <= rows && area.col_b <= cols {
+        tokio::runtime::Runtime::new()?.block_on(async {
+            let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
+            let mut handle = handle_from_vt(writer, &[]).await;
+            let mut field = area.instantiate(&mut handle).place_center(field).await?;
+
+            let frames = field.explosion_frames();
+            let mut res = frames > 0;
+            for frame in 0..=frames {
+                field.explode(&mut handle, positions.iter().cloned(), frame).await?;
+                let drawn: std::collections::HashSet<_> = tile_contents(&vt_state.borrow(), area)
+                    .filter(|(_, [a, b])| a.data != ' ' || b.data != ' ')
+                    .map(|(p, _)| p)
+                    .collect();
+                res &= if frame < frames {
+                    drawn == positions.iter().cloned().collect()
+                } else {
+                    drawn.is_empty()
+                };
+            }
+            Ok(TestResult::from_bool(res))
+        })
+    } else {
+        Ok(TestResult::discard())
+    }
+}
+
+
+#[quickcheck]
+fn play_field_repaint(
+    rows: u8,
+    cols: u8,
+    updates: Vec<(crate::util::Position, Option<crate::util::Colour>)>,
+    exploded: Vec<crate::util::Position>,
+    next: (crate::util::Colour, crate::util::Colour),
+    chain: u8,
+) -> std::io::Result<TestResult> {
+    use area::Repaint;
+
+    let rows: u16 = rows.into();
+    let cols: u16 = cols.into();
+
+    let field = field::PlayField::new(Default::default(), true);
+    let area = Area::new_for_placement(0u16, 0u16, &field);
+
+    if area.row_b <= rows && area.col_b <= cols {
+        tokio::runtime::Runtime::new()?.block_on(async {
+            let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
+            let mut handle = handle_from_vt(writer, &[]).await;
+            let mut field = area.instantiate(&mut handle).place_center(field).await?;
+            field.update(&mut handle, updates.iter().map(|(p, c)| (*p, c.map(Into::into)))).await?;
+            field.explode(&mut handle, exploded.iter().cloned(), 0).await?;
+            field.place_next_elements(&mut handle, &[next.0.into(), next.1.into()]).await?;
+            field.show_chain(&mut handle, chain.into()).await?;
+
+            let mut repainted = VT::new(rows, cols);
+            field.repaint().into_iter().try_for_each(|c| repainted.apply(c))?;
+
+            let original = vt_state.borrow();
+            let res = (area.row_a..area.row_b).all(|r| {
+                let range = usize::from(area.col_a)..usize::from(area.col_b);
+                original.data[usize::from(r)][range.clone()]
+                    .iter()
+                    .zip(&repainted.data[usize::from(r)][range])
+                    .all(|(a, b)| a.data == b.data && (a.data == ' ' || a.format == b.format))
+            });
+            Ok(TestResult::from_bool(res))
+        })
+    } else {
+        Ok(TestResult::discard())
+    }
+}
+
+
+#[quickcheck]
+fn theme_basic_fallback(theme: theme::Theme, colour: crate::util::Colour) -> bool {
+    use commands::SGR;
+
+    let theme = theme.with_depth(theme::ColourDepth::Basic);
+    [theme.border(), theme.text(), theme.highlight(), theme.capsule(colour), theme.virus(colour)]
+        .iter()
+        .all(|s| matches!(s, SGR::FGColour(_)))
+}
+
+
 #[quickcheck]
 fn line_input_update(
     rows: u8,
@@ -379,7 +589,7 @@ fn line_input_update(
     if area.row_b <= rows && area.col_b <= cols {
         tokio::runtime::Runtime::new()?.block_on(async {
             let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
-            let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
+            let mut handle = handle_from_vt(writer, &[]).await;
             let mut placed = area.instantiate(&mut handle).place_center(line_input).await?;
 
             for i in inputs.iter() {
@@ -434,6 +644,11 @@ fn display_handle_drop(
 
     use crate::display::commands::SinkProxy;
 
+    // Without a change, the termination sequence would not be written
+    if commands.contains(&commands::DrawCommand::ShowCursor(true)) {
+        return Ok(TestResult::discard())
+    }
+
     let rows = rows.get().into();
     let cols = cols.get().into();
     tokio::runtime::Runtime::new()?.block_on(async {
@@ -610,36 +825,112 @@ fn area_place_center(area: Area, entity: DummyEntity) -> std::io::Result<bool> {
 
 #[quickcheck]
 fn draw_handle_drop(
-    mut data: Vec<commands::DrawCommand<'static>>,
+    rows: NonZeroU8,
+    cols: NonZeroU8,
+    data: Vec<commands::DrawCommand<'static>>,
     term: Vec<commands::DrawCommand<'static>>,
 ) -> std::io::Result<TestResult> {
     use futures::SinkExt;
 
     use commands::{DrawCommand as DC, SinkProxy};
 
+    let rows = rows.get().into();
+    let cols = cols.get().into();
+    let constrain = |c| if let DC::SetPos(r, c) = c { DC::SetPos(r % rows, c % cols) } else { c };
+    let data: Vec<_> = data.into_iter().map(constrain).collect();
+    let term: Vec<_> = term.into_iter().map(constrain).collect();
+
+    let mut expected = VT::new(rows, cols);
+    if data.iter().chain(term.iter()).cloned().try_for_each(|c| expected.apply(c)).is_err() {
+        return Ok(TestResult::discard())
+    }
+
     let rt = tokio::runtime::Runtime::new()?;
 
-    let inner: Arc<tokio::sync::Mutex<_>> = Arc::new(
-        tokio_util::codec::FramedWrite::new(Vec::new(), commands::ANSIEncoder::new()).into()
-    );
+    let (writer, vt_state) = tokio::sync::watch::channel(VT::new(rows, cols));
+    let inner: Arc<tokio::sync::Mutex<_>> = Arc::new(screen::Screen::new(VTWriter::from(writer), rows, cols).into());
 
     rt.block_on(async {
         let mut handle = commands::draw_handle(inner.clone().lock_owned().await, term.as_ref());
-        let res = handle.as_sink().send_all(&mut futures::stream::iter(data.iter().cloned().map(Ok))).await;
+        handle.as_sink().send_all(&mut futures::stream::iter(data.iter().cloned().map(Ok))).await?;
         drop(handle);
-        res
-    })?;
 
-    data.extend(term);
-    if data.windows(2).any(|w| if let [DC::Text(_), DC::Text(_)] = w { true } else { false }) {
-        Ok(TestResult::discard())
-    } else {
-        let buf = inner.blocking_lock();
-        let res = draw_commands_from(buf.get_ref().as_ref())
-            .try_fold(Vec::new(), |mut a, c| { a.push(c?); Ok(a) })
-            .map(|r| TestResult::from_bool(data == r));
-        res
+        // The termination sequence is sent by a task holding the lock
+        let _ = inner.lock().await;
+        let actual = vt_state.borrow();
+        let cursor_valid = expected.cursor_row < rows && expected.cursor_col < cols;
+        let res = actual.data == expected.data &&
+            actual.rendition == expected.rendition &&
+            actual.show_cursor == expected.show_cursor &&
+            (!cursor_valid || (actual.cursor_row, actual.cursor_col) == (expected.cursor_row, expected.cursor_col));
+        Ok(TestResult::from_bool(res))
+    })
+}
+
+
+#[quickcheck]
+fn display_redraw(
+    rows: NonZeroU8,
+    cols: NonZeroU8,
+    data: Vec<commands::DrawCommand<'static>>,
+    garbage: char,
+) -> std::io::Result<TestResult> {
+    use futures::SinkExt;
+
+    use commands::{DrawCommand as DC, SinkProxy};
+
+    let rows = rows.get().into();
+    let cols = cols.get().into();
+    let data: Vec<_> = data
+        .into_iter()
+        .map(|c| if let DC::SetPos(r, c) = c { DC::SetPos(r % rows, c % cols) } else { c })
+        .collect();
+
+    let mut expected = VT::new(rows, cols);
+    if garbage.is_control() || data.iter().cloned().try_for_each(|c| expected.apply(c)).is_err() {
+        return Ok(TestResult::discard())
     }
+
+    // The terminal's contents differ from what the display assumes
+    let mut corrupted = VT::new(rows, cols);
+    corrupted.data.iter_mut().flatten().for_each(|c| c.data = garbage);
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let (writer, vt_state) = tokio::sync::watch::channel(corrupted);
+        let mut display = display::Display::new(VTWriter::from(writer), rows, cols);
+        let mut handle = display.handle().await?;
+        handle.as_sink().send_all(&mut futures::stream::iter(data.into_iter().map(Ok))).await?;
+        drop(handle);
+
+        display.redraw(&[]).await?;
+        let res = vt_state.borrow().data == expected.data;
+        Ok(TestResult::from_bool(res))
+    })
+}
+
+
+#[quickcheck]
+fn screen_redraw_unchanged(
+    rows: NonZeroU8,
+    cols: NonZeroU8,
+    data: Vec<commands::DrawCommand<'static>>,
+) -> std::io::Result<bool> {
+    use futures::SinkExt;
+
+    use commands::DrawCommand as DC;
+
+    let data: Vec<_> = vec![DC::SetPos(0, 0), commands::SGR::Reset.into()]
+        .into_iter()
+        .chain(data.into_iter().filter(|c| *c != DC::ClearScreen))
+        .collect();
+    let mut screen = screen::Screen::new(Vec::new(), rows.get().into(), cols.get().into());
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        screen.send_all(&mut futures::stream::iter(data.iter().cloned().map(Ok))).await?;
+        let written = screen.get_ref().len();
+        screen.send_all(&mut futures::stream::iter(data.iter().cloned().map(Ok))).await?;
+        Ok(screen.get_ref().len() == written)
+    })
 }
 
 
@@ -686,6 +977,7 @@ pub fn tile_contents(
         .split_at((area.col_a + 1).into())
         .1
         .chunks_exact(2)
+        .take(((area.cols() - 2) / 2).into())
         .enumerate()
         .filter_map(|(c, v)| if let [a, b] = v { Some((c, [*a, *b])) } else { None })
         .filter_map(move |(c, s)| Some(((
@@ -912,6 +1204,82 @@ impl tokio::io::AsyncWrite for VTWriter {
 }
 
 
+/// [VT] fed from an [tokio::io::AsyncRead]
+///
+/// This utility allows observing the output of a display written to some
+/// stream, e.g. one end of a [tokio::io::duplex]. Incomplete escape sequences
+/// and characters are retained until the remainder is read.
+///
+pub struct VTReader<R> {
+    read: R,
+    vt: VT,
+    buf: Vec<u8>,
+}
+
+impl<R: tokio::io::AsyncRead + Unpin> VTReader<R> {
+    /// Create a new reader for a VT with the given number of rows and columns
+    ///
+    pub fn new(read: R, rows: u16, cols: u16) -> Self {
+        Self {read, vt: VT::new(rows, cols), buf: Default::default()}
+    }
+
+    /// Retrieve the current state of the VT
+    ///
+    pub fn vt(&self) -> &VT {
+        &self.vt
+    }
+
+    /// Read and apply data until the VT satisfies the given predicate
+    ///
+    pub async fn wait_for(&mut self, predicate: impl Fn(&VT) -> bool) -> std::io::Result<()> {
+        use tokio::io::AsyncReadExt;
+
+        while !predicate(&self.vt) {
+            let mut data = [0u8; 1024];
+            let len = self.read.read(&mut data).await?;
+            if len == 0 {
+                return Err(std::io::ErrorKind::UnexpectedEof.into())
+            }
+            self.buf.extend_from_slice(&data[..len]);
+
+            while let Some(len) = complete_unit(&self.buf) {
+                let unit: Vec<_> = self.buf.drain(..len).collect();
+                draw_commands_from(&unit).try_for_each(|c| self.vt.apply(c?))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Determine the length of the first complete unit in some encoded ANSI
+///
+/// A unit is either an escape sequence or text up to the next escape sequence.
+/// This function returns `None` if the data does not start with a complete
+/// unit.
+///
+fn complete_unit(src: &[u8]) -> Option<usize> {
+    match src {
+        []                      => None,
+        [0x1b]                  => None,
+        [0x1b, 0x5b, rem @ ..]  => rem
+            .iter()
+            .position(|c| (0x40..=0x7e).contains(c))
+            .map(|p| p + 3),
+        [0x1b, ..]              => Some(1),
+        _ => {
+            let end = src.iter().position(|c| *c == 0x1b).unwrap_or(src.len());
+            let len = match std::str::from_utf8(&src[..end]) {
+                Ok(_)                                   => end,
+                Err(e) if e.error_len().is_none()       => e.valid_up_to(),
+                Err(_)                                  => end,
+            };
+            Some(len).filter(|l| *l > 0)
+        },
+    }
+}
+
+
 /// Simplified model of a virtual terminal
 ///
 #[derive(Clone, Debug, PartialEq)]
@@ -970,6 +1338,18 @@ impl VT {
         }
     }
 
+    /// Retrieve all cells, row by row
+    ///
+    pub fn cells(&self) -> impl Iterator<Item = &FormattedChar> {
+        self.data.iter().flatten()
+    }
+
+    /// Check whether the given text is displayed in any row
+    ///
+    pub fn contains(&self, text: &str) -> bool {
+        (0..self.data.len()).any(|r| self.chars_at(r as u16, 0).collect::<String>().contains(text))
+    }
+
     /// View the data displayed at a given position as characters
     ///
     pub fn chars_at(&self, row: u16, col: u16) -> impl Iterator<Item = char> + '_ {
@@ -1000,14 +1380,14 @@ impl Default for VT {
 ///
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct FormattedChar {
-    pub data: u8,
+    pub data: char,
     pub format: GraphicRendition,
 }
 
 impl FormattedChar {
     pub fn set_from_char(&mut self, data: char, format: GraphicRendition) -> std::io::Result<()> {
-        if data.is_ascii_graphic() || data == '\x20' {
-            self.data = data as u8;
+        if !data.is_control() {
+            self.data = data;
             self.format = format;
             Ok(())
         } else {
@@ -1018,13 +1398,13 @@ impl FormattedChar {
 
 impl From<FormattedChar> for char {
     fn from(c: FormattedChar) -> Self {
-        c.data.into()
+        c.data
     }
 }
 
 impl Default for FormattedChar {
     fn default() -> Self {
-        Self {data: 0x20, format: Default::default()}
+        Self {data: ' ', format: Default::default()}
     }
 }
 
@@ -1039,6 +1419,8 @@ pub struct GraphicRendition {
     pub strike: bool,
     pub fg_colour: Option<(commands::Colour, commands::Brightness)>,
     pub bg_colour: Option<(commands::Colour, commands::Brightness)>,
+    pub fg_extended: Option<commands::ExtendedColour>,
+    pub bg_extended: Option<commands::ExtendedColour>,
 }
 
 impl GraphicRendition {
@@ -1053,8 +1435,10 @@ impl GraphicRendition {
             SGR::Underline(v)   => self.underline = v,
             SGR::Blink(v)       => self.blink = v,
             SGR::Strike(v)      => self.strike = v,
-            SGR::FGColour(v)    => self.fg_colour = v,
-            SGR::BGColour(v)    => self.bg_colour = v,
+            SGR::FGColour(v)    => { self.fg_colour = v; self.fg_extended = None },
+            SGR::BGColour(v)    => { self.bg_colour = v; self.bg_extended = None },
+            SGR::FGExtended(v)  => { self.fg_colour = None; self.fg_extended = Some(v) },
+            SGR::BGExtended(v)  => { self.bg_colour = None; self.bg_extended = Some(v) },
         }
     }
 }
@@ -1068,6 +1452,8 @@ impl Default for GraphicRendition {
             strike: false,
             fg_colour: None,
             bg_colour: None,
+            fg_extended: None,
+            bg_extended: None,
         }
     }
 }
@@ -1079,9 +1465,24 @@ pub async fn handle_from_bare<'a, W: tokio::io::AsyncWrite + Send + Unpin + 'sta
     write: W,
     termination_seq: &'a [commands::DrawCommand<'static>],
 ) -> DrawHandle<'a, W> {
-    let inner: Arc<tokio::sync::Mutex<_>> = Arc::new(
-        tokio_util::codec::FramedWrite::new(write, commands::ANSIEncoder::new()).into()
-    );
+    let inner: Arc<tokio::sync::Mutex<_>> = Arc::new(screen::Screen::new(write, 256, 256).into());
+    commands::draw_handle(inner.lock_owned().await, termination_seq)
+}
+
+
+/// Create a DrawHandle writing to a VT
+///
+/// The handle's screen will have the same size as the VT.
+///
+pub async fn handle_from_vt<'a>(
+    writer: tokio::sync::watch::Sender<VT>,
+    termination_seq: &'a [commands::DrawCommand<'static>],
+) -> DrawHandle<'a, VTWriter> {
+    let (rows, cols) = {
+        let vt = writer.borrow();
+        (vt.data.len() as u16, vt.data.first().map(Vec::len).unwrap_or(0) as u16)
+    };
+    let inner: Arc<tokio::sync::Mutex<_>> = Arc::new(screen::Screen::new(VTWriter::from(writer), rows, cols).into());
     commands::draw_handle(inner.lock_owned().await, termination_seq)
 }
 
@@ -1107,7 +1508,7 @@ fn draw_commands_from(mut src: &[u8]) -> impl Iterator<Item = std::io::Result<co
 fn decode_ansi(src: &[u8]) -> std::io::Result<(Option<commands::DrawCommand<'static>>, &[u8])> {
     use std::io::ErrorKind as EK;
 
-    use commands::{Brightness, Colour, DrawCommand, Intensity, SGR};
+    use commands::{Brightness, Colour, DrawCommand, ExtendedColour, Intensity, SGR};
 
     fn extract_num(s: &[u8]) -> Option<(&[u8], &[u8])> {
         s.iter().position(|c| !c.is_ascii_digit()).map(|p| s.split_at(p))
@@ -1126,6 +1527,26 @@ fn decode_ansi(src: &[u8]) -> std::io::Result<(Option<commands::DrawCommand<'sta
             let (com, rem) = rem.split_first().ok_or(EK::InvalidData)?;
             let data = match com {
                 0x4a if n == 2  => DrawCommand::ClearScreen,
+                0x3b if (n == 38 || n == 48) &&
+                    rem.iter().find(|c| c.is_ascii_alphabetic()) == Some(&0x6d) => {
+                    let end = rem.iter().position(|c| *c == 0x6d).ok_or(EK::InvalidData)?;
+                    let params = rem[..end]
+                        .split(|c| *c == 0x3b)
+                        .map(|p| std::str::from_utf8(p).ok().and_then(|p| p.parse().ok()))
+                        .collect::<Option<Vec<u8>>>()
+                        .ok_or(EK::InvalidData)?;
+                    let colour = match params.as_slice() {
+                        [5, i]          => ExtendedColour::Indexed(*i),
+                        [2, r, g, b]    => ExtendedColour::Rgb(*r, *g, *b),
+                        _ => Err(EK::InvalidData)?
+                    };
+                    let sgr = if n == 38 {
+                        SGR::FGExtended(colour)
+                    } else {
+                        SGR::BGExtended(colour)
+                    };
+                    return Ok((Some(sgr.into()), &rem[end + 1..]))
+                },
                 0x3b            => {
                     let (m, rem) = extract_num(rem).ok_or(EK::InvalidData)?;
                     let m: u16 = parse_u16(m).ok_or(EK::InvalidData)?;
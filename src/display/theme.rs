@@ -0,0 +1,291 @@
+//! Colour themes
+
+use std::fmt;
+
+use crate::util;
+use super::commands::{Brightness, Colour, ExtendedColour, SGR};
+
+
+/// Colour theme
+///
+/// A theme consists of one of the built-in palettes and the colour depth
+/// supported by the terminal. Colours of the palette which are not available
+/// at that depth are substituted with a basic colour.
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Theme {
+    palette: usize,
+    depth: ColourDepth,
+}
+
+impl Theme {
+    /// Retrieve the name of the theme's palette
+    ///
+    pub fn name(&self) -> &'static str {
+        self.palette().name
+    }
+
+    /// Retrieve the colour depth assumed for the terminal
+    ///
+    pub fn depth(&self) -> ColourDepth {
+        self.depth
+    }
+
+    /// Return this theme with the next built-in palette
+    ///
+    pub fn next_palette(self) -> Self {
+        Self {palette: (self.palette + 1) % PALETTES.len(), ..self}
+    }
+
+    /// Return this theme with the given colour depth
+    ///
+    pub fn with_depth(self, depth: ColourDepth) -> Self {
+        Self {depth, ..self}
+    }
+
+    /// Retrieve the format for the play field's border
+    ///
+    pub fn border(&self) -> SGR {
+        self.palette().border.sgr(self.depth)
+    }
+
+    /// Retrieve the format for regular text
+    ///
+    pub fn text(&self) -> SGR {
+        self.palette().text.sgr(self.depth)
+    }
+
+    /// Retrieve the format for highlighted score board rows
+    ///
+    pub fn highlight(&self) -> SGR {
+        self.palette().highlight.sgr(self.depth)
+    }
+
+    /// Retrieve the format for capsule elements of the given colour
+    ///
+    pub fn capsule(&self, colour: util::Colour) -> SGR {
+        self.palette().capsules[index(colour)].sgr(self.depth)
+    }
+
+    /// Retrieve the format for viruses of the given colour
+    ///
+    pub fn virus(&self, colour: util::Colour) -> SGR {
+        self.palette().viruses[index(colour)].sgr(self.depth)
+    }
+
+    fn palette(&self) -> &'static Palette {
+        &PALETTES[self.palette]
+    }
+}
+
+#[cfg(test)]
+impl quickcheck::Arbitrary for Theme {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        let palette = *g.choose(&(0..PALETTES.len()).collect::<Vec<_>>()).unwrap();
+        Self {palette, depth: quickcheck::Arbitrary::arbitrary(g)}
+    }
+}
+
+
+/// Colour depth supported by a terminal
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum ColourDepth {
+    /// Only the 8 basic colours (and their light variants)
+    Basic,
+    /// The 256-colour palette
+    #[default]
+    Indexed,
+    /// 24-bit RGB colours
+    True,
+}
+
+impl ColourDepth {
+    /// Return the next colour depth, wrapping around after the last one
+    ///
+    pub fn next(self) -> Self {
+        match self {
+            Self::Basic     => Self::Indexed,
+            Self::Indexed   => Self::True,
+            Self::True      => Self::Basic,
+        }
+    }
+}
+
+impl fmt::Display for ColourDepth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic     => write!(f, "8 colours"),
+            Self::Indexed   => write!(f, "256 colours"),
+            Self::True      => write!(f, "24-bit"),
+        }
+    }
+}
+
+#[cfg(test)]
+impl quickcheck::Arbitrary for ColourDepth {
+    fn arbitrary(g: &mut quickcheck::Gen) -> Self {
+        *g.choose(&[Self::Basic, Self::Indexed, Self::True]).unwrap()
+    }
+}
+
+
+/// Built-in palette
+///
+struct Palette {
+    name: &'static str,
+    border: Ink,
+    text: Ink,
+    highlight: Ink,
+    capsules: [Ink; 6],
+    viruses: [Ink; 6],
+}
+
+
+/// Colour of a palette, with a basic colour as fallback
+///
+#[derive(Copy, Clone, Debug)]
+struct Ink {
+    basic: Option<(Colour, Brightness)>,
+    rgb: Option<(u8, u8, u8)>,
+}
+
+impl Ink {
+    /// Create an ink using the terminal's default colour
+    ///
+    const fn default() -> Self {
+        Self {basic: None, rgb: None}
+    }
+
+    /// Create an ink using only a basic colour
+    ///
+    const fn basic(colour: Colour, brightness: Brightness) -> Self {
+        Self {basic: Some((colour, brightness)), rgb: None}
+    }
+
+    /// Create an ink from an RGB value with the given basic colour as fallback
+    ///
+    const fn rgb(rgb: (u8, u8, u8), colour: Colour, brightness: Brightness) -> Self {
+        Self {basic: Some((colour, brightness)), rgb: Some(rgb)}
+    }
+
+    /// Retrieve the SGR selecting this ink as foreground colour
+    ///
+    /// For the 256-colour palette, the RGB value is approximated using the
+    /// palette's colour cube.
+    ///
+    fn sgr(self, depth: ColourDepth) -> SGR {
+        match (self.rgb, depth) {
+            (Some((r, g, b)), ColourDepth::True) => SGR::FGExtended(ExtendedColour::Rgb(r, g, b)),
+            (Some((r, g, b)), ColourDepth::Indexed) =>
+                SGR::FGExtended(ExtendedColour::Indexed(16 + 36 * cube_level(r) + 6 * cube_level(g) + cube_level(b))),
+            _ => SGR::FGColour(self.basic),
+        }
+    }
+}
+
+
+/// Determine the level in the 256-colour palette's colour cube closest to a value
+///
+/// The levels of the cube's axes correspond to the values 0, 95, 135, 175, 215
+/// and 255.
+///
+fn cube_level(value: u8) -> u8 {
+    match value {
+        0..=47      => 0,
+        48..=114    => 1,
+        v           => (v - 35) / 40,
+    }
+}
+
+
+/// Determine the index of a colour in a palette's colour lists
+///
+fn index(colour: util::Colour) -> usize {
+    use util::Colour as C;
+
+    match colour {
+        C::Red      => 0,
+        C::Yellow   => 1,
+        C::Blue     => 2,
+        C::Green    => 3,
+        C::Magenta  => 4,
+        C::Cyan     => 5,
+    }
+}
+
+
+/// Built-in palettes
+///
+/// The first palette, which uses only the terminal's basic colours, is the
+/// default.
+///
+const PALETTES: [Palette; 3] = [
+    Palette {
+        name: "classic",
+        border: Ink::default(),
+        text: Ink::default(),
+        highlight: Ink::default(),
+        capsules: [
+            Ink::basic(Colour::Red,     Brightness::Dark),
+            Ink::basic(Colour::Yellow,  Brightness::Dark),
+            Ink::basic(Colour::Blue,    Brightness::Dark),
+            Ink::basic(Colour::Green,   Brightness::Dark),
+            Ink::basic(Colour::Magenta, Brightness::Dark),
+            Ink::basic(Colour::Cyan,    Brightness::Dark),
+        ],
+        viruses: [
+            Ink::basic(Colour::Red,     Brightness::Dark),
+            Ink::basic(Colour::Yellow,  Brightness::Dark),
+            Ink::basic(Colour::Blue,    Brightness::Dark),
+            Ink::basic(Colour::Green,   Brightness::Dark),
+            Ink::basic(Colour::Magenta, Brightness::Dark),
+            Ink::basic(Colour::Cyan,    Brightness::Dark),
+        ],
+    },
+    Palette {
+        name: "pastel",
+        border: Ink::rgb((150, 150, 200), Colour::Blue, Brightness::Light),
+        text: Ink::rgb((220, 220, 220), Colour::White, Brightness::Dark),
+        highlight: Ink::rgb((255, 215, 175), Colour::Yellow, Brightness::Light),
+        capsules: [
+            Ink::rgb((255, 140, 140), Colour::Red,     Brightness::Light),
+            Ink::rgb((255, 240, 150), Colour::Yellow,  Brightness::Light),
+            Ink::rgb((140, 170, 255), Colour::Blue,    Brightness::Light),
+            Ink::rgb((150, 230, 150), Colour::Green,   Brightness::Light),
+            Ink::rgb((230, 150, 230), Colour::Magenta, Brightness::Light),
+            Ink::rgb((150, 230, 230), Colour::Cyan,    Brightness::Light),
+        ],
+        viruses: [
+            Ink::rgb((215, 95, 95),   Colour::Red,     Brightness::Dark),
+            Ink::rgb((215, 200, 95),  Colour::Yellow,  Brightness::Dark),
+            Ink::rgb((95, 120, 215),  Colour::Blue,    Brightness::Dark),
+            Ink::rgb((95, 185, 95),   Colour::Green,   Brightness::Dark),
+            Ink::rgb((185, 95, 185),  Colour::Magenta, Brightness::Dark),
+            Ink::rgb((95, 185, 185),  Colour::Cyan,    Brightness::Dark),
+        ],
+    },
+    Palette {
+        name: "contrast",
+        border: Ink::rgb((255, 255, 255), Colour::White, Brightness::Light),
+        text: Ink::rgb((255, 255, 255), Colour::White, Brightness::Light),
+        highlight: Ink::rgb((255, 215, 0), Colour::Yellow, Brightness::Light),
+        capsules: [
+            Ink::rgb((255, 0, 0),     Colour::Red,     Brightness::Light),
+            Ink::rgb((255, 255, 0),   Colour::Yellow,  Brightness::Light),
+            Ink::rgb((0, 135, 255),   Colour::Blue,    Brightness::Light),
+            Ink::rgb((0, 255, 0),     Colour::Green,   Brightness::Light),
+            Ink::rgb((255, 0, 255),   Colour::Magenta, Brightness::Light),
+            Ink::rgb((0, 255, 255),   Colour::Cyan,    Brightness::Light),
+        ],
+        viruses: [
+            Ink::rgb((175, 0, 0),     Colour::Red,     Brightness::Dark),
+            Ink::rgb((175, 175, 0),   Colour::Yellow,  Brightness::Dark),
+            Ink::rgb((0, 0, 215),     Colour::Blue,    Brightness::Dark),
+            Ink::rgb((0, 175, 0),     Colour::Green,   Brightness::Dark),
+            Ink::rgb((175, 0, 175),   Colour::Magenta, Brightness::Dark),
+            Ink::rgb((0, 175, 175),   Colour::Cyan,    Brightness::Dark),
+        ],
+    },
+];
+
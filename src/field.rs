@@ -12,7 +12,7 @@ mod tick;
 pub mod tests;
 
 
-pub use items::Update;
+pub use items::{Appearance, CapsuleSource, Special, Update};
 pub use static_field::{StaticField, defeated};
 pub use moving_field::{MovingField, MovingRowIndex};
 pub use tick::{settle_elements, eliminate_elements, unsettle_elements};
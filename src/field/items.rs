@@ -33,6 +33,7 @@ impl Virus {
 #[derive(PartialEq)]
 pub struct CapsuleElement {
     colour: Colour,
+    special: Option<Special>,
     /// Direction of any capsule element bound to this one
     ///
     pub partner: Option<Direction>,
@@ -42,7 +43,15 @@ impl CapsuleElement {
     /// Create a new capsule element
     ///
     pub fn new(colour: Colour, partner: Option<Direction>) -> Self {
-        Self {colour, partner}
+        Self {colour, special: None, partner}
+    }
+
+    /// Create a new capsule element with the given appearance
+    ///
+    /// The appearance's partner is ignored in favour of the given `partner`.
+    ///
+    pub fn with_appearance(appearance: Appearance, partner: Option<Direction>) -> Self {
+        Self {colour: appearance.colour, special: appearance.special, partner}
     }
 
     /// Create a new unbound capsule element
@@ -58,6 +67,18 @@ impl CapsuleElement {
     pub fn colour(&self) -> Colour {
         self.colour
     }
+
+    /// Retrieve the capsule element's special kind, if any
+    ///
+    pub fn special(&self) -> Option<Special> {
+        self.special
+    }
+
+    /// Retrieve the capsule element's appearance
+    ///
+    pub fn appearance(&self) -> Appearance {
+        Appearance {colour: self.colour, special: self.special, partner: self.partner}
+    }
 }
 
 impl AsCapsuleElement for Option<CapsuleElement> {
@@ -70,9 +91,102 @@ impl util::PotentiallyColoured for Option<CapsuleElement> {
     fn colour(&self) -> Option<Colour> {
         self.as_ref().map(|e| e.colour())
     }
+
+    fn is_wildcard(&self) -> bool {
+        self.as_ref().map(|e| e.special() == Some(Special::Rainbow)).unwrap_or(false)
+    }
+}
+
+
+/// Special capsule elements
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Special {
+    /// Element clearing the surrounding area once settled
+    Bomb,
+    /// Element matching any colour
+    Rainbow,
+}
+
+
+/// Appearance of a capsule element
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Appearance {
+    pub colour: Colour,
+    pub special: Option<Special>,
+    /// Direction of any capsule element bound to the element
+    ///
+    pub partner: Option<Direction>,
+}
+
+impl Appearance {
+    /// Return this appearance with the given partner
+    ///
+    pub fn with_partner(self, partner: Option<Direction>) -> Self {
+        Self {partner, ..self}
+    }
+}
+
+impl From<Colour> for Appearance {
+    fn from(colour: Colour) -> Self {
+        Self {colour, special: None, partner: None}
+    }
+}
+
+impl util::PotentiallyColoured for Option<Appearance> {
+    fn colour(&self) -> Option<Colour> {
+        self.map(|a| a.colour)
+    }
+
+    fn is_wildcard(&self) -> bool {
+        self.map(|a| a.special == Some(Special::Rainbow)).unwrap_or(false)
+    }
+}
+
+
+/// Source of the capsules spawned for players
+///
+/// Capsule colours are drawn from a palette. If special elements are enabled,
+/// a capsule will occasionally contain a bomb or a rainbow element.
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CapsuleSource {
+    palette: util::Palette,
+    specials: bool,
+}
+
+impl CapsuleSource {
+    /// Create a new capsule source
+    ///
+    pub fn new(palette: util::Palette, specials: bool) -> Self {
+        Self {palette, specials}
+    }
+
+    /// Retrieve the palette capsule colours are drawn from
+    ///
+    pub fn palette(&self) -> util::Palette {
+        self.palette
+    }
+
+    /// Generate the appearance of a new capsule
+    ///
+    pub fn generate(&self, rng: &mut impl rand::Rng) -> [Appearance; 2] {
+        let mut res = [Appearance::from(rng.sample(self.palette)), rng.sample(self.palette).into()];
+        if self.specials && rng.gen_range(0..SPECIAL_CHANCE) == 0 {
+            let special = if rng.gen() { Special::Bomb } else { Special::Rainbow };
+            res[rng.gen_range(0..res.len())].special = Some(special);
+        }
+        res
+    }
 }
 
 
+/// Inverse probability of a capsule containing a special element
+///
+const SPECIAL_CHANCE: u32 = 12;
+
+
 /// Item which may represent a capsule element
 ///
 pub trait AsCapsuleElement {
@@ -88,22 +202,28 @@ pub trait AsCapsuleElement {
 /// Representation of a field update
 ///
 /// A tuple of this kind may be used to convey updates in the play field. If it
-/// contains a `Colour`, it represents a capsule elements emerging at the given
-/// position. A `None` will represent the tile to become free.
+/// contains an `Appearance`, it represents a capsule elements emerging at the
+/// given position. A `None` will represent the tile to become free.
 ///
-pub type Update = (util::Position, Option<Colour>);
+pub type Update = (util::Position, Option<Appearance>);
 
 
 /// Find rows of four or more tiles of the same colour
 ///
-/// This function finds horizontal and vertical configurations of at least four
-/// tiles with the same colour. Only configurations which include the given
-/// position will be considered. If such a configuration is found, it is
-/// returned alongside the colour of that row.
+/// This function finds horizontal and vertical configurations of tiles with the
+/// same colour which are at least as long as the `rule` demands. If the `rule`
+/// says so, diagonal configurations are also considered. Only configurations
+/// which include the given position will be considered. If such a
+/// configuration is found, it is returned alongside the colour of that row.
+///
+/// Wildcard tiles match any colour. If the tile at the given position is a
+/// wildcard, the colours of its neighbours are considered, including diagonal
+/// ones if the `rule` considers diagonal rows.
 ///
 pub fn row_of_four<F>(
     field: &F,
-    hint: util::Position
+    hint: util::Position,
+    rule: util::MatchRule,
 ) -> Option<(Colour, RowOfFour)>
     where F: std::ops::Index<util::Position>,
           F::Output: util::PotentiallyColoured
@@ -111,45 +231,82 @@ pub fn row_of_four<F>(
     use util::Direction as Dir;
     use util::PotentiallyColoured;
 
-    const ROW_OF_FOUR_LEN: usize = 4;
+    fn step(pos: util::Position, dirs: &[Dir]) -> Option<util::Position> {
+        dirs.iter().try_fold(pos, |p, d| p + *d)
+    }
 
-    field[hint]
-        .colour()
-        .and_then(|col| {
-            let positions_towards = |dir| std::iter::successors(Some(hint), move |p| *p + dir)
-                .take_while(|p| field[*p].colour() == Some(col))
+    let len = usize::from(rule.length);
+    let long_enough = |row: &RowOfFour| row.len() >= len;
+
+    const STRAIGHT: [&[Dir]; 4] = [&[Dir::Left], &[Dir::Right], &[Dir::Above], &[Dir::Below]];
+    const DIAGONAL: [&[Dir]; 4] = [
+        &[Dir::Above, Dir::Left],
+        &[Dir::Above, Dir::Right],
+        &[Dir::Below, Dir::Left],
+        &[Dir::Below, Dir::Right],
+    ];
+
+    let candidates: Vec<_> = if field[hint].is_wildcard() {
+        STRAIGHT
+            .iter()
+            .chain(DIAGONAL.iter().filter(|_| rule.diagonal))
+            .filter_map(|d| step(hint, d))
+            .filter(|p| !field[*p].is_wildcard())
+            .filter_map(|p| field[p].colour())
+            .collect()
+    } else {
+        field[hint].colour().into_iter().collect()
+    };
+
+    candidates
+        .into_iter()
+        .find_map(|col| {
+            let matches = |p: &util::Position| field[*p].is_wildcard() || field[*p].colour() == Some(col);
+            let positions_towards = |dirs: &'static [Dir]| std::iter::successors(Some(hint), move |p| step(*p, dirs))
+                .take_while(matches)
                 .last()
                 .expect("Position of tile with hint's colour");
 
-            let columns = util::RangeInclusive::new(
-                positions_towards(Dir::Left).1,
-                positions_towards(Dir::Right).1
-            );
-            if columns.len() >= ROW_OF_FOUR_LEN {
-                return Some((col, RowOfFour::Horizontal(hint.0, columns)))
-            }
-
-            let rows = util::RangeInclusive::new(
-                positions_towards(Dir::Above).0,
-                positions_towards(Dir::Below).0
-            );
-            if rows.len() >= ROW_OF_FOUR_LEN {
-                Some((col, RowOfFour::Vertical(rows, hint.1)))
-            } else {
-                None
-            }
+            let horizontal = || RowOfFour::Horizontal(hint.0, util::RangeInclusive::new(
+                positions_towards(&[Dir::Left]).1,
+                positions_towards(&[Dir::Right]).1,
+            ));
+            let vertical = || RowOfFour::Vertical(util::RangeInclusive::new(
+                positions_towards(&[Dir::Above]).0,
+                positions_towards(&[Dir::Below]).0,
+            ), hint.1);
+            let descending = || {
+                let (first, last) = (positions_towards(&[Dir::Above, Dir::Left]), positions_towards(&[Dir::Below, Dir::Right]));
+                RowOfFour::Descending(util::RangeInclusive::new(first.0, last.0), util::RangeInclusive::new(first.1, last.1))
+            };
+            let ascending = || {
+                let (first, last) = (positions_towards(&[Dir::Below, Dir::Left]), positions_towards(&[Dir::Above, Dir::Right]));
+                RowOfFour::Ascending(util::RangeInclusive::new(last.0, first.0), util::RangeInclusive::new(first.1, last.1))
+            };
+
+            Some(horizontal())
+                .filter(long_enough)
+                .or_else(|| Some(vertical()).filter(long_enough))
+                .or_else(|| Some(descending()).filter(|r| rule.diagonal && long_enough(r)))
+                .or_else(|| Some(ascending()).filter(|r| rule.diagonal && long_enough(r)))
+                .map(|r| (col, r))
         })
 }
 
 
-/// Representation of a vertical or horizontal configuration of elements
+/// Representation of a configuration of elements in a line
 ///
 /// This type is intended as an output type for the `row_of_four` function.
+/// Diagonal configurations are represented by the ranges of rows and columns
+/// they span. A `Descending` diagonal runs from the top left to the bottom
+/// right, an `Ascending` one from the bottom left to the top right.
 ///
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RowOfFour {
     Horizontal(util::RowIndex, util::RangeInclusive<util::ColumnIndex>),
     Vertical(util::RangeInclusive<util::RowIndex>, util::ColumnIndex),
+    Descending(util::RangeInclusive<util::RowIndex>, util::RangeInclusive<util::ColumnIndex>),
+    Ascending(util::RangeInclusive<util::RowIndex>, util::RangeInclusive<util::ColumnIndex>),
 }
 
 impl ExactSizeIterator for RowOfFour {}
@@ -161,13 +318,17 @@ impl Iterator for RowOfFour {
         match self {
             Self::Horizontal(row, columns)  => columns.next().map(|c| (*row, c)),
             Self::Vertical(rows, column)    => rows.next().map(|r| (r, *column)),
+            Self::Descending(rows, columns) => rows.next().zip(columns.next()),
+            Self::Ascending(rows, columns)  => rows.next_back().zip(columns.next()),
         }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         match self {
-            Self::Horizontal(_, range)  => range.size_hint(),
-            Self::Vertical(range, _)    => range.size_hint(),
+            Self::Horizontal(_, range)      => range.size_hint(),
+            Self::Vertical(range, _)        => range.size_hint(),
+            Self::Descending(rows, columns) => rows.zip(*columns).size_hint(),
+            Self::Ascending(rows, columns)  => rows.zip(*columns).size_hint(),
         }
     }
 }
@@ -183,7 +344,25 @@ impl Arbitrary for RowOfFour {
             RowOfFour::Vertical(Arbitrary::arbitrary(g), Arbitrary::arbitrary(g))
         }
 
-        let opts = [horizontal, vertical];
+        fn diagonal(g: &mut Gen) -> (util::RangeInclusive<util::RowIndex>, util::RangeInclusive<util::ColumnIndex>) {
+            let rows: util::RangeInclusive<util::RowIndex> = Arbitrary::arbitrary(g);
+            let mut positions = rows.zip(util::RangeInclusive::<util::ColumnIndex>::arbitrary(g));
+            let first = positions.next().expect("Empty diagonal");
+            let last = positions.next_back().unwrap_or(first);
+            (util::RangeInclusive::new(first.0, last.0), util::RangeInclusive::new(first.1, last.1))
+        }
+
+        fn descending(g: &mut Gen) -> RowOfFour {
+            let (rows, columns) = diagonal(g);
+            RowOfFour::Descending(rows, columns)
+        }
+
+        fn ascending(g: &mut Gen) -> RowOfFour {
+            let (rows, columns) = diagonal(g);
+            RowOfFour::Ascending(rows, columns)
+        }
+
+        let opts = [horizontal, vertical, descending, ascending];
         g.choose(&opts).unwrap()(g)
     }
 }
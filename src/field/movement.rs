@@ -21,16 +21,18 @@ pub struct ControlledCapsule {
 impl ControlledCapsule {
     /// Spawn a new player controlled capsule
     ///
-    /// Place a new player controlled capsule in the given colours. The new
-    /// capsule will be placed in the (current) top row of the moving field.
+    /// Place a new player controlled capsule with the given appearance. The new
+    /// capsule will be placed in the (current) top row of the moving field, at
+    /// the center of a field with the given `dimensions`.
     ///
     pub fn spawn_capsule(
         moving_field: &mut MovingField,
-        colours: &[util::Colour; 2]
+        dimensions: util::Dimensions,
+        appearance: &[items::Appearance; 2]
     ) -> (Self, [items::Update; 2]) {
         use util::Step;
 
-        let rmid = util::ColumnIndex::LEFTMOST_COLUMN.forward_checked((util::FIELD_WIDTH/2).into())
+        let rmid = util::ColumnIndex::LEFTMOST_COLUMN.forward_checked((dimensions.width()/2).into())
             .expect("Failed to compute right position for new capsule");
         let lmid = rmid.backward_checked(1)
             .expect("Failed to compute left position for new capsule");
@@ -38,12 +40,16 @@ impl ControlledCapsule {
         let lmid = (util::RowIndex::TOP_ROW, lmid);
         let rmid = (util::RowIndex::TOP_ROW, rmid);
 
-        moving_field[lmid] = Some(items::CapsuleElement::new(colours[0], Some(util::Direction::Right)));
-        moving_field[rmid] = Some(items::CapsuleElement::new(colours[1], Some(util::Direction::Left)));
+        let appearance = [
+            appearance[0].with_partner(Some(util::Direction::Right)),
+            appearance[1].with_partner(Some(util::Direction::Left)),
+        ];
+        moving_field[lmid] = Some(items::CapsuleElement::with_appearance(appearance[0], appearance[0].partner));
+        moving_field[rmid] = Some(items::CapsuleElement::with_appearance(appearance[1], appearance[1].partner));
 
         (
             Self {row: moving_field.moving_row_index(util::RowIndex::TOP_ROW), column: lmid.1},
-            [(lmid, Some(colours[0])), (rmid, Some(colours[1]))]
+            [(lmid, Some(appearance[0])), (rmid, Some(appearance[1]))]
         )
     }
 
@@ -188,8 +194,6 @@ impl ControlledCapsule {
         transform_pos: impl Fn([util::Position; 2]) -> Option<[util::Position; 2]>,
         transform_element: impl Fn(items::CapsuleElement) -> items::CapsuleElement + Copy,
     ) -> Option<[items::Update; 4]> {
-        use util::PotentiallyColoured;
-
         let row = moving_field.row_index_from_moving(self.row);
         let opos = {
             let pos_a = (row, self.column);
@@ -203,9 +207,12 @@ impl ControlledCapsule {
 
         let tpos = transform_pos(opos)?;
 
-        if !tpos.iter().any(|p| static_field[*p].is_occupied()) {
+        if !tpos.iter().any(|p| static_field.is_blocked(*p)) {
             let mut element = [moving_field[opos[0]].take(), moving_field[opos[1]].take()];
-            let colour = [element[0].colour(), element[1].colour()];
+            let appearance = [
+                element[0].as_ref().map(items::CapsuleElement::appearance),
+                element[1].as_ref().map(items::CapsuleElement::appearance),
+            ];
             moving_field[tpos[0]] = element[0].take().map(transform_element);
             moving_field[tpos[1]] = element[1].take().map(transform_element);
             self.column = tpos
@@ -213,7 +220,7 @@ impl ControlledCapsule {
                 .find(|p| p.0 == row)
                 .expect("Controlled capsule left its row")
                 .1;
-            Some([(opos[0], None), (opos[1], None), (tpos[0], colour[0]), (tpos[1], colour[1])])
+            Some([(opos[0], None), (opos[1], None), (tpos[0], appearance[0]), (tpos[1], appearance[1])])
         } else {
             None
         }
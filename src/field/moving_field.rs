@@ -10,7 +10,7 @@ use super::row::Row;
 ///
 #[derive(Default)]
 pub struct MovingField {
-    data: [Row<Option<items::CapsuleElement>>; util::FIELD_HEIGHT as usize],
+    data: [Row<Option<items::CapsuleElement>>; util::MAX_FIELD_HEIGHT as usize],
     offset: usize,
 }
 
@@ -21,15 +21,18 @@ impl MovingField {
     /// order.
     ///
     pub fn tick(&mut self) -> impl Iterator<Item = items::Update> + '_ {
-        use util::PotentiallyColoured;
+        use util::Dimensions;
 
         self.offset = self.offset.checked_sub(1).unwrap_or(self.data.len() - 1);
 
-        util::ROWS
+        // The field is not aware of its actual dimensions. However, elements
+        // will always settle before leaving the field through the bottom.
+        Dimensions::MAXIMUM
+            .rows()
             .rev()
-            .flat_map(util::complete_row)
-            .filter_map(move |pos| if let Some(c) = self[pos].colour() {
-                Some((pos, Some(c)))
+            .flat_map(|r| Dimensions::MAXIMUM.complete_row(r))
+            .filter_map(move |pos| if let Some(a) = self[pos].as_ref().map(items::CapsuleElement::appearance) {
+                Some((pos, Some(a)))
             } else if (pos + util::Direction::Below).map(|p| self[p].is_some()).unwrap_or(false) {
                 Some((pos, None))
             } else {
@@ -51,7 +54,7 @@ impl MovingField {
         capsules
             .into_iter()
             .inspect(move |(i, c)| top_row[*i] = Some(items::CapsuleElement::new_single(*c)))
-            .map(|(i, c)| ((util::RowIndex::TOP_ROW, i), Some(c)))
+            .map(|(i, c)| ((util::RowIndex::TOP_ROW, i), Some(c.into())))
     }
 
     /// Crate a MovingRowIndex for a given mapped row
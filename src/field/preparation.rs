@@ -8,43 +8,51 @@ use crate::util;
 /// This function returns an iterator over positions and colours intended for
 /// initializing a field with viruses. The iterator will yield at most
 /// `number_of_virs` entries, all with positions in rows from the given
-/// `top_row` to the bottom row. The positions and colours as well as their
-/// ordering will be random.
+/// `top_row` to the bottom row of a field with the given `dimensions`. If those
+/// rows hold fewer tiles than `number_of_virs`, at most one entry per tile will
+/// be yielded. The positions and colours as well as their ordering will be
+/// random, with all colours drawn from the given `palette`.
 ///
-/// The returned positions and colours will not contain horizontal or vertical
-/// configurations of for or more tiles of the same colour.
+/// The returned positions and colours will not contain configurations of tiles
+/// of the same colour which would be eliminated under the given `rule`.
 ///
 pub fn prepare_field(
     rng: &mut impl rand::Rng,
+    dimensions: util::Dimensions,
+    palette: util::Palette,
+    rule: util::MatchRule,
     top_row: util::RowIndex,
     number_of_virs: u8,
 ) -> impl Iterator<Item = (util::Position, util::Colour)> + '_ {
     // We'll use a field of `Option<Colour>` detecting rows of our
     let mut field: PreparationField = Default::default();
 
-    let rows = util::RangeInclusive::new(top_row, util::RowIndex::BOTTOM_ROW);
-    let area = rows.len() * (util::FIELD_WIDTH as usize);
+    let rows = util::RangeInclusive::new(top_row, dimensions.bottom_row());
+    let area = rows.len() * (dimensions.width() as usize);
+
+    // We can't place more viruses than there are tiles in the area.
+    let number_of_virs = std::cmp::min(number_of_virs.into(), area);
 
     (0..number_of_virs).filter_map(move |virus_count| {
-        // Select a colour. If colouring the tile would result in a row of four,
-        // we'll select another colour through rotation. If we can't find a
-        // suitable colour, we'll give up but leave the tile occupied in the
-        // internal preparation field so that it won't be picked again.
-        let colour: util::Colour = rng.gen();
+        // Select a colour. If colouring the tile would result in a match of
+        // the length configured via the `rule`, we'll select another colour
+        // through rotation. If we can't find a suitable colour, we'll give up
+        // but leave the tile occupied in the internal preparation field so
+        // that it won't be picked again.
+        let colour = rng.sample(palette);
         let rotation_dir = rng.gen();
 
         // Select an unoccupied position and fill it with a colour
-        let unfilled = area - (virus_count as usize);
+        let unfilled = area - virus_count;
         rows.clone()
-            .flat_map(util::complete_row)
+            .flat_map(move |r| dimensions.complete_row(r))
             .filter(|p| field[*p].is_none())
             .nth(rng.gen_range(0..unfilled))
-            .and_then(|pos| [colour, colour.rotate(rotation_dir), colour.rotate(!rotation_dir)]
-                .iter()
-                .cloned()
+            .and_then(|pos| std::iter::successors(Some(colour), |c| Some(palette.rotate(*c, rotation_dir)))
+                .take(palette.len().into())
                 .find(|c| {
                     field[pos] = Some(*c);
-                    super::items::row_of_four(&field, pos).is_none()
+                    super::items::row_of_four(&field, pos, rule).is_none()
                 }).map(|c| (pos, c))
             )
     })
@@ -55,7 +63,7 @@ pub fn prepare_field(
 ///
 #[derive(Default)]
 struct PreparationField {
-    data: [super::row::Row<Option<util::Colour>>; util::FIELD_HEIGHT as usize],
+    data: [super::row::Row<Option<util::Colour>>; util::MAX_FIELD_HEIGHT as usize],
 }
 
 impl std::ops::IndexMut<util::Position> for PreparationField {
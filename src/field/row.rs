@@ -12,7 +12,7 @@ use crate::util;
 pub struct Row<T>
     where T: Default
 {
-    data: [T; util::FIELD_WIDTH as usize],
+    data: [T; util::MAX_FIELD_WIDTH as usize],
 }
 
 impl<T> std::ops::IndexMut<util::ColumnIndex> for Row<T>
@@ -10,7 +10,68 @@ use super::row::Row;
 ///
 #[derive(Default)]
 pub struct StaticField {
-    data: [Row<TileContents>; util::FIELD_HEIGHT as usize],
+    data: [Row<TileContents>; util::MAX_FIELD_HEIGHT as usize],
+    dimensions: util::Dimensions,
+}
+
+impl StaticField {
+    /// Create a new static field of the given size with the given viruses
+    ///
+    /// For each item in `viruses`, a virus with the given colour will be placed
+    /// in the tile on the given position.
+    ///
+    pub fn new(
+        dimensions: util::Dimensions,
+        viruses: impl IntoIterator<Item = (util::Position, util::Colour)>,
+    ) -> Self {
+        viruses.into_iter().fold(Self {data: Default::default(), dimensions}, |mut field, (pos, colour)| {
+            field[pos] = TileContents::Virus(Virus::new(colour));
+            field
+        })
+    }
+
+    /// Retrieve the field's dimensions
+    ///
+    pub fn dimensions(&self) -> util::Dimensions {
+        self.dimensions
+    }
+
+    /// Check whether the given position is blocked
+    ///
+    /// A position is blocked if it is occupied or outside the field.
+    ///
+    pub fn is_blocked(&self, pos: util::Position) -> bool {
+        !self.dimensions.contains(pos) || self[pos].is_occupied()
+    }
+
+    /// Raise the field's contents by one row
+    ///
+    /// All tiles are moved up by one row, discarding the contents of the top
+    /// row. The bottom row is filled with unbound capsule elements of the given
+    /// colours in the given columns, leaving all other tiles in that row free.
+    ///
+    /// This function returns whether the player is `defeated` after the shift.
+    ///
+    pub fn raise(&mut self, row: impl IntoIterator<Item = (util::ColumnIndex, util::Colour)>) -> bool {
+        use util::Direction as Dir;
+
+        let height: usize = self.dimensions.height().into();
+        self.data[..height].rotate_left(1);
+
+        // Elements which were bound to an element in the discarded row are no
+        // longer bound to anything.
+        self.dimensions.complete_row(util::RowIndex::TOP_ROW).for_each(|p| if let Some(e) = self[p].as_element_mut() {
+            if e.partner == Some(Dir::Above) {
+                e.partner = None
+            }
+        });
+
+        let bottom = self.dimensions.bottom_row();
+        self.data[usize::from(bottom)] = Default::default();
+        row.into_iter().for_each(|(c, colour)| self[(bottom, c)] = CapsuleElement::new_single(colour).into());
+
+        defeated(self)
+    }
 }
 
 impl std::ops::IndexMut<util::Position> for StaticField {
@@ -27,22 +88,6 @@ impl std::ops::Index<util::Position> for StaticField {
     }
 }
 
-/// Initialize a field from an iterator
-///
-/// For each item in the source iterator, a virus with the given colour will be
-/// placed in the tile on the given position.
-///
-impl std::iter::FromIterator<(util::Position, util::Colour)> for StaticField {
-    fn from_iter<T>(iter: T) -> Self
-        where T: IntoIterator<Item = (util::Position, util::Colour)>
-    {
-        iter.into_iter().fold(Default::default(), |mut field, (pos, colour)| {
-            field[pos] = TileContents::Virus(Virus::new(colour));
-            field
-        })
-    }
-}
-
 
 /// Representation of a single tile's contents
 ///
@@ -152,6 +197,13 @@ impl util::PotentiallyColoured for TileContents {
             Self::Virus(v) => Some(v.colour()),
         }
     }
+
+    fn is_wildcard(&self) -> bool {
+        match self {
+            Self::CapsuleElement(e) => e.special() == Some(items::Special::Rainbow),
+            _ => false,
+        }
+    }
 }
 
 
@@ -160,6 +212,6 @@ impl util::PotentiallyColoured for TileContents {
 /// This function returns true if any tile in the top row is occupied.
 ///
 pub fn defeated(field: &StaticField) -> bool {
-    util::COLUMNS.map(|c| (util::RowIndex::TOP_ROW, c)).any(|p| field[p].is_occupied())
+    field.dimensions.complete_row(util::RowIndex::TOP_ROW).any(|p| field[p].is_occupied())
 }
 
@@ -8,16 +8,20 @@ use super::*;
 
 
 #[quickcheck]
-fn full_tick_consistency(static_field: StaticField, moving_field: MovingField) -> bool {
+fn full_tick_consistency(
+    static_field: StaticField,
+    moving_field: MovingField,
+    match_rule: util::MatchRule,
+) -> bool {
     let mut static_field: static_field::StaticField = static_field.into();
     let mut moving_field = moving_field.instantiate_for(&static_field);
 
     let (settled, _) = tick::settle_elements(
         &mut moving_field,
         &mut static_field,
-        util::RowIndex::BOTTOM_ROW
+        util::Dimensions::default().bottom_row()
     );
-    let eliminated = tick::eliminate_elements(&mut static_field, &settled);
+    let eliminated = tick::eliminate_elements(&mut static_field, &settled, match_rule);
     tick::unsettle_elements(&mut moving_field, &mut static_field, &eliminated);
     moving_field.tick().fold((), |_, _| ());
 
@@ -55,7 +59,7 @@ fn settlement_lowest_unsettled(
     let (_, lowest) = tick::settle_elements(&mut moving_field, &mut static_field, bottom);
 
     let is_empty_to_bottom = |top| util::RangeInclusive::new(top, bottom)
-        .flat_map(util::complete_row)
+        .flat_map(|r| util::Dimensions::default().complete_row(r))
         .all(|p| moving_field[p].is_none());
 
     if let Some(lowest) = lowest {
@@ -70,7 +74,7 @@ fn settlement_lowest_unsettled(
 fn settlement_element_partnership(static_field: StaticField, moving_field: MovingField) -> bool {
     let mut static_field: static_field::StaticField = static_field.into();
     let mut moving_field = moving_field.instantiate_for(&static_field);
-    tick::settle_elements(&mut moving_field, &mut static_field, util::RowIndex::BOTTOM_ROW);
+    tick::settle_elements(&mut moving_field, &mut static_field, util::Dimensions::default().bottom_row());
     check_element_partnership(&static_field) && check_element_partnership(&moving_field)
 }
 
@@ -79,9 +83,9 @@ fn settlement_element_partnership(static_field: StaticField, moving_field: Movin
 fn settlement_tick(static_field: StaticField, moving_field: MovingField) -> bool {
     let mut static_field: static_field::StaticField = static_field.into();
     let mut moving_field = moving_field.instantiate_for(&static_field);
-    tick::settle_elements(&mut moving_field, &mut static_field, util::RowIndex::BOTTOM_ROW);
+    tick::settle_elements(&mut moving_field, &mut static_field, util::Dimensions::default().bottom_row());
     moving_field.tick().fold((), |_, _| ());
-    util::complete_row(util::RowIndex::TOP_ROW).all(|p| moving_field[p].is_none()) &&
+    util::Dimensions::default().complete_row(util::RowIndex::TOP_ROW).all(|p| moving_field[p].is_none()) &&
         check_overlaps(&static_field, &moving_field) &&
         check_element_partnership(&static_field) &&
         check_element_partnership(&moving_field)
@@ -89,22 +93,58 @@ fn settlement_tick(static_field: StaticField, moving_field: MovingField) -> bool
 
 
 #[quickcheck]
-fn elimination_result(field: StaticField, settled: Vec<util::Position>) -> bool {
+fn elimination_result(
+    field: StaticField,
+    settled: Vec<util::Position>,
+    match_rule: util::MatchRule,
+) -> bool {
     let mut field: static_field::StaticField = field.into();
-    tick::eliminate_elements(&mut field, &settled.into())
+    tick::eliminate_elements(&mut field, &settled.into(), match_rule)
         .positions()
         .all(|p| !field[p].is_occupied())
 }
 
 
 #[quickcheck]
-fn elimination_element_partnership(field: StaticField, settled: Vec<util::Position>) -> bool {
+fn elimination_element_partnership(
+    field: StaticField,
+    settled: Vec<util::Position>,
+    match_rule: util::MatchRule,
+) -> bool {
     let mut field: static_field::StaticField = field.into();
-    tick::eliminate_elements(&mut field, &settled.into());
+    tick::eliminate_elements(&mut field, &settled.into(), match_rule);
     check_element_partnership(&field)
 }
 
 
+#[quickcheck]
+fn elimination_bomb(
+    field: StaticField,
+    pos: util::Position,
+    colour: util::Colour,
+    match_rule: util::MatchRule,
+) -> TestResult {
+    use util::Direction as Dir;
+
+    let mut field: static_field::StaticField = field.into();
+    if !field.dimensions().contains(pos) || field[pos].is_occupied() {
+        return TestResult::discard()
+    }
+
+    let bomb = items::Appearance {colour, special: Some(items::Special::Bomb), partner: None};
+    field[pos] = items::CapsuleElement::with_appearance(bomb, None).into();
+    tick::eliminate_elements(&mut field, &vec![pos].into(), match_rule);
+
+    let cleared = [Some(pos), pos + Dir::Above, pos + Dir::Below]
+        .iter()
+        .flatten()
+        .flat_map(|p| [Some(*p), *p + Dir::Left, *p + Dir::Right])
+        .flatten()
+        .all(|p| !field[p].is_occupied());
+    TestResult::from_bool(cleared && check_element_partnership(&field))
+}
+
+
 #[quickcheck]
 fn unsettlement_consistency(
     static_field: StaticField,
@@ -132,8 +172,8 @@ fn unsettlement_occupation(
 ) -> bool {
     let mut static_field: static_field::StaticField = static_field.into();
     let mut moving_field = moving_field.instantiate_for(&static_field);
-    let occupied: Vec<_> = util::ROWS
-        .flat_map(util::complete_row)
+    let occupied: Vec<_> = util::Dimensions::default()
+        .positions()
         .filter(|p| static_field[*p].is_occupied() || moving_field[*p].is_some())
         .collect();
     tick::unsettle_elements(
@@ -141,8 +181,8 @@ fn unsettlement_occupation(
         &mut static_field,
         &tick::Eliminated::new(rows, Default::default())
     );
-    util::ROWS
-        .flat_map(util::complete_row)
+    util::Dimensions::default()
+        .positions()
         .filter(|p| static_field[*p].is_occupied() || moving_field[*p].is_some())
         .eq(occupied)
 }
@@ -168,7 +208,7 @@ fn unsettlement_tick(
 
     tick::unsettle_elements(&mut moving_field, &mut static_field, &tick::Eliminated::new(rows, exes));
     moving_field.tick().fold((), |_, _| ());
-    util::complete_row(util::RowIndex::TOP_ROW).all(|p| moving_field[p].is_none()) &&
+    util::Dimensions::default().complete_row(util::RowIndex::TOP_ROW).all(|p| moving_field[p].is_none()) &&
         check_overlaps(&static_field, &moving_field) &&
         check_element_partnership(&static_field) &&
         check_element_partnership(&moving_field)
@@ -176,14 +216,43 @@ fn unsettlement_tick(
 
 
 #[quickcheck]
-fn preparation_vir_count(seed: u64, top_row: util::RowIndex, vir_count: u8) -> TestResult {
+fn raise_shift(field: StaticField, row: Vec<(util::ColumnIndex, util::Colour)>) -> bool {
+    use util::Direction as Dir;
+
+    let mut field: static_field::StaticField = field.into();
+    let dimensions = field.dimensions();
+    let occupied: Vec<_> = dimensions
+        .positions()
+        .filter(|p| field[*p].is_occupied())
+        .filter_map(|p| p + Dir::Above)
+        .chain(row.iter().map(|(c, _)| (dimensions.bottom_row(), *c)))
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
+    let defeated = field.raise(row);
+    defeated == static_field::defeated(&field) &&
+        dimensions.positions().filter(|p| field[*p].is_occupied()).eq(occupied) &&
+        check_element_partnership(&field)
+}
+
+
+#[quickcheck]
+fn preparation_vir_count(
+    seed: u64,
+    palette: util::Palette,
+    match_rule: util::MatchRule,
+    top_row: util::RowIndex,
+    vir_count: u8,
+) -> TestResult {
     use rand::SeedableRng;
 
-    let area = util::RangeInclusive::new(top_row, util::RowIndex::BOTTOM_ROW).len() *
-        (util::FIELD_WIDTH as usize);
+    let dimensions = util::Dimensions::default();
+    let area = util::RangeInclusive::new(top_row, dimensions.bottom_row()).len() *
+        (dimensions.width() as usize);
     if area >= vir_count as usize {
         TestResult::from_bool(
-            preparation::prepare_field(&mut rand_pcg::Pcg64Mcg::seed_from_u64(seed), top_row, vir_count)
+            preparation::prepare_field(&mut rand_pcg::Pcg64Mcg::seed_from_u64(seed), dimensions, palette, match_rule, top_row, vir_count)
                 .count() <= vir_count.into()
         )
     } else {
@@ -193,14 +262,59 @@ fn preparation_vir_count(seed: u64, top_row: util::RowIndex, vir_count: u8) -> T
 
 
 #[quickcheck]
-fn preparation_unique_pos(seed: u64, top_row: util::RowIndex, vir_count: u8) -> TestResult {
+fn preparation_dimensions(
+    seed: u64,
+    palette: util::Palette,
+    match_rule: util::MatchRule,
+    dimensions: util::Dimensions,
+    top_row: u8,
+    vir_count: u8,
+) -> bool {
+    use std::convert::TryInto;
+
     use rand::SeedableRng;
 
-    let area = util::RangeInclusive::new(top_row, util::RowIndex::BOTTOM_ROW).len() *
-        (util::FIELD_WIDTH as usize);
+    let top_row: util::RowIndex = ((top_row % dimensions.height()) as usize)
+        .try_into()
+        .expect("Could not convert to row index");
+    let rows = util::RangeInclusive::new(top_row, dimensions.bottom_row());
+    let area = rows.len() * (dimensions.width() as usize);
+
+    let mut pos: Vec<_> = preparation::prepare_field(
+        &mut rand_pcg::Pcg64Mcg::seed_from_u64(seed),
+        dimensions,
+        palette,
+        match_rule,
+        top_row,
+        vir_count,
+    ).map(|(p, _)| p).collect();
+    pos.sort();
+
+    pos.len() <= std::cmp::min(vir_count.into(), area) &&
+        pos.windows(2).all(|p| p[0] != p[1]) &&
+        pos.iter().all(|p| rows.clone().any(|r| dimensions.complete_row(r).any(|q| q == *p)))
+}
+
+
+#[quickcheck]
+fn preparation_unique_pos(
+    seed: u64,
+    palette: util::Palette,
+    match_rule: util::MatchRule,
+    top_row: util::RowIndex,
+    vir_count: u8,
+) -> TestResult {
+    use rand::SeedableRng;
+
+    let dimensions = util::Dimensions::default();
+    let area = util::RangeInclusive::new(top_row, dimensions.bottom_row()).len() *
+        (dimensions.width() as usize);
     if area >= vir_count as usize {
         let mut pos: Vec<_> = preparation::prepare_field(
             &mut rand_pcg::Pcg64Mcg::seed_from_u64(seed),
+            dimensions,
+            palette,
+            match_rule,
             top_row,
             vir_count,
         ).map(|(p, _)| p).collect();
@@ -213,14 +327,21 @@ fn preparation_unique_pos(seed: u64, top_row: util::RowIndex, vir_count: u8) ->
 
 
 #[quickcheck]
-fn preparation_empty_rows(seed: u64, top_row: util::RowIndex, vir_count: u8) -> TestResult {
+fn preparation_empty_rows(
+    seed: u64,
+    palette: util::Palette,
+    match_rule: util::MatchRule,
+    top_row: util::RowIndex,
+    vir_count: u8,
+) -> TestResult {
     use rand::SeedableRng;
 
-    let area = util::RangeInclusive::new(top_row, util::RowIndex::BOTTOM_ROW).len() *
-        (util::FIELD_WIDTH as usize);
+    let dimensions = util::Dimensions::default();
+    let area = util::RangeInclusive::new(top_row, dimensions.bottom_row()).len() *
+        (dimensions.width() as usize);
     if area >= vir_count as usize {
         TestResult::from_bool(
-            preparation::prepare_field(&mut rand_pcg::Pcg64Mcg::seed_from_u64(seed), top_row, vir_count)
+            preparation::prepare_field(&mut rand_pcg::Pcg64Mcg::seed_from_u64(seed), dimensions, palette, match_rule, top_row, vir_count)
                 .all(|((r, _), _)| r >= top_row)
         )
     } else {
@@ -229,6 +350,61 @@ fn preparation_empty_rows(seed: u64, top_row: util::RowIndex, vir_count: u8) ->
 }
 
 
+#[quickcheck]
+fn preparation_palette(
+    seed: u64,
+    palette: util::Palette,
+    match_rule: util::MatchRule,
+    top_row: util::RowIndex,
+    vir_count: u8,
+) -> TestResult {
+    use rand::SeedableRng;
+
+    let dimensions = util::Dimensions::default();
+    let area = util::RangeInclusive::new(top_row, dimensions.bottom_row()).len() *
+        (dimensions.width() as usize);
+    if area >= vir_count as usize {
+        TestResult::from_bool(
+            preparation::prepare_field(&mut rand_pcg::Pcg64Mcg::seed_from_u64(seed), dimensions, palette, match_rule, top_row, vir_count)
+                .all(|(_, c)| palette.contains(c))
+        )
+    } else {
+        TestResult::discard()
+    }
+}
+
+
+#[quickcheck]
+fn preparation_no_rows(
+    seed: u64,
+    palette: util::Palette,
+    match_rule: util::MatchRule,
+    top_row: util::RowIndex,
+    vir_count: u8,
+) -> TestResult {
+    use rand::SeedableRng;
+
+    let dimensions = util::Dimensions::default();
+    let area = util::RangeInclusive::new(top_row, dimensions.bottom_row()).len() *
+        (dimensions.width() as usize);
+    if area >= vir_count as usize {
+        let mut field = TwoColouredField {data: Default::default(), omitted: util::Colour::Red};
+        let viruses: Vec<_> = preparation::prepare_field(
+            &mut rand_pcg::Pcg64Mcg::seed_from_u64(seed),
+            dimensions,
+            palette,
+            match_rule,
+            top_row,
+            vir_count,
+        ).collect();
+        viruses.iter().for_each(|(p, c)| field[*p] = Some(*c));
+        TestResult::from_bool(viruses.iter().all(|(p, _)| items::row_of_four(&field, *p, match_rule).is_none()))
+    } else {
+        TestResult::discard()
+    }
+}
+
+
 #[quickcheck]
 fn single_capsule_consitency(
     moves: Vec<movement::Movement>,
@@ -243,7 +419,7 @@ fn single_capsule_consitency(
     let static_field: static_field::StaticField = static_field.into();
 
     {
-        let rmid = util::ColumnIndex::LEFTMOST_COLUMN.forward_checked((util::FIELD_WIDTH/2).into())
+        let rmid = util::ColumnIndex::LEFTMOST_COLUMN.forward_checked((util::Dimensions::default().width()/2).into())
             .expect("Failed to compute right target position for capsule");
         let lmid = rmid.backward_checked(1)
             .expect("Failed to compute left target position for capsule");
@@ -252,7 +428,7 @@ fn single_capsule_consitency(
         }
     }
 
-    let (mut capsule, _) = movement::ControlledCapsule::spawn_capsule(&mut moving_field, &[a, b]);
+    let (mut capsule, _) = movement::ControlledCapsule::spawn_capsule(&mut moving_field, static_field.dimensions(), &[a.into(), b.into()]);
     let ticks = Step::steps_between(&util::RowIndex::TOP_ROW, &row).expect("Invalid row");
     (0..ticks).for_each(|_| moving_field.tick().fold((), |_, _| ()));
 
@@ -277,7 +453,7 @@ fn single_capsule_output(
     let mut moving_field = moving_field::MovingField::default();
     let static_field: static_field::StaticField = static_field.into();
 
-    let rmid = util::ColumnIndex::LEFTMOST_COLUMN.forward_checked((util::FIELD_WIDTH/2).into())
+    let rmid = util::ColumnIndex::LEFTMOST_COLUMN.forward_checked((util::Dimensions::default().width()/2).into())
         .expect("Failed to compute right target position for capsule");
     let lmid = rmid.backward_checked(1)
         .expect("Failed to compute left target position for capsule");
@@ -285,7 +461,7 @@ fn single_capsule_output(
         return TestResult::discard()
     }
 
-    let (mut capsule, _) = movement::ControlledCapsule::spawn_capsule(&mut moving_field, &[a, b]);
+    let (mut capsule, _) = movement::ControlledCapsule::spawn_capsule(&mut moving_field, static_field.dimensions(), &[a.into(), b.into()]);
     let ticks = Step::steps_between(&util::RowIndex::TOP_ROW, &row).expect("Invalid row");
     (0..ticks).for_each(|_| moving_field.tick().fold((), |_, _| ()));
 
@@ -294,7 +470,7 @@ fn single_capsule_output(
         // values will also overwrite earlier values for the same key when
         // collecting into a `HashMap`.
         let updates: std::collections::HashMap<_, _> = updates.iter().cloned().collect();
-        updates.into_iter().all(|(p, c)| moving_field[p].colour() == c)
+        updates.into_iter().all(|(p, c)| moving_field[p].colour() == c.colour())
     } else {
         moving_field[(row, lmid)].colour() == Some(a) && moving_field[(row, rmid)].colour() == Some(b)
     };
@@ -342,7 +518,7 @@ fn tick_output(field: MovingField) -> bool {
 
     let mut field = field.instantiate_for(&Default::default());
     let updates: Vec<_> = field.tick().collect();
-    updates.into_iter().all(|(p, c)| field[p].colour() == c)
+    updates.into_iter().all(|(p, c)| field[p].colour() == c.colour())
 }
 
 
@@ -353,23 +529,64 @@ fn row_of_four_len(row: items::RowOfFour) -> bool {
 
 
 #[quickcheck]
-fn find_row_of_four(original: items::RowOfFour, mut field: TwoColouredField, pick: u8) -> bool {
+fn find_row_of_four(
+    original: items::RowOfFour,
+    mut field: TwoColouredField,
+    pick: u8,
+    rule: util::MatchRule,
+) -> bool {
     original.for_each(|p| field[p] = Some(field.omitted));
     let hint = original.cycle().nth(pick as usize).expect("Could not pick hint");
 
-    let expected = if original.len() >= 4 {
+    let expected = if original.len() >= rule.length.into() && (rule.diagonal || !is_diagonal(original)) {
         Some((field.omitted, original))
     } else {
         None
     };
-    items::row_of_four(&field, hint) == expected
+    items::row_of_four(&field, hint, rule) == expected
 }
 
 
 #[quickcheck]
-fn find_no_row_of_four(mut field: TwoColouredField, pos: util::Position) -> bool {
+fn find_rainbow_row_of_four(
+    original: items::RowOfFour,
+    colour: util::Colour,
+    pick: u8,
+    rainbow: u8,
+    rule: util::MatchRule,
+) -> bool {
+    let mut field = static_field::StaticField::new(util::Dimensions::MAXIMUM, std::iter::empty());
+    original.for_each(|p| field[p] = items::CapsuleElement::new_single(colour).into());
+
+    let rainbow = original.cycle().nth(rainbow as usize).expect("Could not pick rainbow");
+    let appearance = items::Appearance {
+        colour: util::Palette::MAXIMUM.rotate(colour, true),
+        special: Some(items::Special::Rainbow),
+        partner: None,
+    };
+    field[rainbow] = items::CapsuleElement::with_appearance(appearance, None).into();
+    let hint = original.cycle().nth(pick as usize).expect("Could not pick hint");
+
+    let expected = if original.len() >= rule.length.into() && (rule.diagonal || !is_diagonal(original)) {
+        Some((colour, original))
+    } else {
+        None
+    };
+    items::row_of_four(&field, hint, rule) == expected
+}
+
+
+#[quickcheck]
+fn find_no_row_of_four(mut field: TwoColouredField, pos: util::Position, rule: util::MatchRule) -> bool {
     field[pos] = None;
-    items::row_of_four(&field, pos).is_none()
+    items::row_of_four(&field, pos, rule).is_none()
+}
+
+
+/// Check whether the given row is a diagonal one
+///
+fn is_diagonal(row: items::RowOfFour) -> bool {
+    matches!(row, items::RowOfFour::Descending(..) | items::RowOfFour::Ascending(..))
 }
 
 
@@ -377,7 +594,7 @@ fn find_no_row_of_four(mut field: TwoColouredField, pos: util::Position) -> bool
 ///
 #[derive(Clone, Debug)]
 struct TwoColouredField {
-    data: [row::Row<Option<util::Colour>>; util::FIELD_HEIGHT as usize],
+    data: [row::Row<Option<util::Colour>>; util::MAX_FIELD_HEIGHT as usize],
     pub omitted: util::Colour,
 }
 
@@ -397,12 +614,13 @@ impl std::ops::Index<util::Position> for TwoColouredField {
 
 impl Arbitrary for TwoColouredField {
     fn arbitrary(g: &mut Gen) -> Self {
-        let mut data: [_; util::FIELD_HEIGHT as usize] = Default::default();
+        let mut data: [_; util::MAX_FIELD_HEIGHT as usize] = Default::default();
         let omitted = util::Colour::arbitrary(g);
-        let opts = [Some(omitted.rotate(true)), Some(omitted.rotate(false)), None];
+        let palette = util::Palette::MAXIMUM;
+        let opts = [Some(palette.rotate(omitted, true)), Some(palette.rotate(omitted, false)), None];
         data.fill_with(|| {
             let mut row: row::Row<_> = Default::default();
-            util::COLUMNS.for_each(|c| row[c] = *g.choose(&opts).unwrap());
+            util::Dimensions::MAXIMUM.columns().for_each(|c| row[c] = *g.choose(&opts).unwrap());
             row
         });
         Self {data, omitted}
@@ -410,7 +628,7 @@ impl Arbitrary for TwoColouredField {
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
         let field = self.clone();
-        let res = util::ROWS.flat_map(util::complete_row).filter_map(move |p| {
+        let res = util::Dimensions::MAXIMUM.positions().filter_map(move |p| {
             let mut field = field.clone();
             std::mem::take(&mut field[p]).map(|_| field)
         });
@@ -448,7 +666,11 @@ impl SettledField {
                 .collect();
 
             let oldlen = capsules.len();
-            capsules.retain(|c| c.positions().filter_map(|p| p + Below).all(|p| occupied.contains(&p)));
+            capsules.retain(|c| c
+                .positions()
+                .filter_map(|p| p + Below)
+                .filter(|p| util::Dimensions::default().contains(*p))
+                .all(|p| occupied.contains(&p)));
             if oldlen == capsules.len() {
                 break Self {viruses, capsules}
             }
@@ -458,7 +680,7 @@ impl SettledField {
 
 impl From<SettledField> for static_field::StaticField {
     fn from(field: SettledField) -> Self {
-        let mut res: Self = std::iter::FromIterator::from_iter(field.viruses);
+        let mut res = Self::new(Default::default(), field.viruses);
         field.capsules.into_iter().for_each(|c| c.place_on(&mut res));
         res
     }
@@ -503,7 +725,7 @@ impl StaticField {
 
 impl From<StaticField> for static_field::StaticField {
     fn from(field: StaticField) -> Self {
-        let mut res: Self = std::iter::FromIterator::from_iter(field.viruses);
+        let mut res = Self::new(Default::default(), field.viruses);
         field.capsules.into_iter().for_each(|c| c.place_on(&mut res));
         res
     }
@@ -552,7 +774,7 @@ impl MovingField {
         let mut res: moving_field::MovingField = Default::default();
         RandomCapsule::consistent_capsules(
             self.capsules.iter().cloned(),
-            util::ROWS.flat_map(util::complete_row).filter(|p| field[*p].is_occupied()),
+            util::Dimensions::default().positions().filter(|p| field[*p].is_occupied()),
         ).for_each(|c| c.place_on(&mut res));
         res
     }
@@ -597,7 +819,7 @@ impl RandomCapsule {
               F::Output: From<items::CapsuleElement> + util::PotentiallyColoured,
     {
         field[self.pos] = items::CapsuleElement::new(self.colour, self.partner.map(|(d, _)| d)).into();
-        if let Some((d, c, p)) = self.partner.and_then(|(d, c)| (self.pos + d).map(|p| (d, c, p))) {
+        if let Some((d, c, p)) = self.partner.and_then(|(d, c)| self.partner_pos().map(|p| (d, c, p))) {
             field[p] = items::CapsuleElement::new(c, Some(d.rotated_cw().rotated_cw())).into();
         }
     }
@@ -611,7 +833,7 @@ impl RandomCapsule {
         let mut occupied: std::collections::HashSet<_> = std::iter::FromIterator::from_iter(occupied);
 
         capsules.into_iter().filter_map(move |mut c| if occupied.insert(c.pos) {
-            if c.partner.and_then(|(d, _)| c.pos + d).map(|p| !occupied.insert(p)).unwrap_or(true) {
+            if c.partner_pos().map(|p| !occupied.insert(p)).unwrap_or(true) {
                 c.partner = None;
             }
             Some(c)
@@ -623,7 +845,15 @@ impl RandomCapsule {
     /// Positions occupied by the capsule
     ///
     pub fn positions(&self) -> impl Iterator<Item = util::Position> + Clone {
-        std::iter::once(self.pos).chain(self.partner.and_then(|(d, _)| self.pos + d))
+        std::iter::once(self.pos).chain(self.partner_pos())
+    }
+
+    /// Position of the partner element, if it lies within the field
+    ///
+    fn partner_pos(&self) -> Option<util::Position> {
+        self.partner
+            .and_then(|(d, _)| self.pos + d)
+            .filter(|p| util::Dimensions::default().contains(*p))
     }
 }
 
@@ -656,8 +886,8 @@ where F: std::ops::Index<util::Position>,
 {
     use items::AsCapsuleElement;
 
-    util::ROWS
-        .flat_map(util::complete_row)
+    util::Dimensions::default()
+        .positions()
         .filter_map(|p| field[p].as_element().and_then(|c| c.partner).map(|d| (p, d)))
         .all(|(p, d)| (p + d)
             .and_then(|p| field[p].as_element())
@@ -671,8 +901,8 @@ fn check_overlaps(
     static_field: &static_field::StaticField,
     moving_field: &moving_field::MovingField,
 ) -> bool {
-    !util::ROWS
-        .flat_map(util::complete_row)
+    !util::Dimensions::default()
+        .positions()
         .any(|p| moving_field[p].is_some() && static_field[p].is_occupied())
 }
 
@@ -26,12 +26,14 @@ pub fn settle_elements(
 ) -> (Settled, Option<util::RowIndex>) {
     use util::Direction as Dir;
 
+    let dimensions = static_field.dimensions();
+
     // Settle elements, collecting their position
     let mut settled: Vec<_> = Default::default();
     util::RangeInclusive::new(util::RowIndex::TOP_ROW, lowest)
         .rev()
-        .flat_map(util::complete_row)
-        .for_each(|pos| if (pos + Dir::Below).map(|p| static_field[p].is_occupied()).unwrap_or(true) {
+        .flat_map(|r| dimensions.complete_row(r))
+        .for_each(|pos| if (pos + Dir::Below).map(|p| static_field.is_blocked(p)).unwrap_or(true) {
             // The tile below is occupied. Hence, we must move elements in the
             // current tile. However, we must not free the tile in the static
             // field but only transfer elements.
@@ -54,7 +56,7 @@ pub fn settle_elements(
     // Determine the new lowest row with unsettled elements
     let lowest = util::RangeInclusive::new(util::RowIndex::TOP_ROW, lowest)
         .rev()
-        .find(|r| util::complete_row(*r).any(|p| moving_field[p].is_some()));
+        .find(|r| dimensions.complete_row(*r).any(|p| moving_field[p].is_some()));
 
     (Settled {elements: settled}, lowest)
 }
@@ -95,25 +97,39 @@ impl From<Vec<util::Position>> for Settled {
 ///
 /// This function eliminates rows of four from the field of settled elements.
 /// These rows of four are detected based on hints provided in the form of
-/// settled elements. The function will return a type encapsulating the
-/// individual rows.
+/// settled elements. Only rows satisfying the given `rule` are considered. In
+/// addition, settled bombs clear the 3x3 area centered on them. The function
+/// will return a type encapsulating the individual rows and blasts.
 ///
 pub fn eliminate_elements(
     field: &mut StaticField,
-    settled: &Settled
+    settled: &Settled,
+    rule: util::MatchRule,
 ) -> Eliminated {
-    use super::items::row_of_four;
+    use super::items::{Special, row_of_four};
+    use util::Direction as Dir;
+
+    let rows: HashSet<_> = settled.iter().filter_map(|p| row_of_four(field, *p, rule)).collect();
+
+    let dimensions = field.dimensions();
+    let blasts: HashSet<_> = settled
+        .iter()
+        .filter(|p| field[**p].as_element().and_then(|e| e.special()) == Some(Special::Bomb))
+        .flat_map(|p| [Some(*p), *p + Dir::Above, *p + Dir::Below].iter().flatten().copied().collect::<Vec<_>>())
+        .flat_map(|p| [Some(p), p + Dir::Left, p + Dir::Right].iter().flatten().copied().collect::<Vec<_>>())
+        .filter(|p| dimensions.contains(*p) && field[*p].is_occupied())
+        .collect();
 
-    let rows: HashSet<_> = settled.iter().filter_map(|p| row_of_four(field, *p)).collect();
     let exes: HashSet<_> = rows
         .iter()
         .flat_map(|(_, p)| p.clone())
+        .chain(blasts.iter().cloned())
         .filter_map(|p| field[p].take().into_element().and_then(|e| e.partner).and_then(|d| p + d))
         .collect();
     exes.iter().for_each(|p| if let Some(e) = field[*p].as_element_mut() {
         e.partner = None
     });
-    Eliminated {rows, exes}
+    Eliminated {rows, blasts, exes}
 }
 
 
@@ -122,6 +138,7 @@ pub fn eliminate_elements(
 pub struct Eliminated {
     // We use a hashset in order to prevent registering the same row twice.
     rows: HashSet<(util::Colour, RowOfFour)>,
+    blasts: HashSet<util::Position>,
     exes: HashSet<util::Position>,
 }
 
@@ -141,7 +158,16 @@ impl Eliminated {
     /// Retrieve the positions of eliminated elements
     ///
     pub fn positions(&self) -> impl Iterator<Item = util::Position> + '_ {
-        self.rows_of_four().flat_map(|(_, p)| p.clone())
+        self.rows_of_four().flat_map(|(_, p)| p.clone()).chain(self.blasts.iter().cloned())
+    }
+
+    /// Retrieve the positions of former partners of eliminated elements
+    ///
+    /// Some of these positions may refer to elements which were eliminated
+    /// themselves.
+    ///
+    pub fn exes(&self) -> impl Iterator<Item = util::Position> + '_ {
+        self.exes.iter().cloned()
     }
 }
 
@@ -151,7 +177,7 @@ impl Eliminated {
         rows: HashSet<(util::Colour, RowOfFour)>,
         exes: HashSet<util::Position>,
     ) -> Self {
-        Self {rows, exes}
+        Self {rows, blasts: Default::default(), exes}
     }
 }
 
@@ -179,7 +205,7 @@ pub fn unsettle_elements(
         .exes
         .iter()
         .cloned()
-        .filter(|p| !(*p + Dir::Below).map(|p| static_field[p].is_occupied()).unwrap_or(true))
+        .filter(|p| !(*p + Dir::Below).map(|p| static_field.is_blocked(p)).unwrap_or(true))
         .chain(eliminated.positions().filter_map(|p| p + Dir::Above))
         .collect();
 
@@ -189,7 +215,7 @@ pub fn unsettle_elements(
             let partner_supported = partner
                 .and_then(|p| p + Dir::Below)
                 .filter(|p| *p != pos)
-                .map(|p| static_field[p].is_occupied())
+                .map(|p| static_field.is_blocked(p))
                 .unwrap_or(false);
             if !partner_supported {
                 let to_move = std::iter::once(pos)
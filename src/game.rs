@@ -3,6 +3,10 @@
 mod lobby;
 mod waiting;
 mod round;
+mod rules;
+mod standings;
+mod summary;
+mod tournament;
 
 #[cfg(test)]
 mod tests;
@@ -13,91 +17,235 @@ use std::sync::Arc;
 
 use tokio::io;
 use tokio::net;
-use tokio::sync::{RwLock, watch};
+use tokio::sync::{RwLock, broadcast, mpsc, watch};
 
+use crate::display;
 use crate::error;
 use crate::player;
 use crate::util;
 
 
 pub use lobby::LobbyControl;
+pub use rules::WinCondition;
+
+
+/// A new connection handed to a game, along with the peer's address
+///
+pub type Arrival = (Connection<net::tcp::OwnedReadHalf, net::tcp::OwnedWriteHalf>, std::net::SocketAddr);
 
 
 /// Run the game
 ///
 /// This function implements the the overall game phase logic. During the lobby
-/// phase, connections will be accepted via the given `listener` and new players
-/// are added to the `roster`.
+/// phase, connections will be received via the given `intake` and new players
+/// are added to the `roster`. Connections still queued once the lobby phase is
+/// over are handed back via `bounce`. Noteworthy events are sent via `reports`.
 ///
 pub async fn run<R>(
-    listener: net::TcpListener,
+    mut intake: mpsc::Receiver<Arrival>,
+    bounce: mpsc::UnboundedSender<Arrival>,
     lobby_control: watch::Receiver<lobby::LobbyControl>,
     roster: Arc<RwLock<player::Roster>>,
     phase: watch::Sender<GamePhase<R>>,
     phase_receiver: watch::Receiver<GamePhase<R>>,
+    reports: broadcast::Sender<Report>,
 ) -> Result<(), error::WrappedErr>
 where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
 {
-    use crate::field::prepare_field;
+    use crate::field::{CapsuleSource, prepare_field};
+    use error::TryExt;
     use error::WrappedErr as E;
     use util::Step;
 
+    let enter = |p: GamePhase<R>| {
+        let name = p.to_string();
+        phase.send(p).map_err(|e| E::new("Could not send phase updates", e))?;
+        report(&reports, Report::Phase{phase: name});
+        Ok::<_, E>(())
+    };
+
     log::info!("Starting lobby");
     let (ports, control) = lobby::ports();
-    phase.send(GamePhase::Lobby{ports}).map_err(|e| E::new("Could not send phase updates", e))?;
-    let (game_control, mut disconnects) = lobby::control(
+    enter(GamePhase::Lobby{ports})?;
+    let lobby = lobby::control(
         control,
         lobby_control,
         phase_receiver,
-        listener,
+        &mut intake,
         serve_connection,
         roster.clone(),
-    ).await.unwrap();
+        &reports,
+    ).await;
 
+    // Connections which didn't make it into the lobby are handed back
+    intake.close();
+    while let Some(arrival) = intake.recv().await {
+        bounce.send(arrival).ok().or_warn("Could not hand back connection");
+    }
+    let (game_control, mut notifications) = lobby.map_err(|e| E::new("Lobby failed", e))?;
+
+    // Report players leaving before passing them on to the control functions
+    let (disconnect_sender, mut disconnects) = mpsc::unbounded_channel();
+    tokio::spawn({
+        let reports = reports.clone();
+        async move {
+            while let Some(tag) = notifications.recv().await {
+                report(&reports, Report::Left{player: tag.name().to_string()});
+                if disconnect_sender.send(tag).is_err() {
+                    break
+                }
+            }
+        }
+    });
+
+    // In tournament mode, only the players of the current match participate
+    let match_size = match *game_control.borrow() {
+        GameControl::Settings{match_size, ..} => match_size,
+        GameControl::EndOfGame => 0,
+    };
+    let mut bracket = if match_size > 1 {
+        log::info!("Setting up tournament bracket");
+        Some(tournament::Bracket::new(roster.read().await.clone(), match_size.into()))
+    } else {
+        None
+    };
+
+    let mut condition = Default::default();
+    let mut recap = Vec::new();
     let mut num = 1;
+    let mut decided = false;
 
     while !game_control.borrow().is_end_of_game() {
-        log::info!("Beginning pre-round waiting");
-        let (ports, control) = waiting::ports(roster.read().await.clone());
-        phase.send(GamePhase::Waiting{ports}).map_err(|e| E::new("Could not send phase updates", e))?;
-        waiting::control(control, game_control.clone(), roster.clone(), &mut disconnects).await;
-
-        let mut rng = R::from_entropy();
-        let (viruses, tick_duration): (HashMap<_, _>, _) = match game_control.borrow().clone() {
-            GameControl::Settings{viruses, tick} => {
-                let first_row = util::RowIndex::TOP_ROW.forward_checked(FREE_ROWS)
-                    .expect("Not enough rows to keep free");
-                (prepare_field(&mut rng, first_row, viruses).collect(), tick)
-            },
-            GameControl::EndOfGame => break,
+        let players = if let Some(bracket) = bracket.as_mut() {
+            match bracket.next_match() {
+                Some(players) => players,
+                None => break,
+            }
+        } else if !decided {
+            roster.read().await.clone()
+        } else {
+            break
+        };
+
+        // Play rounds until the match is decided
+        let mut record = rules::Record::new(players.clone());
+        decided = loop {
+            condition = match *game_control.borrow() {
+                GameControl::Settings{win_condition, ..} => win_condition,
+                GameControl::EndOfGame => break false,
+            };
+            if bracket.is_some() {
+                // A tournament match is decided by a single round by default
+                if condition == WinCondition::None {
+                    condition = WinCondition::Rounds(1);
+                }
+                if players.iter().filter(|p| p.is_connected()).count() < 2 {
+                    break true
+                }
+            }
+            if record.is_decided(condition) {
+                break true
+            }
+
+            log::info!("Beginning pre-round waiting");
+            let (ports, control) = waiting::ports(players.clone(), bracket.clone().map(Arc::new), recap.clone());
+            enter(GamePhase::Waiting{ports})?;
+            let participants = Arc::new(RwLock::new(players.clone()));
+            waiting::control(control, game_control.clone(), participants.clone(), &mut disconnects).await;
+
+            let mut rng = R::from_entropy();
+            let settings = match game_control.borrow().clone() {
+                GameControl::Settings{dimensions, palette, match_length, diagonal, specials, viruses, tick, effects, ..} => {
+                    let match_rule = util::MatchRule {length: match_length, diagonal};
+                    let first_row = util::RowIndex::TOP_ROW.forward_checked(FREE_ROWS)
+                        .expect("Not enough rows to keep free");
+                    let viruses: HashMap<_, _> = prepare_field(&mut rng, dimensions, palette, match_rule, first_row, viruses)
+                        .collect();
+                    let source = CapsuleSource::new(palette, specials);
+                    round::Settings {dimensions, source, match_rule, viruses, tick_duration: tick, effects}
+                },
+                GameControl::EndOfGame => break false,
+            };
+
+            log::info!("Starting round {}", num);
+            let virus_count = settings.viruses.len() as u32;
+            let (dimensions, palette) = (settings.dimensions, settings.source.palette());
+            let (ports, control) = round::ports(participants.read().await.clone(), virus_count, reports.clone());
+            enter(GamePhase::Round{ports, settings, rng: rng.clone(), num})?;
+            let players = participants.read().await.iter().map(|p| p.name().to_string()).collect();
+            report(&reports, Report::RoundStart{num, players});
+            let ranking = round::control(control, participants, dimensions, palette, virus_count, &mut disconnects, &mut rng).await?;
+            recap = ranking.iter().map(|(t, _, s)| (t.clone(), s.clone())).collect();
+            let ranking: Vec<_> = ranking.into_iter().map(|(t, s, _)| (t, s)).collect();
+            report(&reports, Report::RoundEnd{
+                num,
+                ranking: ranking.iter().map(|(t, s)| (t.name().to_string(), *s)).collect(),
+            });
+            if let Some(winner) = record.add_round(ranking.as_ref()) {
+                log::info!("{} won round {}", winner.name(), num);
+                report(&reports, Report::RoundWon{num, player: winner.name().to_string()});
+            }
+
+            num = num + 1;
         };
 
-        log::info!("Starting round {}", num);
-        let virus_count = viruses.len() as u32;
-        let (ports, control) = round::ports(roster.read().await.clone(), virus_count);
-        phase
-            .send(GamePhase::Round{ports, viruses, tick_duration, rng: rng.clone(), num})
-            .map_err(|e| E::new("Could not send phase updates", e))?;
-        round::control(control, roster.clone(), virus_count, &mut disconnects, &mut rng).await?;
+        if !decided {
+            break
+        }
+
+        let winner = record.winner(condition).ok_or_else(|| E::new("No winner for match", error::NoneError))?;
+        log::info!("{} won the match", winner.name());
+        report(&reports, Report::MatchWon{player: winner.name().to_string()});
+        if let Some(bracket) = bracket.as_mut() {
+            bracket.report(winner);
+        }
+    }
 
-        num = num + 1;
+    let mut ranking = roster.read().await.clone();
+    condition.rank(&mut ranking, |p| p.wins(), |p| p.score());
+    if let Some(winner) = bracket.as_ref().and_then(tournament::Bracket::winner).cloned() {
+        log::info!("{} won the tournament", winner.name());
+        report(&reports, Report::TournamentWon{player: winner.name().to_string()});
+        ranking.retain(|p| *p != winner);
+        ranking.insert(0, winner);
+    }
+
+    if decided {
+        log::info!("Showing final standings");
+        let (ports, control) = standings::ports(ranking.clone());
+        enter(GamePhase::Standings{ports})?;
+        standings::control(control, game_control.clone(), &mut disconnects).await;
     }
 
     log::info!("Ending game");
-    phase.send(GamePhase::End).map_err(|e| E::new("Could not send final phase updates", e))
+    enter(GamePhase::End{ranking: ranking.into()})
+}
+
+
+/// Report an event to observers of the game
+///
+fn report(reports: &broadcast::Sender<Report>, report: Report) {
+    // Nobody may be listening, which is fine
+    reports.send(report).ok();
 }
 
 
 /// Serve a given connection
 ///
 async fn serve_connection(
-    connection: net::TcpStream,
+    connection: Connection<impl io::AsyncRead + Unpin, impl io::AsyncWrite + Send + Unpin>,
     phase: watch::Receiver<GamePhase<impl rand::Rng + Clone>>,
     token: lobby::ConnectionToken,
 ) {
     use crate::error::TryExt;
+    use crate::metrics::METRICS;
 
-    match do_serve(connection, phase, token).await {
+    let _connected = METRICS.players_connected.track();
+    let res = do_serve(connection, phase, token).await;
+    if let Err(e) = &res {
+        METRICS.conn_task_errors.get(e.kind()).inc();
+    }
+    match res {
         Err(ConnTaskError::Terminated) => log::info!("Player disconnected"),
         e => { e.or_warn("Lost player"); },
     }
@@ -107,23 +255,32 @@ async fn serve_connection(
 /// Actual connection logic
 ///
 async fn do_serve(
-    connection: net::TcpStream,
+    connection: Connection<impl io::AsyncRead + Unpin, impl io::AsyncWrite + Send + Unpin>,
     phase: watch::Receiver<GamePhase<impl rand::Rng + Clone>>,
     token: lobby::ConnectionToken,
 ) -> Result<(), ConnTaskError> {
-    use crate::display::Display;
-
     use {GamePhase as P, TransitionWatcher as W};
 
-    connection.set_nodelay(true)?;
-    let (conn_in, conn_out) = connection.into_split();
-    let mut display = Display::new(conn_out, DISPLAY_HEIGHT, DISPLAY_WIDTH);
-    let mut input = ASCIIStream::new(conn_in, Default::default());
+    use crate::metrics::METRICS;
+
+    let Connection {mut display, mut input} = connection;
 
     let mut me: Option<player::Handle> = Default::default();
 
     loop {
         let p = phase.borrow().clone();
+        let spectating = match &p {
+            P::Round{ports, ..} => me.as_ref().map(|m| !ports.is_participant(&m.tag())).unwrap_or(false),
+            _ => false,
+        };
+        let _task = METRICS.phase_tasks.get(match &p {
+            P::Lobby{..}                => "lobby",
+            P::Waiting{..}              => "waiting",
+            P::Round{..} if spectating  => "spectating",
+            P::Round{..}                => "round",
+            P::Standings{..}            => "standings",
+            P::End{..}                  => "summary",
+        }).track();
         match p {
             P::Lobby{ports} => me = lobby::serve(
                 ports,
@@ -139,17 +296,34 @@ async fn do_serve(
                 W::new(phase.clone(), |p| if let P::Waiting{..} = p { false } else { true }),
                 me.as_ref().ok_or_else(|| ConnTaskError::other(error::NoneError))?,
             ).await?,
-            P::Round{ports, viruses, tick_duration, rng, ..} => round::serve(
+            P::Round{ports, ..} if spectating => round::spectate(
+                    ports,
+                    &mut display,
+                    &mut input,
+                    W::new(phase.clone(), |p| !matches!(p, P::Round{..})),
+                ).await?,
+            P::Round{ports, settings, rng, ..} => round::serve(
                 ports,
                 &mut display,
                 &mut input,
                 W::new(phase.clone(), |p| if let P::Round{..} = p { false } else { true }),
                 me.as_ref().ok_or_else(|| ConnTaskError::other(error::NoneError))?,
-                viruses,
-                tick_duration,
+                settings,
                 rng,
             ).await?,
-            P::End => break Ok(()),
+            P::Standings{ports} => standings::serve(
+                ports,
+                &mut display,
+                &mut input,
+                W::new(phase.clone(), |p| !matches!(p, P::Standings{..})),
+                me.as_ref().ok_or_else(|| ConnTaskError::other(error::NoneError))?,
+            ).await?,
+            P::End{ranking} => break summary::serve(
+                ranking,
+                &mut display,
+                &mut input,
+                me.as_ref(),
+            ).await,
         }
     }
 }
@@ -160,14 +334,9 @@ async fn do_serve(
 pub enum GamePhase<R: rand::Rng> {
     Lobby{ports: lobby::Ports},
     Waiting{ports: waiting::Ports},
-    Round{
-        ports: round::Ports,
-        viruses: HashMap<util::Position, util::Colour>,
-        tick_duration: std::time::Duration,
-        rng: R,
-        num: u32,
-    },
-    End,
+    Round{ports: round::Ports, settings: round::Settings, rng: R, num: u32},
+    Standings{ports: standings::Ports},
+    End{ranking: Arc<[player::Tag]>},
 }
 
 impl<R: rand::Rng> GamePhase<R> {
@@ -175,15 +344,27 @@ impl<R: rand::Rng> GamePhase<R> {
     ///
     pub fn is_end_of_game(&self) -> bool {
         match self {
-            Self::End => true,
+            Self::End{..} => true,
             _ => false,
         }
     }
 }
 
+impl<R: rand::Rng> fmt::Display for GamePhase<R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Lobby{..}         => write!(f, "lobby"),
+            Self::Waiting{..}       => write!(f, "waiting"),
+            Self::Round{num, ..}    => write!(f, "round {}", num),
+            Self::Standings{..}     => write!(f, "standings"),
+            Self::End{..}           => write!(f, "end"),
+        }
+    }
+}
+
 impl<R: rand::Rng> Default for GamePhase<R> {
     fn default() -> Self {
-        GamePhase::End
+        GamePhase::End{ranking: Arc::new([])}
     }
 }
 
@@ -230,10 +411,26 @@ impl<P, F: Fn(&P) -> bool> TransitionWatcher<P, F> {
 #[derive(Clone, Debug)]
 pub enum GameControl {
     Settings{
+        /// Dimensions of the play fields
+        dimensions: util::Dimensions,
+        /// Colours viruses and capsules are drawn from
+        palette: util::Palette,
+        /// Minimum number of tiles in a row eliminating them
+        match_length: util::MatchLength,
+        /// Whether diagonal rows are eliminated
+        diagonal: bool,
+        /// Whether capsules may contain special elements
+        specials: bool,
         /// Number of visuses a field is initialized with
         viruses: u8,
         /// Duration of a tick
         tick: std::time::Duration,
+        /// Whether eliminations are animated and chains indicated
+        effects: bool,
+        /// Number of players per tournament match, or `0` for a free-for-all
+        match_size: u8,
+        /// Condition deciding a match
+        win_condition: WinCondition,
     },
     EndOfGame,
 }
@@ -250,9 +447,91 @@ impl GameControl {
 }
 
 
+/// Noteworthy event occuring during a game
+///
+/// Reports are sent to observers such as game master consoles. Players are
+/// identified by their names.
+///
+#[derive(Clone, Debug)]
+pub enum Report {
+    /// A connection was accepted into the lobby
+    Connected{addr: std::net::SocketAddr},
+    /// A player registered
+    Registered{player: String},
+    /// A registered player disconnected
+    Left{player: String},
+    /// The game entered a new phase
+    Phase{phase: String},
+    /// A round started with the given players
+    RoundStart{num: u32, players: Vec<String>},
+    /// A round ended with the given ranking and round scores
+    RoundEnd{num: u32, ranking: Vec<(String, u32)>},
+    /// A player sent the given number of capsule elements to others
+    Capsules{player: String, count: u32},
+    /// A player sent the given number of garbage rows to others
+    Garbage{player: String, rows: u32},
+    /// The number of viruses remaining in a player's field changed
+    Score{player: String, viruses: u32},
+    /// A player topped out
+    Defeat{player: String},
+    /// A player won a round
+    RoundWon{num: u32, player: String},
+    /// A player won a match
+    MatchWon{player: String},
+    /// A player won the tournament
+    TournamentWon{player: String},
+}
+
+
+/// A player's connection
+///
+/// A connection consists of the display we draw on and the stream of characters
+/// we receive from the player.
+///
+pub struct Connection<R, W: io::AsyncWrite + Send + Unpin + 'static> {
+    display: display::Display<W>,
+    input: ASCIIStream<R>,
+}
+
+impl<R: io::AsyncRead, W: io::AsyncWrite + Send + Unpin + 'static> Connection<R, W> {
+    /// Create a new connection from the given reader and writer
+    ///
+    pub fn new(read: R, write: W) -> Self {
+        Self {
+            display: display::Display::new(write, DISPLAY_HEIGHT, DISPLAY_WIDTH),
+            input: ASCIIStream::new(read, Default::default()),
+        }
+    }
+
+    /// Retrieve both the display and the input stream
+    ///
+    pub fn split(&mut self) -> (&mut display::Display<W>, &mut ASCIIStream<R>) {
+        (&mut self.display, &mut self.input)
+    }
+}
+
+impl Connection<net::tcp::OwnedReadHalf, net::tcp::OwnedWriteHalf> {
+    /// Create a new connection from a TCP stream
+    ///
+    pub fn from_tcp(stream: net::TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let (read, write) = stream.into_split();
+        Ok(Self::new(read, write))
+    }
+}
+
+
+/// Character requesting a redraw of the display (Ctrl-L)
+///
+/// Connection tasks handle this character by calling `Display::redraw` with the
+/// entities they placed, which then repaint themselves.
+///
+pub const REDRAW_KEY: char = '\x0c';
+
+
 /// A stream of ASCII characters
 ///
-type ASCIIStream<R> = tokio_util::codec::FramedRead<R, ASCIICharDecoder>;
+pub type ASCIIStream<R> = tokio_util::codec::FramedRead<R, ASCIICharDecoder>;
 
 
 /// Decoder for single ASCII characters
@@ -262,7 +541,7 @@ type ASCIIStream<R> = tokio_util::codec::FramedRead<R, ASCIICharDecoder>;
 /// `UnexpectedEof` error.
 ///
 #[derive(Default, Debug)]
-struct ASCIICharDecoder {}
+pub struct ASCIICharDecoder {}
 
 impl tokio_util::codec::Decoder for ASCIICharDecoder {
     type Item = char;
@@ -312,6 +591,16 @@ impl ConnTaskError {
         Self::Other(Box::new(e))
     }
 
+    /// Retrieve a short name for the kind of error
+    ///
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Terminated    => "terminated",
+            Self::WouldBlock    => "would_block",
+            Self::Other(_)      => "other",
+        }
+    }
+
     /// Check whether this error just indicates that an operation would block
     ///
     pub fn is_would_block(&self) -> bool {
@@ -358,7 +647,7 @@ impl fmt::Display for ConnTaskError {
 /// Number of columns to split from the left
 ///
 /// The remeining columns are reserved for the score board.
-const COLUMN_SPLIT: u16 = 32;
+pub const COLUMN_SPLIT: u16 = 32;
 
 
 /// Number of rows to split from the top when displaying instructions
@@ -386,12 +675,12 @@ const INSTRUCTIONS: [&str; 8] = [
 
 /// Assumed width of the player's terminal
 ///
-const DISPLAY_WIDTH: u16 = 80;
+pub const DISPLAY_WIDTH: u16 = 80;
 
 
 /// Assumed height of the player's terminal
 ///
-const DISPLAY_HEIGHT: u16 = 24;
+pub const DISPLAY_HEIGHT: u16 = 24;
 
 
 /// Number of rows at the top to keep free when placing viruses
@@ -6,8 +6,7 @@ use std::sync::Arc;
 
 use log;
 use tokio::io;
-use tokio::net;
-use tokio::sync::{RwLock, mpsc, oneshot, watch};
+use tokio::sync::{RwLock, broadcast, mpsc, oneshot, watch};
 
 use crate::display;
 use crate::player;
@@ -36,6 +35,7 @@ pub async fn serve<P>(
 
 
     // Set up the display
+    let theme = display.options().theme;
     let mut area = display.area().await?.pad_top(1);
     let mut left = area.split_left(super::COLUMN_SPLIT);
     let mut reg = left.split_top(super::INSTRUCTION_SPLIT);
@@ -45,14 +45,14 @@ pub async fn serve<P>(
     let mut name_input = reg.place_top(
         display::LineInput::new((player::MAX_PLAYER_NAME_LEN as u16).try_into().unwrap())
     ).await?;
-    let reply_text = reg.place_center(
-        display::DynamicText::new(4u16.try_into().unwrap(), (super::COLUMN_SPLIT - 2).try_into().unwrap())
+    let mut reply_text = reg.place_center(
+        display::DynamicText::new(8u16.try_into().unwrap(), (super::COLUMN_SPLIT - 2).try_into().unwrap())
     ).await?;
 
     left.place_center(display::StaticText::from(&super::INSTRUCTIONS as &[_])).await?;
 
     let max_scores = area.rows().saturating_sub(2);
-    let mut score_board = area.place_center(display::ScoreBoard::new(max_scores).show_scores(false)).await?;
+    let mut score_board = area.place_center(display::ScoreBoard::new(max_scores).show_scores(false).theme(theme)).await?;
     {
         let scores = scores.borrow().clone();
         score_board.update(&mut display.handle().await?, scores.iter(), |_| false).await?
@@ -63,6 +63,7 @@ pub async fn serve<P>(
     let handle = loop {
         tokio::select!{
             res = input.next() => match res {
+                Some(Ok(super::REDRAW_KEY)) => display.redraw(&[&name_input, &reply_text, &score_board]).await?,
                 Some(Ok(c)) => {
                     let name = name_input
                         .update(&mut display.handle().await?, c)
@@ -100,18 +101,42 @@ pub async fn serve<P>(
         }
     };
 
-    let reg_msg = [
-        "You are now registered.",
-        "Please wait for the game",
-        "to start.",
+    let reg_msg = |options: display::RenderOptions| [
+        "You are now registered.".to_owned(),
+        "Please wait for the game".to_owned(),
+        "to start.".to_owned(),
+        Default::default(),
+        match options.glyphs {
+            display::Glyphs::Uniform => "G: glyphs  uniform".to_owned(),
+            display::Glyphs::Shapes  => "G: glyphs  shapes".to_owned(),
+        },
+        format!("T: theme   {}", options.theme.name()),
+        format!("C: colours {}", options.theme.depth()),
+        format!("U: unicode {}", if options.unicode { "on" } else { "off" }),
     ];
-    reply_text.update(&mut display.handle().await?, reg_msg.iter()).await?;
+    let options = display.options();
+    reply_text.update(&mut display.handle().await?, reg_msg(options).iter()).await?;
 
 
     // Wait for the transition, updating scores
     while !phase.transitioned() {
         tokio::select!{
             res = input.next() => match res {
+                Some(Ok(super::REDRAW_KEY)) => display.redraw(&[&name_input, &reply_text, &score_board]).await?,
+                Some(Ok(c @ 'g')) | Some(Ok(c @ 'G')) |
+                Some(Ok(c @ 't')) | Some(Ok(c @ 'T')) |
+                Some(Ok(c @ 'c')) | Some(Ok(c @ 'C')) |
+                Some(Ok(c @ 'u')) | Some(Ok(c @ 'U')) => {
+                    let options = display.options_mut();
+                    match c.to_ascii_lowercase() {
+                        'g' => options.glyphs = options.glyphs.toggled(),
+                        't' => options.theme = options.theme.next_palette(),
+                        'u' => options.unicode = !options.unicode,
+                        _   => options.theme = options.theme.with_depth(options.theme.depth().next()),
+                    }
+                    let options = *options;
+                    reply_text.update(&mut display.handle().await?, reg_msg(options).iter()).await?
+                },
                 Some(Err(e)) if !e.is_would_block() => return Err(e.into()),
                 None => return Err(ConnTaskError::Terminated),
                 _ => (),
@@ -134,21 +159,27 @@ pub async fn serve<P>(
 /// Lobby control function
 ///
 /// This function implements the central control logic for the lobby phase.
+/// New connections are received via the `intake` for as long as registration
+/// is accepted. Arrivals, registrations and departures are sent via `reports`.
 ///
-pub async fn control<F, P, O>(
+pub async fn control<F, P, O, C>(
     ports: ControlPorts,
     mut lobby_control: watch::Receiver<LobbyControl>,
     phase: watch::Receiver<P>,
-    listener: net::TcpListener,
+    intake: &mut mpsc::Receiver<(C, SocketAddr)>,
     serve_conn: F,
     roster: Arc<RwLock<player::Roster>>,
+    reports: &broadcast::Sender<super::Report>,
 ) -> io::Result<(watch::Receiver<super::GameControl>, mpsc::UnboundedReceiver<player::Tag>)>
-where F: Fn(net::TcpStream, watch::Receiver<P>, ConnectionToken) -> O + 'static + Send + Sync + Copy,
+where F: Fn(C, watch::Receiver<P>, ConnectionToken) -> O + 'static + Send + Sync + Copy,
       P: 'static + Send + Sync + std::fmt::Debug,
       O: std::future::Future<Output = ()> + Send,
+      C: 'static + Send,
 {
     use crate::error::TryExt;
 
+    use super::{Report, report};
+
     let scores = ports.scores;
     let mut registrations = ports.registration;
 
@@ -161,15 +192,17 @@ where F: Fn(net::TcpStream, watch::Receiver<P>, ConnectionToken) -> O + 'static
 
     loop {
         tokio::select! {
-            stream = listener.accept(), if accept => {
-                let (stream, peer) = stream?;
+            arrival = intake.recv(), if accept => {
+                let (conn, peer) = arrival.ok_or(io::ErrorKind::BrokenPipe)?;
                 log::info!("Accepting connection from {}", peer);
+                report(reports, Report::Connected{addr: peer});
+                crate::metrics::METRICS.connections.inc();
                 let token: ConnectionToken = peer.into();
 
                 let conn_task = tokio::spawn({
                     let token = token.clone();
                     let phase = phase.clone();
-                    async move { serve_conn(stream, phase, token).await }
+                    async move { serve_conn(conn, phase, token).await }
                 });
                 tokens.insert(token, conn_task);
             },
@@ -196,6 +229,7 @@ where F: Fn(net::TcpStream, watch::Receiver<P>, ConnectionToken) -> O + 'static
                     );
                     roster.push(handle.tag());
                     scores.send(roster.clone().into()).or_warn("Could not send updates");
+                    report(reports, Report::Registered{player: handle.tag().name().to_string()});
                     RegistrationReply::Accepted(handle)
                 } else {
                     log::warn!("No connection token found for {}", r.token.data);
@@ -206,7 +240,12 @@ where F: Fn(net::TcpStream, watch::Receiver<P>, ConnectionToken) -> O + 'static
             _ = player_notifications.recv() => {
                 let mut roster = roster.write().await;
                 let original_size = roster.len();
-                roster.retain(|p| p.is_connected());
+                roster.retain(|p| if p.is_connected() {
+                    true
+                } else {
+                    report(reports, Report::Left{player: p.name().to_string()});
+                    false
+                });
                 if roster.len() < original_size {
                     scores.send(roster.clone().into()).or_warn("Could not send updates");
                 }
@@ -4,7 +4,7 @@ use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
 use tokio::io;
-use tokio::sync::{Mutex, RwLock, mpsc, watch};
+use tokio::sync::{Mutex, RwLock, broadcast, mpsc, watch};
 use tokio::time;
 
 use crate::display;
@@ -25,8 +25,7 @@ pub async fn serve<P>(
     mut input: impl futures::stream::Stream<Item = Result<char, super::ConnTaskError>> + Unpin,
     mut phase: super::TransitionWatcher<P, impl Fn(&P) -> bool>,
     me: &player::Handle,
-    viruses: HashMap<util::Position, util::Colour>,
-    tick_diration: std::time::Duration,
+    settings: Settings,
     mut rng: impl rand::Rng,
 ) -> Result<(), super::ConnTaskError> {
     use std::convert::TryInto;
@@ -35,6 +34,8 @@ pub async fn serve<P>(
 
     use super::ConnTaskError;
 
+    let Settings {dimensions, source, match_rule, viruses, tick_duration, effects} = settings;
+
     let mut scores = control.scores;
     let events = control.events;
     let capsules = control
@@ -44,16 +45,26 @@ pub async fn serve<P>(
         .clone();
 
     // Set up display
+    let options = display.options();
     let mut area = display.area().await?.pad_top(1);
     let mut left = area.split_left(super::COLUMN_SPLIT);
 
-    let field = left.place_top(display::PlayField::default()).await?;
-    let indicator = left.place_center(
-        display::DynamicText::new(2u16.try_into().unwrap(), (super::COLUMN_SPLIT - 2).try_into().unwrap())
-    ).await?;
+    let mut field = left.place_top(display::PlayField::new(dimensions, effects).glyphs(options.glyphs).theme(options.theme).unicode(options.unicode)).await?;
+    let indicator = display::DynamicText::new(
+        2u16.try_into().unwrap(),
+        (super::COLUMN_SPLIT - 2).try_into().unwrap(),
+    );
+    let mut indicator = if left.rows() >= 2 {
+        left.place_center(indicator).await?
+    } else {
+        // Tall fields may not leave enough room below them
+        let indicator = area.place_top(indicator).await?;
+        area = area.pad_top(1);
+        indicator
+    };
 
     let max_scores = area.rows().saturating_sub(2);
-    let mut score_board = area.place_center(display::ScoreBoard::new(max_scores)).await?;
+    let mut score_board = area.place_center(display::ScoreBoard::new(max_scores).theme(options.theme)).await?;
     let highlight = {
         let tag = me.tag();
         move |t: &player::Tag| *t == tag
@@ -64,37 +75,48 @@ pub async fn serve<P>(
     }
 
 
-    let next_colours = rng.gen();
+    let next_capsule = source.generate(&mut rng);
     let mut virus_sym = Default::default();
     field.place_viruses(&mut display.handle().await?, viruses.clone().into_iter(), virus_sym).await?;
-    field.place_next_elements(&mut display.handle().await?, &next_colours).await?;
-    let mut actor = Actor::new(events, capsules, me.tag(), viruses, next_colours);
+    field.place_next_elements(&mut display.handle().await?, &next_capsule).await?;
+    let mut actor = Actor::new(
+        events,
+        capsules,
+        me.tag(),
+        field::StaticField::new(dimensions, viruses),
+        source,
+        match_rule,
+        next_capsule,
+    );
 
     // Let the player grasp the field for a bit before the game starts
     time::sleep(GRACE_PERIOD).await;
 
     // Kick off the actual game
-    let mut tick_timer = Timer::new(tick_diration);
+    let mut tick_timer = Timer::new(tick_duration);
     let mut virs_timer = time::interval(time::Duration::from_secs(1));
+    let mut anim_timer = time::interval(EXPLOSION_FRAME);
+    anim_timer.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
     while !actor.is_defeated() && actor.virus_count() > 0{
         use field::Movement as M;
 
         tokio::select! {
             res = input.next() => match res {
+                Some(Ok(super::REDRAW_KEY)) => display.redraw(&[&field, &indicator, &score_board]).await?,
                 Some(Ok('p')) | Some(Ok('P')) | Some(Ok('\x1b')) if !tick_timer.is_paused() => {
                     tick_timer.pause();
                     indicator.update_single(&mut display.handle().await?, "Game paused").await?
                 },
                 Some(Ok('s')) | Some(Ok('S')) if !tick_timer.is_paused() =>
-                    actor.r#move(&mut display.handle().await?, &field, M::Left).await?,
+                    actor.r#move(&mut display.handle().await?, &mut field, M::Left).await?,
                 Some(Ok('d')) | Some(Ok('D')) if !tick_timer.is_paused() =>
-                    actor.r#move(&mut display.handle().await?, &field, M::Right).await?,
+                    actor.r#move(&mut display.handle().await?, &mut field, M::Right).await?,
                 Some(Ok('k')) | Some(Ok('K')) if !tick_timer.is_paused() =>
-                    actor.r#move(&mut display.handle().await?, &field, M::RotateCCW).await?,
+                    actor.r#move(&mut display.handle().await?, &mut field, M::RotateCCW).await?,
                 Some(Ok('l')) | Some(Ok('L')) if !tick_timer.is_paused() =>
-                    actor.r#move(&mut display.handle().await?, &field, M::RotateCW).await?,
+                    actor.r#move(&mut display.handle().await?, &mut field, M::RotateCW).await?,
                 Some(Ok(' ')) if !tick_timer.is_paused() => if actor.is_controlled() {
-                    actor.tick(&mut display.handle().await?, &field, &mut rng).await?
+                    actor.tick(&mut display.handle().await?, &mut field, &mut rng).await?
                 },
                 Some(Ok(c)) => if tick_timer.is_paused() && !c.is_ascii_control() {
                     tick_timer.resume();
@@ -104,7 +126,8 @@ pub async fn serve<P>(
                 None => return Err(ConnTaskError::Terminated),
                 _ => (),
             },
-            _ = tick_timer.tick() => actor.tick(&mut display.handle().await?, &field, &mut rng).await?,
+            _ = tick_timer.tick() => actor.tick(&mut display.handle().await?, &mut field, &mut rng).await?,
+            _ = anim_timer.tick(), if actor.is_animating() => actor.animate(&mut display.handle().await?, &mut field).await?,
             _ = virs_timer.tick() => {
                 virus_sym = virus_sym.flipped();
                 field.place_viruses(
@@ -141,6 +164,7 @@ pub async fn serve<P>(
     while !phase.transitioned() {
         tokio::select! {
             res = input.next() => match res {
+                Some(Ok(super::REDRAW_KEY)) => display.redraw(&[&field, &indicator, &score_board]).await?,
                 Some(Err(e)) if !e.is_would_block() => return Err(e.into()),
                 None => return Err(ConnTaskError::Terminated),
                 _ => (),
@@ -168,17 +192,74 @@ pub async fn serve<P>(
 }
 
 
+/// Round spectator function
+///
+/// This function implements the connection task part of the game logic for
+/// players not participating in the current round. It displays the scores of
+/// the participating players until the round ends.
+///
+pub async fn spectate<P>(
+    control: Ports,
+    display: &mut display::Display<impl io::AsyncWrite + Send + Unpin>,
+    mut input: impl futures::stream::Stream<Item = Result<char, super::ConnTaskError>> + Unpin,
+    mut phase: super::TransitionWatcher<P, impl Fn(&P) -> bool>,
+) -> Result<(), super::ConnTaskError> {
+    use futures::stream::StreamExt;
+
+    use super::ConnTaskError;
+
+    let mut scores = control.scores;
+
+    // Set up display
+    let theme = display.options().theme;
+    let mut area = display.area().await?.pad_top(1);
+    let left = area.split_left(super::COLUMN_SPLIT);
+    left.place_center(display::StaticText::from(&SPECTATOR_MESSAGE as &[_])).await?;
+
+    let max_scores = area.rows().saturating_sub(2);
+    let mut score_board = area.place_center(display::ScoreBoard::new(max_scores).theme(theme)).await?;
+    {
+        let scores = scores.borrow().clone();
+        score_board.update(&mut display.handle().await?, scores.iter(), |_| false).await?
+    }
+
+    while !phase.transitioned() {
+        tokio::select! {
+            res = input.next() => match res {
+                Some(Ok(super::REDRAW_KEY)) => display.redraw(&[&score_board]).await?,
+                Some(Err(e)) if !e.is_would_block() => return Err(e),
+                None => return Err(ConnTaskError::Terminated),
+                _ => (),
+            },
+            _ = scores.changed() => {
+                let scores = scores.borrow().clone();
+                score_board.update(&mut display.handle().await?, scores.iter(), |_| false).await?
+            },
+            t = phase.transition() => return t,
+        }
+    }
+
+    Ok(())
+}
+
+
 /// Round control function
 ///
-/// This function implements the central control logic for the round phase.
+/// This function implements the central control logic for the round phase. It
+/// returns the players of the round along with their round score and the
+/// statistics collected during the round, ranked by the round score with the
+/// best player first.
 ///
 pub async fn control(
     ports: ControlPorts,
     roster: Arc<RwLock<player::Roster>>,
+    dimensions: util::Dimensions,
+    palette: util::Palette,
     virus_count: u32,
     disconnects: &mut mpsc::UnboundedReceiver<player::Tag>,
     rng: &mut impl rand::Rng,
-) -> Result<(), error::WrappedErr> {
+) -> Result<Vec<(player::Tag, u32, RoundStats)>, error::WrappedErr> {
+    use super::{Report, report};
     use display::ScoreBoardEntry as _;
     use error::TryExt;
     use error::WrappedErr as E;
@@ -186,6 +267,8 @@ pub async fn control(
     let scores_sender = ports.scores;
     let mut events = ports.events;
     let mut active = ports.capsules;
+    let reports = ports.reports;
+    let start = time::Instant::now();
 
     let mut scores: Vec<_> = roster
         .read()
@@ -213,17 +296,19 @@ pub async fn control(
             Event::Capsules(elements) => {
                 use std::convert::TryInto;
 
+                report(&reports, Report::Capsules{player: player.name().to_string(), count: elements.len() as u32});
+
                 let max = scores.first().ok_or_else(|| E::new("no players", error::NoneError))?.round_score();
                 let targets: Vec<_> = scores
                     .iter()
                     .take_while(|p| p.round_score() >= max)
-                    .filter_map(|p| active.get(&p.tag()))
+                    .filter_map(|p| active.get_key_value(&p.tag()))
                     .collect();
                 let with_colidx = |e: &[_]| e
                     .iter()
                     .cloned()
                     .map(|e| (
-                        (rng.next_u32() as usize % util::FIELD_WIDTH as usize)
+                        (rng.next_u32() as usize % dimensions.width() as usize)
                             .try_into()
                             .expect("Could not convert to field index"),
                         e
@@ -232,15 +317,56 @@ pub async fn control(
                     .chunks((elements.len() / targets.len()).clamp(1, MAX_CAPSULE_RECEIVE))
                     .map(with_colidx)
                     .zip(targets);
-                for (elements, target) in sends {
-                    target.lock().await.push_back(elements)
+                let mut received = Vec::new();
+                for (batch, (tag, target)) in sends {
+                    let batch: Capsules = batch;
+                    received.push((tag.clone(), batch.len() as u32));
+                    target.lock().await.push_back(Attack::Capsules(batch))
+                }
+
+                scores.iter_mut().for_each(|e| if e.tag == player {
+                    e.stats.sent += elements.len() as u32
+                } else if let Some((_, n)) = received.iter().find(|(t, _)| e.tag == *t) {
+                    e.stats.received += n
+                });
+            },
+            Event::Garbage(rows) => {
+                report(&reports, Report::Garbage{player: player.name().to_string(), rows});
+                let targets: Vec<_> = scores
+                    .iter()
+                    .filter(|p| *p.tag() != player)
+                    .filter_map(|p| active.get(p.tag()))
+                    .collect();
+                let mut sent = 0;
+                for target in targets.iter().cycle().take(rows as usize) {
+                    let gap = rng.next_u32() as usize % dimensions.width() as usize;
+                    let mut colour = rng.sample(palette);
+                    let row = dimensions
+                        .columns()
+                        .filter(|c| usize::from(*c) != gap)
+                        .map(|c| {
+                            // Avoid rows of the same colour within the garbage
+                            colour = palette.rotate(colour, rng.gen());
+                            (c, colour)
+                        })
+                        .collect();
+                    target.lock().await.push_back(Attack::Garbage(row));
+                    sent += 1;
+                }
+
+                if let Some(entry) = scores.iter_mut().find(|e| *e.tag() == player) {
+                    entry.stats.garbage += sent
+                } else {
+                    log::warn!("Could not find entry for player tag");
                 }
             },
             Event::Score(score) => {
+                report(&reports, Report::Score{player: player.name().to_string(), viruses: score});
                 if let Some(entry) = scores.iter_mut().find(|e| *e.tag() == player) {
                     entry.set_score(score);
                     if score == 0 {
-                        entry.set_state(PlayerState::Suceeded)
+                        entry.set_state(PlayerState::Suceeded);
+                        entry.stats.clear_time = Some(start.elapsed());
                     }
                 } else {
                     log::warn!("Could not find entry for player tag");
@@ -253,22 +379,32 @@ pub async fn control(
                 }
             },
             Event::Defeat => {
+                report(&reports, Report::Defeat{player: player.name().to_string()});
                 let entry = scores
                     .iter_mut()
                     .find(|e| *e.tag() == player)
                     .or_warn("Defeated player not found in scores");
                 if let Some(entry) = entry {
                     entry.set_state(PlayerState::Defeated);
+                    entry.stats.top_out_time = Some(start.elapsed());
                 }
                 active.remove(&player).or_warn("Defeated player not active");
             },
+            Event::Spawn => if let Some(entry) = scores.iter_mut().find(|e| *e.tag() == player) {
+                entry.stats.capsules += 1
+            },
+            Event::Chain(rows) => if let Some(entry) = scores.iter_mut().find(|e| *e.tag() == player) {
+                entry.stats.max_chain = entry.stats.max_chain.max(rows)
+            },
         }
     }
 
-    // Preserve the round scores by adding them to the overall scores
-    scores.into_iter().for_each(|e| {e.tag().add_score(e.round_score()); });
+    crate::metrics::METRICS.rounds.inc();
+    crate::metrics::METRICS.round_duration.observe(start.elapsed().as_secs_f64());
 
-    Ok(())
+    // Preserve the round scores by adding them to the overall scores
+    scores.sort_by_key(|p| p.round_score());
+    Ok(scores.into_iter().map(|e| { e.tag().add_score(e.round_score()); (e.tag, e.round_score, e.stats) }).collect())
 }
 
 
@@ -279,33 +415,59 @@ pub async fn control(
 ///
 pub(super) struct Actor {
     event_sender: mpsc::Sender<(player::Tag, Event)>,
-    capsule_receiver: CapsulesQueue,
+    attack_receiver: AttackQueue,
     player_tag: player::Tag,
     moving: field::MovingField,
     r#static: field::StaticField,
     viruses: HashMap<util::Position, util::Colour>,
+    source: field::CapsuleSource,
+    match_rule: util::MatchRule,
     active: ActiveElements,
-    next_colours: [util::Colour; 2],
+    next_capsule: [field::Appearance; 2],
+    chain: u32,
+    explosions: Vec<(util::Position, usize)>,
+    rising: Vec<util::Position>,
 }
 
 impl Actor {
     /// Create a new actor
     ///
+    /// The actor will operate on the given static field, which is expected to
+    /// only contain viruses.
+    ///
     pub fn new(
         event_sender: mpsc::Sender<(player::Tag, Event)>,
-        capsule_receiver: CapsulesQueue,
+        attack_receiver: AttackQueue,
         player_tag: player::Tag,
-        viruses: HashMap<util::Position, util::Colour>,
-        next_colours: [util::Colour; 2],
+        r#static: field::StaticField,
+        source: field::CapsuleSource,
+        match_rule: util::MatchRule,
+        next_capsule: [field::Appearance; 2],
     ) -> Self {
         let moving: field::MovingField = Default::default();
-        let r#static = viruses
-            .iter()
-            .map(|(p, c)| (p.clone(), c.clone()))
+        let dimensions = r#static.dimensions();
+        let viruses = dimensions
+            .rows()
+            .flat_map(|r| dimensions.complete_row(r))
+            .filter_map(|p| r#static[p].as_virus().map(|v| (p, v.colour())))
             .collect();
         // We'll start with an empty moving field. A capsule will be spawned on the first tick.
         let active = moving.moving_row_index(util::RowIndex::TOP_ROW).into();
-        Self {event_sender, capsule_receiver, player_tag, moving, r#static, viruses, active, next_colours}
+        Self {
+            event_sender,
+            attack_receiver,
+            player_tag,
+            moving,
+            r#static,
+            viruses,
+            source,
+            match_rule,
+            active,
+            next_capsule,
+            chain: 0,
+            explosions: Default::default(),
+            rising: Default::default(),
+        }
     }
 
     /// Perform a controlled move
@@ -318,7 +480,7 @@ impl Actor {
     pub async fn r#move(
         &mut self,
         display_handle: &mut display::DrawHandle<'_, impl io::AsyncWrite + Send + Unpin>,
-        field: &display::FieldUpdater,
+        field: &mut display::FieldUpdater,
         movement: field::Movement,
     ) -> Result<(), super::ConnTaskError> {
         match &mut self.active {
@@ -349,7 +511,7 @@ impl Actor {
     pub async fn tick(
         &mut self,
         display_handle: &mut display::DrawHandle<'_, impl io::AsyncWrite + Send + Unpin>,
-        field: &display::FieldUpdater,
+        field: &mut display::FieldUpdater,
         rng: &mut impl rand::Rng,
     ) -> Result<(), super::ConnTaskError> {
         let lowest = self.moving.row_index_from_moving(self.active.lowest_row());
@@ -358,26 +520,53 @@ impl Actor {
 
         if !settled.is_empty() {
             // A lot of interesting stuff only happens if elements settled
-            let eliminated = field::eliminate_elements(&mut self.r#static, &settled);
+            let eliminated = field::eliminate_elements(&mut self.r#static, &settled, self.match_rule);
             lowest = lower_row(
                 field::unsettle_elements(&mut self.moving, &mut self.r#static, &eliminated),
                 lowest
             );
 
+            // Rows eliminated until the next spawn count towards the same chain
+            if eliminated.row_count() > 0 {
+                self.chain += eliminated.row_count() as u32;
+                self.player_tag.stats().record_chain(self.chain);
+                self.send_event(Event::Chain(self.chain)).await?;
+                field.show_chain(display_handle, self.chain).await?;
+            }
+
             // We use need to remove _all_ viruses at eliminated positions, i.e.
             // make sure we don't fall into a short-circuiting trap.
-            if eliminated.positions().filter_map(|p| self.viruses.remove(&p)).count() > 0 {
+            let cleared = eliminated.positions().filter_map(|p| self.viruses.remove(&p)).count();
+            if cleared > 0 {
+                self.player_tag.stats().add_viruses_cleared(cleared as u32);
                 self.send_event(Event::Score(self.viruses.len() as u32)).await?;
             }
             if eliminated.row_count() > MIN_CAPSULES_SEND {
-                let capsules = eliminated.rows_of_four().map(|(c, _)| *c).collect();
+                let capsules: Vec<_> = eliminated.rows_of_four().map(|(c, _)| *c).collect();
+                self.player_tag.stats().add_attacks_sent(capsules.len() as u32);
                 self.send_event(Event::Capsules(capsules)).await?;
             }
             if field::defeated(&self.r#static) {
                 self.send_event(Event::Defeat).await?;
             }
 
-            field.update(display_handle, eliminated.positions().map(|p| (p, None))).await?;
+            if field.explosion_frames() > 0 {
+                let positions: Vec<_> = eliminated.positions().collect();
+                field.explode(display_handle, positions.iter().cloned(), 0).await?;
+                self.explosions.extend(positions.into_iter().map(|p| (p, 0)));
+            } else {
+                field.update(display_handle, eliminated.positions().map(|p| (p, None))).await?;
+            }
+
+            // Former partners of eliminated elements are no longer bound
+            let exes: Vec<_> = eliminated
+                .exes()
+                .filter_map(|p| self.r#static[p]
+                    .as_element()
+                    .or_else(|| self.moving[p].as_ref())
+                    .map(|e| (p, Some(e.appearance())))
+                ).collect();
+            field.update(display_handle, exes).await?;
 
             if let Some(lowest) = lowest {
                 self.active = self.moving.moving_row_index(lowest).into();
@@ -390,28 +579,115 @@ impl Actor {
         } else if !self.is_defeated() {
             // There are no moving element left. We need to respawn something.
             use util::RowIndex;
-            if let Some(capsules) = self.capsule_receiver.lock().await.pop_front() {
-                self.active = self.moving.moving_row_index(RowIndex::TOP_ROW).into();
-                return field
-                    .update(display_handle, self.moving.spawn_single_capsules(capsules))
-                    .await
-                    .map_err(Into::into)
+
+            if self.chain > MIN_GARBAGE_CHAIN {
+                let rows = self.chain - MIN_GARBAGE_CHAIN;
+                self.player_tag.stats().add_garbage_sent(rows);
+                self.send_event(Event::Garbage(rows)).await?;
+            }
+            if self.chain > 0 {
+                self.chain = 0;
+                field.show_chain(display_handle, self.chain).await?;
+            }
+
+            let attack = self.attack_receiver.lock().await.pop_front();
+            match attack {
+                Some(Attack::Capsules(capsules)) => {
+                    self.active = self.moving.moving_row_index(RowIndex::TOP_ROW).into();
+                    return field
+                        .update(display_handle, self.moving.spawn_single_capsules(capsules))
+                        .await
+                        .map_err(Into::into)
+                },
+                Some(Attack::Garbage(row)) => {
+                    use util::Direction as Dir;
+
+                    let defeated = self.r#static.raise(row);
+                    self.explosions.clear();
+                    self.viruses = self
+                        .viruses
+                        .drain()
+                        .filter_map(|(p, c)| (p + Dir::Above).map(|p| (p, c)))
+                        .collect();
+
+                    let dimensions = self.r#static.dimensions();
+                    let contents: Vec<_> = dimensions
+                        .rows()
+                        .flat_map(|r| dimensions.complete_row(r))
+                        .map(|p| (p, self.r#static[p].as_element().map(|e| e.appearance())))
+                        .collect();
+                    // Tiles are revealed from the back of `rising`
+                    self.rising = field.raise(display_handle, contents).await?;
+                    self.rising.reverse();
+                    field.place_viruses(display_handle, self.remaining_viruses(), Default::default()).await?;
+
+                    if defeated {
+                        return self.send_event(Event::Defeat).await
+                    }
+                },
+                None => (),
             }
 
             // We didn't receive any unbound capsules, spawn a controlled capsule.
             let (capsule, updates) = field::ControlledCapsule::spawn_capsule(
                 &mut self.moving,
-                &self.next_colours
+                self.r#static.dimensions(),
+                &self.next_capsule
             );
-            self.next_colours = rng.gen();
+            self.next_capsule = self.source.generate(rng);
+            self.send_event(Event::Spawn).await?;
 
             self.active = capsule.into();
             field.update(display_handle, updates.iter().cloned()).await?;
-            field.place_next_elements(display_handle, &self.next_colours).await?;
+            field.place_next_elements(display_handle, &self.next_capsule).await?;
         }
         Ok(())
     }
 
+    /// Advance the elimination and rising animations
+    ///
+    /// This function draws the next frame of the animation for all tiles
+    /// eliminated recently. Tiles which were occupied again in the meantime
+    /// are left alone. In addition, the next tiles of a recently risen bottom
+    /// row are revealed.
+    ///
+    pub async fn animate(
+        &mut self,
+        display_handle: &mut display::DrawHandle<'_, impl io::AsyncWrite + Send + Unpin>,
+        field: &mut display::FieldUpdater,
+    ) -> Result<(), super::ConnTaskError> {
+        let (r#static, moving) = (&self.r#static, &self.moving);
+        self.explosions.retain(|(p, _)| !r#static[*p].is_occupied() && moving[*p].is_none());
+        self.explosions.iter_mut().for_each(|(_, f)| *f += 1);
+
+        let mut frames: Vec<_> = self.explosions.iter().map(|(_, f)| *f).collect();
+        frames.sort_unstable();
+        frames.dedup();
+        for frame in frames {
+            let positions = self.explosions.iter().filter(|(_, f)| *f == frame).map(|(p, _)| *p);
+            field.explode(display_handle, positions, frame).await?;
+        }
+
+        let frames = field.explosion_frames();
+        self.explosions.retain(|(_, f)| *f < frames);
+
+        let revealed = self.rising.split_off(self.rising.len().saturating_sub(RISE_STEP));
+        let elements = revealed.iter().map(|p| (
+            *p,
+            r#static[*p].as_element().or_else(|| moving[*p].as_ref()).map(|e| e.appearance()),
+        ));
+        field.update(display_handle, elements).await?;
+        let viruses = revealed.iter().filter_map(|p| self.viruses.get(p).map(|c| (*p, *c)));
+        field.place_viruses(display_handle, viruses, Default::default()).await?;
+        Ok(())
+    }
+
+    /// Check whether the elimination or rising animation is still running
+    ///
+    pub fn is_animating(&self) -> bool {
+        !self.explosions.is_empty() || !self.rising.is_empty()
+    }
+
     /// Check whether there is a controlled capsule
     ///
     pub fn is_controlled(&self) -> bool {
@@ -432,8 +708,16 @@ impl Actor {
 
     /// Retrieve the remaining viruses
     ///
+    /// Viruses in tiles of a risen row which were not revealed yet are left out.
+    ///
     pub fn remaining_viruses(&self) -> impl Iterator<Item = (util::Position, util::Colour)> {
-        self.viruses.clone().into_iter()
+        let viruses: Vec<_> = self
+            .viruses
+            .iter()
+            .filter(|(p, _)| !self.rising.contains(p))
+            .map(|(p, c)| (*p, *c))
+            .collect();
+        viruses.into_iter()
     }
 
     /// Send the given event
@@ -449,40 +733,63 @@ impl Actor {
     ///
     pub fn new_with_fields(
         event_sender: mpsc::Sender<(player::Tag, Event)>,
-        capsule_receiver: CapsulesQueue,
+        attack_receiver: AttackQueue,
         player_tag: player::Tag,
         r#static: field::StaticField,
         moving: field::MovingField,
         next_colours: [util::Colour; 2],
     ) -> Self {
-        let viruses = util::ROWS
-            .flat_map(util::complete_row)
+        let next_capsule = [next_colours[0].into(), next_colours[1].into()];
+        let dimensions = r#static.dimensions();
+        let source = field::CapsuleSource::new(util::Palette::MAXIMUM, false);
+        let match_rule = Default::default();
+        let viruses = dimensions
+            .positions()
             .filter_map(|p| r#static[p].as_virus().map(|v| (p, v.colour())))
             .collect();
-        let lowest = util::ROWS
+        let lowest = dimensions
+            .rows()
             .rev()
-            .find(|r| util::complete_row(*r).any(|p| moving[p].is_some()))
+            .find(|r| dimensions.complete_row(*r).any(|p| moving[p].is_some()))
             .unwrap_or(util::RowIndex::TOP_ROW);
         let active = moving.moving_row_index(lowest).into();
-        Self {event_sender, capsule_receiver, player_tag, moving, r#static, viruses, active, next_colours}
+        Self {
+            event_sender,
+            attack_receiver,
+            player_tag,
+            moving,
+            r#static,
+            viruses,
+            source,
+            match_rule,
+            active,
+            next_capsule,
+            chain: 0,
+            explosions: Default::default(),
+            rising: Default::default(),
+        }
     }
 
     /// Construct an Actor with given static field and capsule position
     ///
     pub fn new_with_capsule(
         event_sender: mpsc::Sender<(player::Tag, Event)>,
-        capsule_receiver: CapsulesQueue,
+        attack_receiver: AttackQueue,
         player_tag: player::Tag,
         r#static: field::StaticField,
         row: util::RowIndex,
         colours: [util::Colour; 2],
     ) -> Self {
+        let dimensions = r#static.dimensions();
+        let source = field::CapsuleSource::new(util::Palette::MAXIMUM, false);
+        let match_rule = Default::default();
         let mut moving: field::MovingField = Default::default();
-        let (capsule, _) = field::ControlledCapsule::spawn_capsule(&mut moving, &colours);
+        let next_capsule = [colours[0].into(), colours[1].into()];
+        let (capsule, _) = field::ControlledCapsule::spawn_capsule(&mut moving, dimensions, &next_capsule);
         (0..util::Step::steps_between(&util::RowIndex::TOP_ROW, &row).expect("Invalid row"))
             .for_each(|_| moving.tick().fold((), |_, _| ()));
-        let overlap = util::ROWS
-            .flat_map(util::complete_row)
+        let overlap = dimensions
+            .positions()
             .any(|p| r#static[p].is_occupied() && moving[p].is_some());
         let active = if overlap {
             moving = Default::default();
@@ -491,14 +798,27 @@ impl Actor {
             capsule.into()
         };
 
-        let viruses = util::ROWS
-            .flat_map(util::complete_row)
+        let viruses = dimensions
+            .positions()
             .filter_map(|p| r#static[p].as_virus().map(|v| (p, v.colour())))
             .collect();
 
-        let next_colours = colours;
 
-        Self {event_sender, capsule_receiver, player_tag, moving, r#static, viruses, active, next_colours}
+        Self {
+            event_sender,
+            attack_receiver,
+            player_tag,
+            moving,
+            r#static,
+            viruses,
+            source,
+            match_rule,
+            active,
+            next_capsule,
+            chain: 0,
+            explosions: Default::default(),
+            rising: Default::default(),
+        }
     }
 
     /// Retrieve a reference to the static field
@@ -636,9 +956,14 @@ enum ResumableInterval {
 /// Create ports for communication between connection and control task
 ///
 /// This function returns a pair of ports specific to the round phase, one for
-/// the connection task and one for the control task.
+/// the connection task and one for the control task. The control task will
+/// send events of the round via `reports`.
 ///
-pub fn ports(scores: impl IntoIterator<Item = player::Tag>, virus_count: u32) -> (Ports, ControlPorts) {
+pub fn ports(
+    scores: impl IntoIterator<Item = player::Tag>,
+    virus_count: u32,
+    reports: broadcast::Sender<super::Report>,
+) -> (Ports, ControlPorts) {
     let (capsules, scores): (HashMap<_, _>, Vec<_>) = scores
         .into_iter()
         .filter(|p| p.is_connected())
@@ -650,7 +975,7 @@ pub fn ports(scores: impl IntoIterator<Item = player::Tag>, virus_count: u32) ->
     let (event_sender, event_receiver) = mpsc::channel(player_num);
 
     let ports = Ports {scores: score_receiver, events: event_sender, capsules: Arc::new(capsules.clone())};
-    let control = ControlPorts {scores: score_sender, events: event_receiver, capsules};
+    let control = ControlPorts {scores: score_sender, events: event_receiver, capsules, reports};
 
     (ports, control)
 }
@@ -662,7 +987,15 @@ pub fn ports(scores: impl IntoIterator<Item = player::Tag>, virus_count: u32) ->
 pub struct Ports {
     scores: watch::Receiver<Arc<[ScoreBoardEntry]>>,
     events: mpsc::Sender<(player::Tag, Event)>,
-    capsules: Arc<HashMap<player::Tag, CapsulesQueue>>,
+    capsules: Arc<HashMap<player::Tag, AttackQueue>>,
+}
+
+impl Ports {
+    /// Check whether the given player participates in the round
+    ///
+    pub fn is_participant(&self, player: &player::Tag) -> bool {
+        self.capsules.contains_key(player)
+    }
 }
 
 
@@ -672,7 +1005,8 @@ pub struct Ports {
 pub struct ControlPorts {
     scores: watch::Sender<Arc<[ScoreBoardEntry]>>,
     events: mpsc::Receiver<(player::Tag, Event)>,
-    capsules: HashMap<player::Tag, CapsulesQueue>,
+    capsules: HashMap<player::Tag, AttackQueue>,
+    reports: broadcast::Sender<super::Report>,
 }
 
 
@@ -682,16 +1016,75 @@ pub struct ControlPorts {
 pub(super) enum Event {
     /// Capsules to be sent to ther players
     Capsules(Vec<util::Colour>),
+    /// Number of garbage rows to be sent to other players
+    Garbage(u32),
     /// The player's score has changed
     Score(u32),
     /// The player was defeated
     Defeat,
+    /// A controlled capsule was spawned for the player
+    Spawn,
+    /// The player's current chain grew to the given number of rows
+    Chain(u32),
+}
+
+
+/// Settings of a single round
+///
+#[derive(Clone, Debug, Default)]
+pub struct Settings {
+    /// Dimensions of the players' fields
+    pub dimensions: util::Dimensions,
+    /// Source of the capsules spawned
+    pub source: field::CapsuleSource,
+    /// Rule for matching elements
+    pub match_rule: util::MatchRule,
+    /// Viruses initially placed in each player's field
+    pub viruses: HashMap<util::Position, util::Colour>,
+    /// Duration of a tick
+    pub tick_duration: std::time::Duration,
+    /// Whether visual effects are displayed
+    pub effects: bool,
 }
 
 
-/// Queue for distribution of capsules
+/// Statistics of a single player collected during a round
+///
+#[derive(Clone, Debug, Default)]
+pub struct RoundStats {
+    /// Time it took the player to clear all viruses
+    pub clear_time: Option<time::Duration>,
+    /// Number of capsules the player controlled
+    pub capsules: u32,
+    /// Biggest chain, in number of rows eliminated
+    pub max_chain: u32,
+    /// Number of capsule elements sent to other players
+    pub sent: u32,
+    /// Number of capsule elements received from other players
+    pub received: u32,
+    /// Number of garbage rows sent to other players
+    pub garbage: u32,
+    /// Time after which the player topped out
+    pub top_out_time: Option<time::Duration>,
+}
+
+
+/// Queue for distribution of attacks
+///
+type AttackQueue = Arc<Mutex<VecDeque<Attack>>>;
+
+
+/// Attack received from another player
 ///
-type CapsulesQueue = Arc<Mutex<VecDeque<Capsules>>>;
+#[derive(Clone, Debug)]
+pub(super) enum Attack {
+    /// Unbound capsule elements spawning at the top of the field
+    Capsules(Capsules),
+    /// Row of blocks rising from the bottom of the field
+    ///
+    /// Columns not listed remain free.
+    Garbage(Capsules),
+}
 
 
 /// Convenience type for a batch of capsules
@@ -706,6 +1099,7 @@ struct ScoreBoardEntry {
     tag: player::Tag,
     round_score: u32,
     state: PlayerState,
+    stats: RoundStats,
 }
 
 impl ScoreBoardEntry {
@@ -715,7 +1109,7 @@ impl ScoreBoardEntry {
     /// initial `round_score`.
     ///
     pub fn new(tag: player::Tag, round_score: u32) -> Self {
-        ScoreBoardEntry {tag, round_score, state: Default::default()}
+        ScoreBoardEntry {tag, round_score, state: Default::default(), stats: Default::default()}
     }
 
     /// Set the player's round score
@@ -780,7 +1174,32 @@ const MIN_CAPSULES_SEND: usize = 2;
 const MAX_CAPSULE_RECEIVE: usize = 4;
 
 
+/// The minimum chain length, in rows, for garbage rows to be sent
+///
+/// A chain longer than this sends one garbage row for each additional row.
+///
+const MIN_GARBAGE_CHAIN: u32 = 3;
+
+
+/// Number of tiles of a risen row revealed in a single animation frame
+///
+const RISE_STEP: usize = 2;
+
+
+/// Duration of a single frame of the elimination animation
+///
+const EXPLOSION_FRAME: time::Duration = time::Duration::from_millis(80);
+
+
 /// Grace period before the first tick
 ///
 const GRACE_PERIOD: time::Duration = time::Duration::from_secs(2);
 
+
+/// Message displayed to players not participating in a round
+///
+const SPECTATOR_MESSAGE: [&str; 3] = [
+    "You are watching a match.",
+    "Please wait for the next",
+    "round.",
+];
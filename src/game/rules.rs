@@ -0,0 +1,169 @@
+//! Match rules
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::player;
+
+
+/// Condition deciding a match
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WinCondition {
+    /// The match is not decided by any rule
+    #[default]
+    None,
+    /// The first player to win the given number of rounds wins the match
+    FirstTo(u8),
+    /// The player winning the most of the given number of rounds wins the match
+    Rounds(u8),
+    /// The player with the lowest total after the given number of rounds wins
+    LowestTotal(u8),
+}
+
+impl WinCondition {
+    /// Rank the given players according to this condition
+    ///
+    /// The players are ranked based on the number of rounds won and their
+    /// total score, as provided via `wins` and `score`. The best player will
+    /// come first.
+    ///
+    pub fn rank<T>(
+        &self,
+        players: &mut [T],
+        wins: impl Fn(&T) -> u32,
+        score: impl Fn(&T) -> u32,
+    ) {
+        use std::cmp::Reverse;
+
+        match self {
+            Self::LowestTotal(_) => players.sort_by_key(|p| (score(p), Reverse(wins(p)))),
+            _ => players.sort_by_key(|p| (Reverse(wins(p)), score(p))),
+        }
+    }
+}
+
+impl fmt::Display for WinCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None              => write!(f, "none"),
+            Self::FirstTo(n)        => write!(f, "first-to:{}", n),
+            Self::Rounds(n)         => write!(f, "rounds:{}", n),
+            Self::LowestTotal(n)    => write!(f, "lowest:{}", n),
+        }
+    }
+}
+
+impl FromStr for WinCondition {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "none" {
+            return Ok(Self::None)
+        }
+
+        let (kind, num) = s.split_at(s.find(':').ok_or(ParseError)?);
+        let num = num[1..].parse().ok().filter(|n| *n > 0).ok_or(ParseError)?;
+        match kind {
+            "first-to"  => Ok(Self::FirstTo(num)),
+            "rounds"    => Ok(Self::Rounds(num)),
+            "lowest"    => Ok(Self::LowestTotal(num)),
+            _ => Err(ParseError),
+        }
+    }
+}
+
+
+/// Error indicating an invalid win condition specification
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ParseError;
+
+impl std::error::Error for ParseError {}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Expected 'none', 'first-to:<n>', 'rounds:<n>' or 'lowest:<n>'")
+    }
+}
+
+
+/// Record of a match in progress
+///
+#[derive(Clone, Debug)]
+pub struct Record {
+    rounds: u32,
+    entries: Vec<Entry>,
+}
+
+impl Record {
+    /// Create a new record for a match between the given players
+    ///
+    pub fn new(players: impl IntoIterator<Item = player::Tag>) -> Self {
+        let entries = players.into_iter().map(|tag| Entry {tag, wins: 0, score: 0}).collect();
+        Self {rounds: 0, entries}
+    }
+
+    /// Record the outcome of a round
+    ///
+    /// The `ranking` is expected to hold the round's players along with their
+    /// round score, the best player first. The best player still connected is
+    /// considered the winner of the round. This function returns that player.
+    ///
+    pub fn add_round(&mut self, ranking: &[(player::Tag, u32)]) -> Option<player::Tag> {
+        self.rounds += 1;
+
+        ranking.iter().for_each(|(tag, score)| if let Some(e) = self.entry_mut(tag) {
+            e.score += score
+        });
+
+        let winner = ranking.iter().map(|(t, _)| t).find(|t| t.is_connected())?;
+        if let Some(e) = self.entry_mut(winner) {
+            e.wins += 1
+        }
+        winner.add_win();
+        Some(winner.clone())
+    }
+
+    /// Check whether the match is decided under the given condition
+    ///
+    pub fn is_decided(&self, condition: WinCondition) -> bool {
+        match condition {
+            WinCondition::None              => false,
+            WinCondition::FirstTo(n)        => self.entries.iter().any(|e| e.wins >= n.into()),
+            WinCondition::Rounds(n)         => self.rounds >= n.into(),
+            WinCondition::LowestTotal(n)    => self.rounds >= n.into(),
+        }
+    }
+
+    /// Retrieve the winner of the match under the given condition
+    ///
+    /// The winner is the best ranked player still connected or, if no player
+    /// is connected, the best ranked player.
+    ///
+    pub fn winner(&self, condition: WinCondition) -> Option<player::Tag> {
+        let mut entries = self.entries.clone();
+        condition.rank(&mut entries, |e| e.wins, |e| e.score);
+        entries
+            .iter()
+            .find(|e| e.tag.is_connected())
+            .or_else(|| entries.first())
+            .map(|e| e.tag.clone())
+    }
+
+    /// Retrieve the entry for a given player
+    ///
+    fn entry_mut(&mut self, tag: &player::Tag) -> Option<&mut Entry> {
+        self.entries.iter_mut().find(|e| e.tag == *tag)
+    }
+}
+
+
+/// Record entry for a single player
+///
+#[derive(Clone, Debug)]
+struct Entry {
+    tag: player::Tag,
+    wins: u32,
+    score: u32,
+}
@@ -0,0 +1,166 @@
+//! Implementation of the final standings phase
+
+use std::sync::Arc;
+
+use tokio::io;
+use tokio::sync::{mpsc, watch};
+use tokio::time;
+
+use crate::display;
+use crate::player;
+
+
+/// Standings phase function
+///
+/// This function implements the connection task part of the game logic for the
+/// final standings phase.
+///
+pub async fn serve<P>(
+    control: Ports,
+    display: &mut display::Display<impl io::AsyncWrite + Send + Unpin>,
+    mut input: impl futures::stream::Stream<Item = Result<char, super::ConnTaskError>> + Unpin,
+    mut phase: super::TransitionWatcher<P, impl Fn(&P) -> bool>,
+    me: &player::Handle,
+) -> Result<(), super::ConnTaskError> {
+    use std::convert::TryInto;
+
+    use futures::stream::StreamExt;
+
+    use super::ConnTaskError;
+
+    let mut ranking = control.ranking;
+
+    // Set up the display
+    let theme = display.options().theme;
+    let mut area = display.area().await?.pad_top(1);
+    let mut left = area.split_left(super::COLUMN_SPLIT);
+
+    left.place_top(display::StaticText::from("The game is over!")).await?;
+    left = left.pad_top(1);
+    let mut winner_text = left.place_top(
+        display::DynamicText::new(2u16.try_into().unwrap(), (super::COLUMN_SPLIT - 2).try_into().unwrap())
+    ).await?;
+
+    let max_scores = area.rows().saturating_sub(2);
+    let mut score_board = area.place_center(display::ScoreBoard::new(max_scores).round_label("Wins").theme(theme)).await?;
+    let highlight = {
+        let tag = me.tag();
+        move |t: &player::Tag| *t == tag
+    };
+
+    let mut update = true;
+    while !phase.transitioned() {
+        if update {
+            let ranking = ranking.borrow().clone();
+            let entries: Vec<_> = ranking.iter().cloned().map(ScoreBoardEntry).collect();
+            score_board.update(&mut display.handle().await?, entries.iter(), &highlight).await?;
+            if let Some(winner) = ranking.first() {
+                let msg = if *me == *winner {
+                    ["You won the game!", ""]
+                } else {
+                    ["Winner:", winner.name()]
+                };
+                winner_text.update(&mut display.handle().await?, msg.iter()).await?;
+            }
+        }
+
+        update = tokio::select! {
+            res = input.next() => match res {
+                Some(Ok(super::REDRAW_KEY)) => {
+                    display.redraw(&[&winner_text, &score_board]).await?;
+                    false
+                },
+                Some(Err(e)) if !e.is_would_block() => return Err(e),
+                None => return Err(ConnTaskError::Terminated),
+                _ => false,
+            },
+            _ = ranking.changed() => true,
+            t = phase.transition() => {
+                t?;
+                break
+            },
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Standings phase control function
+///
+/// This function implements the central control logic for the final standings
+/// phase. It returns after a fixed time, once all players disconnected or if
+/// the game is ended.
+///
+pub async fn control(
+    ports: ControlPorts,
+    mut game_control: watch::Receiver<super::GameControl>,
+    disconnects: &mut mpsc::UnboundedReceiver<player::Tag>,
+) {
+    let ranking = ports.ranking;
+    let mut timeout = Box::pin(time::sleep(STANDINGS_TIME));
+
+    while ranking.borrow().iter().any(|p| p.is_connected()) && !game_control.borrow().is_end_of_game() {
+        tokio::select! {
+            _ = &mut timeout => break,
+            _ = game_control.changed() => (),
+            _ = disconnects.recv() => {
+                // Make sure the players' connection states are reflected
+                let current = ranking.borrow().clone();
+                ranking.send(current).ok();
+            },
+        }
+    }
+}
+
+
+/// Create ports for communication between connection and control task
+///
+/// This function returns a pair of ports specific to the standings phase, one
+/// for the connection task and one for the control task. The `ranking` is
+/// expected to hold all players, the winner first.
+///
+pub fn ports(ranking: impl IntoIterator<Item = player::Tag>) -> (Ports, ControlPorts) {
+    let (ranking_sender, ranking_receiver) = watch::channel(ranking.into_iter().collect());
+
+    (Ports {ranking: ranking_receiver}, ControlPorts {ranking: ranking_sender})
+}
+
+
+/// Connection task side of communication ports for the standings phase
+///
+#[derive(Clone, Debug)]
+pub struct Ports {
+    ranking: watch::Receiver<Arc<[player::Tag]>>,
+}
+
+
+/// Control task side of communication ports for the standings phase
+///
+#[derive(Debug)]
+pub struct ControlPorts {
+    ranking: watch::Sender<Arc<[player::Tag]>>,
+}
+
+
+/// Score board entry for the standings phase
+///
+/// The round score column will show the number of rounds won.
+///
+#[derive(Clone, Debug)]
+struct ScoreBoardEntry(player::Tag);
+
+impl display::ScoreBoardEntry for ScoreBoardEntry {
+    fn tag(&self) -> &player::Tag {
+        &self.0
+    }
+
+    fn round_score(&self) -> u32 {
+        self.0.wins()
+    }
+}
+
+
+/// Time in which the final standings are displayed
+///
+const STANDINGS_TIME: time::Duration = time::Duration::from_secs(30);
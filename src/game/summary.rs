@@ -0,0 +1,89 @@
+//! Implementation of the end-of-game summary
+
+use std::sync::Arc;
+
+use tokio::io;
+use tokio::time;
+
+use crate::display;
+use crate::player;
+
+
+/// End-of-game summary function
+///
+/// This function implements the connection task part of the game logic after
+/// the game ended. It presents the final `ranking` along with each player's
+/// statistics and returns once the player pressed any key, or after a fixed
+/// time.
+///
+pub async fn serve(
+    ranking: Arc<[player::Tag]>,
+    display: &mut display::Display<impl io::AsyncWrite + Send + Unpin>,
+    mut input: impl futures::stream::Stream<Item = Result<char, super::ConnTaskError>> + Unpin,
+    me: Option<&player::Handle>,
+) -> Result<(), super::ConnTaskError> {
+    use std::convert::TryInto;
+
+    use futures::stream::StreamExt;
+
+    // Set up the display
+    let mut table = {
+        let mut area = display.area().await?.pad_top(1);
+        area.place_top(display::StaticText::from("The game is over!")).await?;
+        area = area.pad_top(1);
+        area.place_top(display::StaticText::from("Press any key to leave.")).await?;
+        area = area.pad_top(1);
+
+        let rows = area.rows().max(1);
+        area.place_top(
+            display::DynamicText::new(rows.try_into().unwrap(), (TABLE_WIDTH as u16).try_into().unwrap())
+        ).await?
+    };
+
+    let header = format!(
+        "  {:>3} {:<16} {:>5} {:>8} {:>6} {:>8} {:>7}",
+        "#", "Name", "Wins", "Viruses", "Chain", "Attacks", "Garbage",
+    );
+    let lines = ranking.iter().enumerate().map(|(n, p)| {
+        let stats = p.stats();
+        format!(
+            "{} {:>3} {:<16} {:>5} {:>8} {:>6} {:>8} {:>7}",
+            if me.map(|m| *m == *p).unwrap_or(false) { '>' } else { ' ' },
+            n + 1,
+            p.name(),
+            p.wins(),
+            stats.viruses_cleared(),
+            stats.max_chain(),
+            stats.attacks_sent(),
+            stats.garbage_sent(),
+        )
+    });
+    let lines: Vec<_> = std::iter::once(header).chain(lines).collect();
+    table.update(&mut display.handle().await?, lines.iter()).await?;
+
+    let mut timeout = Box::pin(time::sleep(SUMMARY_TIME));
+    loop {
+        tokio::select! {
+            res = input.next() => match res {
+                Some(Ok(super::REDRAW_KEY)) => display.redraw(&[&table]).await?,
+                Some(Err(e)) if e.is_would_block() => (),
+                Some(Err(super::ConnTaskError::Terminated)) | None => break,
+                Some(Err(e)) => return Err(e),
+                Some(Ok(_)) => break,
+            },
+            _ = &mut timeout => break,
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Width of the summary table
+///
+const TABLE_WIDTH: usize = 61;
+
+
+/// Time after which the connection is closed
+///
+const SUMMARY_TIME: time::Duration = time::Duration::from_secs(60);
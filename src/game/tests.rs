@@ -102,7 +102,63 @@ fn lobby_serve_registration(
 async fn waiting_serve_instant_transition() {
     let me = dummy_handle();
 
-    let (ports, _) = waiting::ports(std::iter::once(me.tag()));
+    let (ports, _) = waiting::ports(std::iter::once(me.tag()), None, Vec::new());
+    let mut display = sink_display();
+    let input = futures::stream::pending();
+    let (_, phase) = tokio::sync::watch::channel(());
+    waiting::serve(ports, &mut display, input, TransitionWatcher::new(phase, |_| true), &me)
+        .await
+        .expect("Waiting returned an error")
+}
+
+
+#[tokio::test]
+async fn waiting_serve_recap() {
+    let me = dummy_handle();
+
+    let stats = round::RoundStats {
+        clear_time: Some(std::time::Duration::from_millis(12345)),
+        capsules: 21,
+        max_chain: 3,
+        sent: 2,
+        ..Default::default()
+    };
+    let (ports, _) = waiting::ports(std::iter::once(me.tag()), None, vec![(me.tag(), stats)]);
+    let mut display = sink_display();
+    let input = futures::stream::pending();
+    let (_, phase) = tokio::sync::watch::channel(());
+    waiting::serve(ports, &mut display, input, TransitionWatcher::new(phase, |_| true), &me)
+        .await
+        .expect("Waiting returned an error")
+}
+
+
+#[tokio::test]
+async fn waiting_serve_large_recap() {
+    let me = dummy_handle();
+    let players: Vec<_> = (0..2 * DISPLAY_HEIGHT).map(|_| dummy_handle().tag()).collect();
+
+    let recap: Vec<_> = players.iter().map(|t| (t.clone(), Default::default())).collect();
+    let (ports, _) = waiting::ports(players, None, recap);
+    let mut display = sink_display();
+    let input = futures::stream::pending();
+    let (_, phase) = tokio::sync::watch::channel(());
+    waiting::serve(ports, &mut display, input, TransitionWatcher::new(phase, |_| true), &me)
+        .await
+        .expect("Waiting returned an error")
+}
+
+
+#[tokio::test]
+async fn waiting_serve_large_bracket() {
+    let me = dummy_handle();
+    let handles: Vec<_> = (0..4 * DISPLAY_HEIGHT).map(|_| dummy_handle()).collect();
+    let players: Vec<_> = handles.iter().map(crate::player::Handle::tag).collect();
+
+    let mut bracket = tournament::Bracket::new(players.clone(), 2);
+    let next = bracket.next_match().expect("No match to play");
+    let recap: Vec<_> = players.iter().map(|t| (t.clone(), Default::default())).collect();
+    let (ports, _) = waiting::ports(next, Some(Arc::new(bracket)), recap);
     let mut display = sink_display();
     let input = futures::stream::pending();
     let (_, phase) = tokio::sync::watch::channel(());
@@ -116,7 +172,7 @@ async fn waiting_serve_instant_transition() {
 async fn waiting_serve_input_eof() {
     let me = dummy_handle();
 
-    let (ports, _) = waiting::ports(std::iter::once(me.tag()));
+    let (ports, _) = waiting::ports(std::iter::once(me.tag()), None, Vec::new());
     let mut display = sink_display();
     let input = futures::stream::empty();
     let (phase_sender, phase) = tokio::sync::watch::channel(());
@@ -140,7 +196,7 @@ fn waiting_serve_ready(
         let me: crate::player::Handle = me.into();
         let tag = me.tag();
 
-        let (ports, mut control) = waiting::ports(std::iter::once(tag.clone()));
+        let (ports, mut control) = waiting::ports(std::iter::once(tag.clone()), None, Vec::new());
         let (phase_sender, phase) = tokio::sync::watch::channel(false);
 
         let waiting = {
@@ -181,7 +237,7 @@ fn waiting_control_end_of_game(
             .collect();
         let tags: Vec<_> = handles.iter().map(crate::player::Handle::tag).collect();
 
-        let (_, ports) = waiting::ports(tags.clone());
+        let (_, ports) = waiting::ports(tags.clone(), None, Vec::new());
         let (_, game_control) = tokio::sync::watch::channel(super::GameControl::EndOfGame);
 
         waiting::control(ports, game_control, Arc::new(tags.into()), &mut disconnects).await
@@ -210,8 +266,19 @@ fn waiting_control_players(
             .collect();
         let tags: Vec<_> = handles.iter().map(|(h, _)| h.tag()).collect();
 
-        let (mut ports, control_ports) = waiting::ports(tags.clone());
-        let (_, game_control) = tokio::sync::watch::channel(super::GameControl::Settings{viruses, tick});
+        let (mut ports, control_ports) = waiting::ports(tags.clone(), None, Vec::new());
+        let (_, game_control) = tokio::sync::watch::channel(super::GameControl::Settings{
+            dimensions: Default::default(),
+            palette: Default::default(),
+            match_length: Default::default(),
+            diagonal: false,
+            specials: false,
+            viruses,
+            tick,
+            effects: false,
+            match_size: 0,
+            win_condition: Default::default(),
+        });
 
         let waiting = tokio::spawn(async move {
             let mut disconnects = disconnects;
@@ -238,7 +305,7 @@ async fn round_serve_instant_transition() {
 
     let me = dummy_handle();
 
-    let (ports, _) = round::ports(std::iter::once(me.tag()), 0);
+    let (ports, _) = round::ports(std::iter::once(me.tag()), 0, broadcast::channel(1).0);
     let mut display = sink_display();
     let input = futures::stream::pending();
     let (_, phase) = tokio::sync::watch::channel(());
@@ -248,8 +315,7 @@ async fn round_serve_instant_transition() {
         input,
         TransitionWatcher::new(phase, |_| true),
         &me,
-        Default::default(),
-        std::time::Duration::from_millis(100),
+        round::Settings {tick_duration: std::time::Duration::from_millis(100), ..Default::default()},
         rand_pcg::Pcg64Mcg::seed_from_u64(0),
     ).await.expect("Round returned an error")
 }
@@ -261,7 +327,7 @@ async fn round_serve_input_eof() {
 
     let me = dummy_handle();
 
-    let (ports, _) = round::ports(std::iter::once(me.tag()), 0);
+    let (ports, _) = round::ports(std::iter::once(me.tag()), 0, broadcast::channel(1).0);
     let mut display = sink_display();
     let input = futures::stream::empty();
     let (phase_sender, phase) = tokio::sync::watch::channel(());
@@ -271,8 +337,7 @@ async fn round_serve_input_eof() {
         input,
         TransitionWatcher::new(phase, |_| false),
         &me,
-        Default::default(),
-        std::time::Duration::from_millis(100),
+        round::Settings {tick_duration: std::time::Duration::from_millis(100), ..Default::default()},
         rand_pcg::Pcg64Mcg::seed_from_u64(0),
     ).await;
     drop(phase_sender);
@@ -291,15 +356,15 @@ fn actor_move_output(
     a: util::Colour,
     b: util::Colour,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use crate::display::tests::{Area, VT, VTWriter, handle_from_bare};
+    use crate::display::tests::{Area, VT, handle_from_vt};
 
-    let field = crate::display::PlayField::new();
+    let field = crate::display::PlayField::default();
     let area = Area::new_for_placement(0u16, 0u16, &field);
 
     tokio::runtime::Runtime::new()?.block_on(async {
         let (writer, vt_state) = tokio::sync::watch::channel(VT::new(area.rows(), area.cols()));
-        let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
-        let field = area.instantiate(&mut handle).place_center(field).await?;
+        let mut handle = handle_from_vt(writer, &[]).await;
+        let mut field = area.instantiate(&mut handle).place_center(field).await?;
         let (event_sender, _) = tokio::sync::mpsc::channel(1);
 
         let mut actor = round::Actor::new_with_capsule(
@@ -311,10 +376,10 @@ fn actor_move_output(
             [a, b],
         );
 
-        populate_field_display(&mut handle, &field, actor.static_field(), actor.moving_field()).await?;
+        populate_field_display(&mut handle, &mut field, actor.static_field(), actor.moving_field()).await?;
 
         for movement in moves {
-            actor.r#move(&mut handle, &field, movement).await?;
+            actor.r#move(&mut handle, &mut field, movement).await?;
             check_field_display(&vt_state.borrow(), area, actor.static_field(), actor.moving_field())?;
         }
         Ok(())
@@ -327,25 +392,29 @@ fn actor_tick_output(
     static_field: crate::field::tests::SettledField,
     moving_field: crate::field::tests::MovingField,
     ticks: std::num::NonZeroU8,
-    capsules: std::collections::VecDeque<Vec<(util::ColumnIndex, util::Colour)>>,
-    a: util::Colour,
-    b: util::Colour,
+    attacks: std::collections::VecDeque<(bool, Vec<(util::ColumnIndex, util::Colour)>)>,
+    next: (util::Colour, util::Colour),
     seed: u64,
+    effects: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use rand::SeedableRng;
 
-    use crate::display::tests::{Area, VT, VTWriter, handle_from_bare};
+    use crate::display::tests::{Area, VT, handle_from_vt};
 
-    let field = crate::display::PlayField::new();
+    let field = crate::display::PlayField::new(Default::default(), effects);
     let area = Area::new_for_placement(0u16, 0u16, &field);
 
     let static_field: crate::field::StaticField = static_field.into();
     let moving_field = moving_field.instantiate_for(&static_field);
+    let attacks: std::collections::VecDeque<_> = attacks
+        .into_iter()
+        .map(|(garbage, a)| if garbage { round::Attack::Garbage(a) } else { round::Attack::Capsules(a) })
+        .collect();
 
     tokio::runtime::Runtime::new()?.block_on(async {
         let (writer, vt_state) = tokio::sync::watch::channel(VT::new(area.rows(), area.cols()));
-        let mut handle = handle_from_bare(VTWriter::from(writer), &[]).await;
-        let field = area.instantiate(&mut handle).place_center(field).await?;
+        let mut handle = handle_from_vt(writer, &[]).await;
+        let mut field = area.instantiate(&mut handle).place_center(field).await?;
         let (event_sender, mut events) = tokio::sync::mpsc::channel(1);
         tokio::spawn(async move {
             while events.recv().await.is_some() {}
@@ -353,21 +422,24 @@ fn actor_tick_output(
 
         let mut actor = round::Actor::new_with_fields(
             event_sender,
-            std::sync::Arc::new(capsules.into()),
+            std::sync::Arc::new(attacks.into()),
             dummy_handle().tag(),
             static_field,
             moving_field,
-            [a, b],
+            [next.0, next.1],
         );
 
-        populate_field_display(&mut handle, &field, actor.static_field(), actor.moving_field()).await?;
+        populate_field_display(&mut handle, &mut field, actor.static_field(), actor.moving_field()).await?;
         let mut rng = rand_pcg::Pcg64Mcg::seed_from_u64(seed);
         for _ in 0..ticks.get() {
             if actor.is_defeated() {
                 break;
             }
 
-            actor.tick(&mut handle, &field, &mut rng).await?;
+            actor.tick(&mut handle, &mut field, &mut rng).await?;
+            while actor.is_animating() {
+                actor.animate(&mut handle, &mut field).await?;
+            }
             check_field_display(&vt_state.borrow(), area, actor.static_field(), actor.moving_field())?;
         }
         Ok(())
@@ -375,6 +447,110 @@ fn actor_tick_output(
 }
 
 
+#[quickcheck]
+fn bracket_played(
+    players: Vec<crate::player::tests::TestHandle>,
+    match_size: u8,
+    picks: Vec<usize>,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let handles: Vec<crate::player::Handle> = players.into_iter().map(Into::into).collect();
+        let mut alive: Vec<_> = handles.iter().map(crate::player::Handle::tag).collect();
+        let mut bracket = tournament::Bracket::new(alive.clone(), match_size.into());
+        let mut picks = picks.into_iter().chain(std::iter::repeat(0));
+
+        while let Some(players) = bracket.next_match() {
+            if players.len() < 2
+                || players.len() > (match_size as usize).max(2)
+                || !players.iter().all(|p| alive.contains(p)) {
+                return Ok(false)
+            }
+            let winner = players[picks.next().unwrap_or_default() % players.len()].clone();
+            alive.retain(|p| *p == winner || !players.contains(p));
+            bracket.report(winner);
+        }
+
+        Ok(alive.len() <= 1 && bracket.winner() == alive.first())
+    })
+}
+
+
+#[quickcheck]
+fn bracket_walkover(players: Vec<crate::player::Tag>, match_size: u8) -> bool {
+    let mut bracket = tournament::Bracket::new(players.clone(), match_size.into());
+    bracket.next_match().is_none() && bracket.winner() == players.first()
+}
+
+
+#[quickcheck]
+fn summary_serve_keypress(
+    ranking: Vec<crate::player::Tag>,
+    input: crate::tests::ASCIIString,
+) -> Result<TestResult, ConnTaskError> {
+    use futures::StreamExt;
+
+    let input: String = input.into();
+    if input.is_empty() {
+        return Ok(TestResult::discard())
+    }
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let mut display = sink_display();
+        let input = ascii_stream(input.as_ref()).chain(futures::stream::pending());
+        summary::serve(ranking.into(), &mut display, input, None).await?;
+        Ok(TestResult::passed())
+    })
+}
+
+
+#[quickcheck]
+fn win_condition_parse(kind: u8, num: u8) -> bool {
+    let condition = match kind % 4 {
+        0 => rules::WinCondition::None,
+        1 => rules::WinCondition::FirstTo(num.max(1)),
+        2 => rules::WinCondition::Rounds(num.max(1)),
+        _ => rules::WinCondition::LowestTotal(num.max(1)),
+    };
+    condition.to_string().parse::<rules::WinCondition>().ok() == Some(condition)
+}
+
+
+#[quickcheck]
+fn record_first_to(
+    players: Vec<crate::player::tests::TestHandle>,
+    target: u8,
+    picks: Vec<usize>,
+) -> Result<TestResult, Box<dyn std::error::Error>> {
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let handles: Vec<crate::player::Handle> = players.into_iter().map(Into::into).collect();
+        if handles.is_empty() || target == 0 {
+            return Ok(TestResult::discard())
+        }
+        let tags: Vec<_> = handles.iter().map(crate::player::Handle::tag).collect();
+        let condition = rules::WinCondition::FirstTo(target);
+
+        let mut record = rules::Record::new(tags.clone());
+        let mut wins = vec![0u8; tags.len()];
+        for pick in picks.into_iter().chain(std::iter::repeat(0)) {
+            if record.is_decided(condition) {
+                break
+            }
+            let pick = pick % tags.len();
+            let mut ranking: Vec<_> = tags.iter().cloned().map(|t| (t, 1)).collect();
+            ranking.swap(0, pick);
+            ranking[0].1 = 0;
+            if record.add_round(ranking.as_ref()) != Some(tags[pick].clone()) {
+                return Ok(TestResult::failed())
+            }
+            wins[pick] += 1;
+        }
+
+        let best = (0..tags.len()).find(|i| wins[*i] == target);
+        Ok(TestResult::from_bool(best.map(|i| tags[i].clone()) == record.winner(condition)))
+    })
+}
+
+
 #[quickcheck]
 fn ascii_stream_smoke(orig: crate::tests::ASCIIString) -> Result<bool, ConnTaskError> {
     use futures::TryStreamExt;
@@ -387,15 +563,82 @@ fn ascii_stream_smoke(orig: crate::tests::ASCIIString) -> Result<bool, ConnTaskE
 }
 
 
+#[tokio::test]
+async fn serve_end_to_end() -> Result<(), Box<dyn std::error::Error>> {
+    use std::convert::TryFrom;
+
+    use tokio::io::AsyncWriteExt;
+
+    use crate::display::tests::VTReader;
+    use crate::util::Colour;
+
+    let (client, server) = tokio::io::duplex(1024);
+    let (client_read, mut client_write) = tokio::io::split(client);
+    let (server_read, server_write) = tokio::io::split(server);
+    let mut term = VTReader::new(client_read, DISPLAY_HEIGHT, DISPLAY_WIDTH);
+
+    let addr = std::net::SocketAddrV6::new(std::net::Ipv6Addr::LOCALHOST, 4242, 0, 0).into();
+    let me = {
+        let (notifier, _) = tokio::sync::mpsc::unbounded_channel();
+        let task = tokio::spawn(futures::future::pending());
+        crate::player::Handle::new(Arc::new(crate::player::Data::new("Alice".into(), addr, task)), notifier)
+    };
+    let tag = me.tag();
+
+    let (ports, mut lobby) = lobby::ports();
+    let (phase_sender, phase) = watch::channel(GamePhase::<rand_pcg::Pcg64Mcg>::Lobby{ports});
+    let serve = tokio::spawn(do_serve(Connection::new(server_read, server_write), phase, addr.into()));
+
+    let steps = async {
+        term.wait_for(|vt| vt.contains("Please enter your name:")).await?;
+        client_write.write_all(b"Alice\n").await?;
+        let (name, _) = lobby.receive_registration(Some(me)).await.ok_or(crate::error::NoneError)?;
+        term.wait_for(|vt| vt.contains("T: theme")).await?;
+
+        let (ports, _waiting) = waiting::ports(std::iter::once(tag.clone()), None, Vec::new());
+        phase_sender.send(GamePhase::Waiting{ports})?;
+        term.wait_for(|vt| vt.contains("Press any key when ready.") && vt.contains("Alice")).await?;
+        client_write.write_all(b" ").await?;
+        term.wait_for(|vt| vt.contains("Wait for the round to start.")).await?;
+
+        let dimensions: util::Dimensions = Default::default();
+        let virus = dimensions.positions().last().ok_or(crate::error::NoneError)?;
+        let (ports, _round) = round::ports(std::iter::once(tag.clone()), 1, broadcast::channel(1).0);
+        let settings = round::Settings {
+            dimensions,
+            viruses: std::iter::once((virus, Colour::Red)).collect(),
+            tick_duration: std::time::Duration::from_secs(60),
+            ..Default::default()
+        };
+        phase_sender.send(GamePhase::Round{ports, settings, rng: rand::SeedableRng::seed_from_u64(0), num: 1})?;
+        term.wait_for(|vt| vt.cells().any(|c| {
+            let colour = c.format.fg_colour.and_then(|(c, _)| Colour::try_from(c).ok());
+            colour == Some(Colour::Red) && (c.data == '>' || c.data == '-')
+        })).await?;
+        assert!(term.vt().contains("Alice"));
+
+        phase_sender.send(GamePhase::End{ranking: vec![tag].into()})?;
+        term.wait_for(|vt| vt.contains("The game is over!") && vt.contains("Alice")).await?;
+        client_write.write_all(b" ").await?;
+
+        Ok::<_, Box<dyn std::error::Error>>(name)
+    };
+    let name = tokio::time::timeout(std::time::Duration::from_secs(10), steps).await??;
+    serve.await??;
+    assert_eq!(name, "Alice");
+    Ok(())
+}
+
+
 /// Populate a field's display from given static and moving fields
 ///
 async fn populate_field_display(
     handle: &mut crate::display::DrawHandle<'_, impl tokio::io::AsyncWrite + Send + Unpin>,
-    field: &crate::display::FieldUpdater,
+    field: &mut crate::display::FieldUpdater,
     static_field: &crate::field::StaticField,
     moving_field: &crate::field::MovingField,
 ) -> std::io::Result<()> {
-    let whole_field = crate::util::ROWS.flat_map(crate::util::complete_row);
+    let whole_field = static_field.dimensions().positions();
 
     field.place_viruses(
         handle,
@@ -404,11 +647,11 @@ async fn populate_field_display(
     ).await?;
     field.update(
         handle,
-        whole_field.clone().filter_map(|p| static_field[p].as_element().map(|v| (p, Some(v.colour())))),
+        whole_field.clone().filter_map(|p| static_field[p].as_element().map(|v| (p, Some(v.appearance())))),
     ).await?;
     field.update(
         handle,
-        whole_field.filter_map(|p| moving_field[p].as_ref().map(|v| (p, Some(v.colour())))),
+        whole_field.filter_map(|p| moving_field[p].as_ref().map(|v| (p, Some(v.appearance())))),
     ).await?;
     Ok(())
 }
@@ -432,9 +675,9 @@ fn check_field_display(
     let v: Vec<_> = crate::display::tests::tile_contents(vt, area).map(|(p, [a, _])| {
         let colour = a.format.fg_colour.map(|(c, _)| c).and_then(|c| c.try_into().ok());
         let displayed = match a.data {
-            0x2D | 0x3E => colour.map(TC::Virus).unwrap_or(TC::Invalid),
-            0x28        => colour.map(TC::Element).unwrap_or(TC::Invalid),
-            0x20        => TC::None,
+            '-' | '>'   => colour.map(TC::Virus).unwrap_or(TC::Invalid),
+            '('         => colour.map(TC::Element).unwrap_or(TC::Invalid),
+            ' '         => TC::None,
             _           => TC::Invalid,
         };
         let r#static = match &static_field[p] {
@@ -496,9 +739,15 @@ impl std::fmt::Display for TileContents {
             Self::Element(C::Red)       => "ER",
             Self::Element(C::Yellow)    => "EY",
             Self::Element(C::Blue)      => "EB",
+            Self::Element(C::Green)     => "EG",
+            Self::Element(C::Magenta)   => "EM",
+            Self::Element(C::Cyan)      => "EC",
             Self::Virus(C::Red)         => "VR",
             Self::Virus(C::Yellow)      => "VY",
             Self::Virus(C::Blue)        => "VB",
+            Self::Virus(C::Green)       => "VG",
+            Self::Virus(C::Magenta)     => "VM",
+            Self::Virus(C::Cyan)        => "VC",
             Self::Invalid               => "I",
         })
     }
@@ -0,0 +1,115 @@
+//! Tournament bracket
+
+use crate::display;
+use crate::player;
+
+
+/// Single elimination bracket
+///
+/// A bracket consists of consecutive stages, each consisting of a number of
+/// matches. Players are distributed over the matches of the first stage. The
+/// winners of a stage are distributed over the matches of the next stage until
+/// only a single player is left.
+///
+#[derive(Clone, Debug)]
+pub struct Bracket {
+    stages: Vec<Vec<Match>>,
+    match_size: usize,
+}
+
+impl Bracket {
+    /// Create a new bracket for the given players
+    ///
+    /// Each match will be played by at most `match_size` players.
+    ///
+    pub fn new(players: impl IntoIterator<Item = player::Tag>, match_size: usize) -> Self {
+        let match_size = match_size.max(2);
+        let stage = Self::stage(players.into_iter().collect(), match_size);
+        Self {stages: vec![stage], match_size}
+    }
+
+    /// Retrieve the players of the next match to be played
+    ///
+    /// If all matches of the current stage were decided, this function will
+    /// create the next stage from their winners. Matches with a single player,
+    /// or with fewer than two connected players, are decided automatically. If
+    /// the bracket is decided, this function returns `None`.
+    ///
+    pub fn next_match(&mut self) -> Option<player::Roster> {
+        loop {
+            let stage = self.stages.last_mut()?;
+            if let Some(current) = stage.iter_mut().find(|m| m.winner.is_none()) {
+                let connected: Vec<_> = current.players.iter().filter(|p| p.is_connected()).cloned().collect();
+                if connected.len() > 1 {
+                    return Some(current.players.clone())
+                }
+                current.winner = connected.first().or_else(|| current.players.first()).cloned();
+                continue
+            }
+
+            let winners: Vec<_> = stage.iter().filter_map(|m| m.winner.clone()).collect();
+            if winners.len() <= 1 {
+                return None
+            }
+            let next = Self::stage(winners, self.match_size);
+            self.stages.push(next);
+        }
+    }
+
+    /// Report the winner of the current match
+    ///
+    /// The current match is the one previously returned by `next_match`.
+    ///
+    pub fn report(&mut self, winner: player::Tag) {
+        if let Some(current) = self.stages.last_mut().and_then(|s| s.iter_mut().find(|m| m.winner.is_none())) {
+            current.winner = Some(winner)
+        } else {
+            log::warn!("No undecided match to report a winner for");
+        }
+    }
+
+    /// Retrieve the winner of the tournament, if it is decided
+    ///
+    pub fn winner(&self) -> Option<&player::Tag> {
+        match self.stages.last()?.as_slice() {
+            [last] => last.winner.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Retrieve the stages of this bracket
+    ///
+    pub fn stages(&self) -> impl Iterator<Item = &[Match]> {
+        self.stages.iter().map(AsRef::as_ref)
+    }
+
+    /// Distribute the given players over matches of a single stage
+    ///
+    /// Players are distributed as evenly as possible.
+    ///
+    fn stage(players: Vec<player::Tag>, match_size: usize) -> Vec<Match> {
+        let matches = players.len().div_ceil(match_size);
+        let mut stage: Vec<_> = (0..matches).map(|_| Match::default()).collect();
+        players.into_iter().enumerate().for_each(|(n, p)| stage[n % matches].players.push(p));
+        stage
+    }
+}
+
+
+/// A single match within a bracket
+///
+#[derive(Clone, Debug, Default)]
+pub struct Match {
+    players: player::Roster,
+    winner: Option<player::Tag>,
+}
+
+impl display::BracketMatch for Match {
+    fn players(&self) -> &[player::Tag] {
+        self.players.as_ref()
+    }
+
+    fn winner(&self) -> Option<&player::Tag> {
+        self.winner.as_ref()
+    }
+}
@@ -31,30 +31,75 @@ pub async fn serve<P>(
     let mut scores = control.scores;
     let mut countdown = control.countdown;
     let ready = control.ready;
+    let participating = scores.borrow().iter().any(|e| *me == e.tag);
 
     // Set up the display
+    let theme = display.options().theme;
     let mut area = display.area().await?.pad_top(1);
     let mut left = area.split_left(super::COLUMN_SPLIT);
     let mut ct = left.split_top(super::INSTRUCTION_SPLIT);
 
     ct.place_top(display::StaticText::from("Round starts in:")).await?;
     ct = ct.pad_top(1);
-    let num_display = ct.place_top(display::DynamicText::new_line(4u16.try_into().unwrap())).await?;
+    let mut num_display = ct.place_top(display::DynamicText::new_line(4u16.try_into().unwrap())).await?;
     ct = ct.pad_top(1);
     ct.place_top(display::StaticText::from("or when everybody's ready.")).await?;
     ct = ct.pad_top(1);
-    let inst = ct.place_center(
+    let mut inst = ct.place_center(
         display::DynamicText::new_line((super::COLUMN_SPLIT - 2).try_into().unwrap())
     ).await?;
 
     left.place_center(display::StaticText::from(&super::INSTRUCTIONS as &[_])).await?;
 
-    let max_scores = area.rows().saturating_sub(2);
-    let mut score_board = area.place_center(display::ScoreBoard::new(max_scores).show_scores(false)).await?;
     let highlight = {
         let tag = me.tag();
         move |t: &player::Tag| *t == tag
     };
+    let recap_lines: Vec<_> = control.recap.iter().map(|(t, s)| recap_line(t, s)).collect();
+    let (mut score_board, mut recap, mut bracket) = if control.bracket.is_some() || !recap_lines.is_empty() {
+        // Show the players of the next match above the recap and bracket. The
+        // rows available are shared between them, as neither the number of
+        // players nor the recap are guaranteed to fit on the screen.
+        let wanted = [
+            scores.borrow().len().saturating_add(2),
+            if recap_lines.is_empty() { 0 } else { recap_lines.len().saturating_add(2) },
+            if control.bracket.is_some() { usize::MAX } else { 0 },
+        ];
+        let [board_rows, recap_rows, bracket_rows] = distribute_rows(area.rows(), wanted);
+
+        let top = area.split_top(board_rows);
+        let max_scores = board_rows.saturating_sub(2);
+        let score_board = top.place_center(display::ScoreBoard::new(max_scores).show_scores(false).theme(theme)).await?;
+
+        // The recap is ordered by ranking, so truncating it will leave the top
+        // players of the previous round.
+        let recap = if let Some(rows) = std::num::NonZeroU16::new(recap_rows.saturating_sub(1)) {
+            let mut recap = area.split_top(recap_rows);
+            Some(recap.place_top(display::DynamicText::new(rows, (RECAP_WIDTH as u16).try_into().unwrap())).await?)
+        } else {
+            None
+        };
+
+        let bracket = if let Some(bracket) = control.bracket {
+            let rows = bracket_rows.saturating_sub(1);
+            let cols = area.cols().saturating_sub(2);
+            Some((area.place_center(display::Bracket::new(rows, cols)).await?, bracket))
+        } else {
+            drop(area);
+            None
+        };
+        (score_board, recap, bracket)
+    } else {
+        let max_scores = area.rows().saturating_sub(2);
+        (area.place_center(display::ScoreBoard::new(max_scores).show_scores(false).theme(theme)).await?, None, None)
+    };
+    if let Some(recap) = recap.as_mut() {
+        let lines = std::iter::once(recap_header()).chain(recap_lines);
+        recap.update(&mut display.handle().await?, lines).await?;
+    }
+    if let Some((updater, bracket)) = bracket.as_mut() {
+        updater.update(&mut display.handle().await?, bracket.stages(), &highlight).await?;
+    }
     {
         let scores = scores.borrow().clone();
         score_board.update(&mut display.handle().await?, scores.iter(), &highlight).await?
@@ -65,13 +110,23 @@ pub async fn serve<P>(
         let countdown = *countdown.borrow();
         num_display.update_single(&mut display.handle().await?, countdown).await?
     }
-    inst.update_single(&mut display.handle().await?, "Press any key when ready.").await?;
+    if participating {
+        inst.update_single(&mut display.handle().await?, "Press any key when ready.").await?;
+    } else {
+        inst.update_single(&mut display.handle().await?, "You'll watch the next match.").await?;
+    }
 
     // Actual waiting display logic
     while !phase.transitioned() {
         tokio::select! {
             res = input.next() => match res {
-                Some(Ok(_)) => {
+                Some(Ok(super::REDRAW_KEY)) => {
+                    let mut entities: Vec<&dyn display::Repaint> = vec![&num_display, &inst, &score_board];
+                    entities.extend(recap.as_ref().map(|r| r as &dyn display::Repaint));
+                    entities.extend(bracket.as_ref().map(|(b, _)| b as &dyn display::Repaint));
+                    display.redraw(&entities).await?
+                },
+                Some(Ok(_)) if participating => {
                     ready.send(me.tag()).await.map_err(ConnTaskError::other)?;
                     inst.update_single(&mut display.handle().await?, "Wait for the round to start.").await?;
                 },
@@ -148,9 +203,16 @@ pub async fn control(
 /// Create ports for communication between connection and control task
 ///
 /// This function returns a pair of ports specific to the waiting phase, one for
-/// the connection task and one for the control task.
+/// the connection task and one for the control task. The `scores` are expected
+/// to hold the players participating in the next round. If a `bracket` is
+/// given, it will be displayed alongside them. The `recap` holds the statistics
+/// of the previous round, if any.
 ///
-pub fn ports(scores: impl IntoIterator<Item = player::Tag>) -> (Ports, ControlPorts) {
+pub fn ports(
+    scores: impl IntoIterator<Item = player::Tag>,
+    bracket: Option<Arc<super::tournament::Bracket>>,
+    recap: impl IntoIterator<Item = (player::Tag, super::round::RoundStats)>,
+) -> (Ports, ControlPorts) {
     let scores: Arc<_> = scores.into_iter().map(Into::into).collect();
     let player_num = scores.len();
 
@@ -158,7 +220,8 @@ pub fn ports(scores: impl IntoIterator<Item = player::Tag>) -> (Ports, ControlPo
     let (countdown_sender, countdown_receiver) = watch::channel(Default::default());
     let (readiness_sender, readiness_receiver) = mpsc::channel(player_num);
 
-    let ports = Ports {scores: score_receiver, countdown: countdown_receiver, ready: readiness_sender};
+    let recap = recap.into_iter().collect();
+    let ports = Ports {scores: score_receiver, countdown: countdown_receiver, ready: readiness_sender, bracket, recap};
     let control = ControlPorts {scores: score_sender, countdown: countdown_sender, ready: readiness_receiver};
 
     (ports, control)
@@ -172,6 +235,8 @@ pub struct Ports {
     scores: watch::Receiver<Arc<[ScoreBoardEntry]>>,
     countdown: watch::Receiver<u8>,
     ready: mpsc::Sender<player::Tag>,
+    bracket: Option<Arc<super::tournament::Bracket>>,
+    recap: Arc<[(player::Tag, super::round::RoundStats)]>,
 }
 
 #[cfg(test)]
@@ -249,6 +314,64 @@ impl display::ScoreBoardEntry for ScoreBoardEntry {
 }
 
 
+/// Distribute the given number of rows among parts requesting `wanted` rows
+///
+/// Parts requesting no more than an even share of the rows left get what they
+/// requested. The remaining rows are shared evenly among the other parts.
+///
+fn distribute_rows<const N: usize>(mut rows: u16, wanted: [usize; N]) -> [u16; N] {
+    let mut order: Vec<_> = (0..N).filter(|i| wanted[*i] > 0).collect();
+    order.sort_by_key(|i| wanted[*i]);
+
+    let mut res = [0; N];
+    let mut left = order.len() as u16;
+    for i in order {
+        let share = rows / left;
+        res[i] = std::cmp::min(wanted[i], share.into()) as u16;
+        rows -= res[i];
+        left -= 1;
+    }
+    res
+}
+
+
+/// Format the header of the round recap
+///
+fn recap_header() -> String {
+    format!(
+        "{:<12} {:>6} {:>4} {:>5} {:>5} {:>4} {:>6}",
+        "Name", "Clear", "Caps", "Chain", "Sent", "Recv", "Out",
+    )
+}
+
+
+/// Format a player's line of the round recap
+///
+/// Attacks received are counted in capsule elements. Attacks sent are listed as
+/// capsule elements and garbage rows, separated by a slash.
+///
+fn recap_line(tag: &player::Tag, stats: &super::round::RoundStats) -> String {
+    let time = |t: Option<time::Duration>| t
+        .map(|t| format!("{}.{}s", t.as_secs(), t.subsec_millis() / 100))
+        .unwrap_or_else(|| "-".to_string());
+    format!(
+        "{:<12.12} {:>6} {:>4} {:>5} {:>5} {:>4} {:>6}",
+        tag.name(),
+        time(stats.clear_time),
+        stats.capsules,
+        stats.max_chain,
+        format!("{}/{}", stats.sent, stats.garbage),
+        stats.received,
+        time(stats.top_out_time),
+    )
+}
+
+
+/// Width of the round recap
+///
+const RECAP_WIDTH: usize = 48;
+
+
 /// Waiting time in seconds
 ///
 /// This constant is used as the initial value for the counter used for counting
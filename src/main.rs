@@ -1,8 +1,9 @@
 //! Dr. W. Falls
 
+use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::{net, sync::watch};
+use tokio::net;
 
 #[macro_use]
 extern crate clap;
@@ -17,7 +18,9 @@ mod display;
 mod error;
 mod field;
 mod game;
+mod metrics;
 mod player;
+mod room;
 mod util;
 
 #[cfg(test)]
@@ -26,14 +29,7 @@ mod tests;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let matches = clap_app!(dr_w_falls =>
-        (@arg listen: -l --listen +takes_value "Address to listen on")
-        (@arg port: -p --port +takes_value "Port to listen on")
-        (@arg maxp: --max-players +takes_value "Maximum number of players allowed")
-        (@arg virs: --virs +takes_value "number of viruses placed on the field at the beginning of a round")
-        (@arg tick: --tick +takes_value "duration of a tick (the time a capsule moved down one tile) im ms")
-        (@arg console: --gm-sock +takes_value "serve a GM console on a UNIX domain socket at this path")
-    ).get_matches();
+    let matches = app().get_matches();
 
 
     // Collect settings
@@ -51,35 +47,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or(2020);
     let addr = std::net::SocketAddr::new(addr, port);
 
-    let settings = console::Settings {
-        accept_players: true,
-        max_players: matches
-            .value_of("maxp")
-            .map(str::parse)
-            .transpose()
-            .map_err(|e| error::WrappedErr::new("Expected maximum number of players", e))?
-            .unwrap_or(u8::MAX),
-        virus_count: matches
-            .value_of("virs")
-            .map(str::parse)
-            .transpose()
-            .map_err(|e| error::WrappedErr::new("Expected number of viruses", e))?
-            .unwrap_or(10),
-        tick_duration: Duration::from_millis(matches
-            .value_of("virs")
-            .map(str::parse)
-            .transpose()
-            .map_err(|e| error::WrappedErr::new("Expected tick duration in number of ms", e))?
-            .unwrap_or(200)),
-    };
+    let settings = settings(&matches)?;
+
+    let max_rooms = matches
+        .value_of("maxr")
+        .map(str::parse)
+        .transpose()
+        .map_err(|e| error::WrappedErr::new("Expected maximum number of rooms", e))?
+        .unwrap_or(8);
 
     let gm_sock_path = matches.value_of_os("console");
 
+    let gm_addr: Option<std::net::SocketAddr> = matches
+        .value_of("gm_tcp")
+        .map(str::parse)
+        .transpose()
+        .map_err(|e| error::WrappedErr::new("Expected address for the remote GM console", e))?;
+    let gm_tokens = matches
+        .value_of_os("gm_tokens")
+        .map(std::fs::read_to_string)
+        .transpose()
+        .map_err(|e| error::WrappedErr::new("Could not read GM tokens", e))?
+        .map(|s| s.parse::<console::Tokens>())
+        .transpose()
+        .map_err(|e| error::WrappedErr::new("Malformed GM tokens", e))?;
+
+    let admin_addr: Option<std::net::SocketAddr> = matches
+        .value_of("admin_http")
+        .map(str::parse)
+        .transpose()
+        .map_err(|e| error::WrappedErr::new("Expected address for the admin API", e))?;
+    // The admin API doesn't authenticate requests, so we only serve it locally
+    if admin_addr.map(|a| !a.ip().is_loopback()).unwrap_or(false) {
+        return Err(error::WrappedErr::new(
+            "The admin API may only listen on a loopback address",
+            error::NoneError,
+        ).into())
+    }
+
 
     // Setup
-    let (control_sender, control_receiver) = watch::channel(settings.as_lobby_control());
-    let (phase_sender, phase) = watch::channel(game::GamePhase::<rand_pcg::Pcg64Mcg>::default());
-    let roster = Default::default();
+    let registry = Arc::new(room::Registry::<rand_pcg::Pcg64Mcg>::new(settings, max_rooms));
+    registry
+        .create(INITIAL_ROOM)
+        .await
+        .map_err(|e| error::WrappedErr::new("Could not create initial room", e))?;
 
     log::info!("Listening for players on {}", addr);
     let player_sock = net::TcpListener::bind(addr)
@@ -89,17 +101,136 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .map(net::UnixListener::bind)
         .transpose()
         .map_err(|e| error::WrappedErr::new("Could not open GM socket", e))?;
+    let gm_remote = if let Some(addr) = gm_addr {
+        let tokens = gm_tokens.ok_or_else(|| error::WrappedErr::new(
+            "Remote GM console requires tokens",
+            error::NoneError,
+        ))?;
+        log::info!("Serving remote GM consoles on {}", addr);
+        let listener = net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| error::WrappedErr::new("Could not listen for remote GMs", e))?;
+        Some((listener, Arc::new(tokens)))
+    } else {
+        None
+    };
+    let admin_sock = if let Some(addr) = admin_addr {
+        log::info!("Serving admin API on {}", addr);
+        Some(net::TcpListener::bind(addr)
+            .await
+            .map_err(|e| error::WrappedErr::new("Could not listen for admin requests", e))?)
+    } else {
+        None
+    };
 
 
     // Run
     log::info!("Finished setup {}", addr);
-    let gm = console::game_master(control_sender, settings, phase.clone(), Clone::clone(&roster), gm_sock);
-    let game = game::run(player_sock, control_receiver, roster, phase_sender, phase);
+    let gm = console::game_master(registry.clone(), gm_sock, gm_remote);
+    let admin = async {
+        match admin_sock {
+            Some(listener) => console::http_admin(listener, registry.clone()).await,
+            None => futures::future::pending().await,
+        }
+    };
+    let rooms = room::run(player_sock, registry.clone());
     let sigint = tokio::signal::ctrl_c();
     tokio::select!{
         r = gm => r.map_err(Into::into),
-        r = game => r.map_err(Into::into),
+        r = admin => r.map_err(Into::into),
+        r = rooms => r.map_err(Into::into),
         r = sigint => r.map_err(Into::into),
     }
 }
 
+
+/// Create the command line interface
+///
+fn app() -> clap::App<'static, 'static> {
+    clap_app!(dr_w_falls =>
+        (@arg listen: -l --listen +takes_value "Address to listen on")
+        (@arg port: -p --port +takes_value "Port to listen on")
+        (@arg maxp: --("max-players") +takes_value "Maximum number of players allowed")
+        (@arg maxr: --("max-rooms") +takes_value "Maximum number of rooms allowed")
+        (@arg virs: --virs +takes_value "number of viruses placed on the field at the beginning of a round")
+        (@arg bracket: --bracket +takes_value "number of players per tournament match, 0 for a free-for-all")
+        (@arg rule: --rule +takes_value "condition deciding a match: none, first-to:<n>, rounds:<n> or lowest:<n>")
+        (@arg field: --field +takes_value "dimensions of the play field as <width>x<height>, e.g. 8x16")
+        (@arg colours: --colours +takes_value "number of colours used for viruses and capsules, between 2 and 6")
+        (@arg match_len: --("match-len") +takes_value "minimum number of tiles in a row eliminating them, between 3 and 5")
+        (@arg diagonal: --diagonal "also eliminate diagonal rows of tiles of the same colour")
+        (@arg specials: --specials "spawn bombs and rainbow elements in the capsule stream")
+        (@arg no_effects: --("no-effects") "don't animate eliminations, e.g. for slow connections")
+        (@arg tick: --tick +takes_value "duration of a tick (the time a capsule moved down one tile) im ms")
+        (@arg console: --("gm-sock") +takes_value "serve a GM console on a UNIX domain socket at this path")
+        (@arg gm_tcp: --("gm-tcp") +takes_value "serve an authenticated GM console over TCP on this address")
+        (@arg gm_tokens: --("gm-tokens") +takes_value "file holding tokens for remote GMs, one '<token> <read|full>' per line")
+        (@arg admin_http: --("admin-http") +takes_value "serve an HTTP admin API on this loopback address, e.g. 127.0.0.1:8080")
+    )
+}
+
+
+/// Collect the default room settings from the command line arguments
+///
+fn settings(matches: &clap::ArgMatches) -> Result<room::Settings, error::WrappedErr> {
+    Ok(room::Settings {
+        accept_players: true,
+        max_players: matches
+            .value_of("maxp")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected maximum number of players", e))?
+            .unwrap_or(u8::MAX),
+        virus_count: matches
+            .value_of("virs")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected number of viruses", e))?
+            .unwrap_or(10),
+        tick_duration: Duration::from_millis(matches
+            .value_of("tick")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected tick duration in number of ms", e))?
+            .unwrap_or(200)),
+        match_size: matches
+            .value_of("bracket")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected number of players per match", e))?
+            .unwrap_or(0),
+        win_condition: matches
+            .value_of("rule")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected win condition", e))?
+            .unwrap_or_default(),
+        dimensions: matches
+            .value_of("field")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected field dimensions", e))?
+            .unwrap_or_default(),
+        palette: matches
+            .value_of("colours")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected number of colours", e))?
+            .unwrap_or_default(),
+        match_length: matches
+            .value_of("match_len")
+            .map(str::parse)
+            .transpose()
+            .map_err(|e| error::WrappedErr::new("Expected match length", e))?
+            .unwrap_or_default(),
+        diagonal: matches.is_present("diagonal"),
+        specials: matches.is_present("specials"),
+        effects: !matches.is_present("no_effects"),
+    })
+}
+
+
+/// Name of the room created on startup
+///
+const INITIAL_ROOM: &str = "main";
+
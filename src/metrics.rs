@@ -0,0 +1,316 @@
+//! Operational metrics
+//!
+//! Metrics are collected in a single global instance of [Metrics] and may be
+//! presented in the Prometheus text exposition format.
+
+#[cfg(test)]
+mod tests;
+
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+
+/// Global metrics
+///
+pub static METRICS: Metrics = Metrics {
+    connections: Counter::new(),
+    players_connected: Gauge::new(),
+    phase_tasks: Family::new("phase", [
+        ("lobby", Gauge::new()),
+        ("waiting", Gauge::new()),
+        ("round", Gauge::new()),
+        ("spectating", Gauge::new()),
+        ("standings", Gauge::new()),
+        ("summary", Gauge::new()),
+    ]),
+    conn_task_errors: Family::new("kind", [
+        ("terminated", Counter::new()),
+        ("would_block", Counter::new()),
+        ("other", Counter::new()),
+    ]),
+    rounds: Counter::new(),
+    round_duration: Histogram::new(&[10., 30., 60., 120., 300., 600., 1200.]),
+    bytes_written: Counter::new(),
+    connection_bytes: Histogram::new(&[1e3, 1e4, 1e5, 1e6, 1e7, 1e8]),
+    flush_latency: Histogram::new(&[1e-4, 1e-3, 5e-3, 1e-2, 5e-2, 0.1, 0.5, 1.]),
+};
+
+
+/// Collection of all metrics
+///
+pub struct Metrics {
+    /// Number of connections admitted to a room's lobby
+    pub connections: Counter,
+    /// Number of connections currently served by a room's game
+    pub players_connected: Gauge,
+    /// Number of connection tasks in each phase
+    pub phase_tasks: Family<Gauge, 6>,
+    /// Number of connection tasks ended by each kind of error
+    pub conn_task_errors: Family<Counter, 3>,
+    /// Number of rounds played
+    pub rounds: Counter,
+    /// Duration of rounds in seconds
+    pub round_duration: Histogram,
+    /// Number of bytes written to players
+    pub bytes_written: Counter,
+    /// Number of bytes written to each player over a connection's lifetime
+    pub connection_bytes: Histogram,
+    /// Time in seconds it took to flush a draw handle
+    pub flush_latency: Histogram,
+}
+
+impl Metrics {
+    /// Render all metrics in the Prometheus text format
+    ///
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let mut write = |name, help, metric: &dyn Metric| {
+            out.push_str(&format!("# HELP {} {}\n# TYPE {} {}\n", name, help, name, metric.kind()));
+            metric.render(&mut out, name);
+        };
+
+        write("dr_w_falls_connections_total", "Connections admitted to a room", &self.connections);
+        write("dr_w_falls_players_connected", "Connections currently served by a game", &self.players_connected);
+        write("dr_w_falls_phase_tasks", "Connection tasks in each phase", &self.phase_tasks);
+        write("dr_w_falls_conn_task_errors_total", "Connection tasks ended by errors", &self.conn_task_errors);
+        write("dr_w_falls_rounds_total", "Rounds played", &self.rounds);
+        write("dr_w_falls_round_duration_seconds", "Duration of rounds", &self.round_duration);
+        write("dr_w_falls_written_bytes_total", "Bytes written to players", &self.bytes_written);
+        write("dr_w_falls_connection_written_bytes", "Bytes written over a connection", &self.connection_bytes);
+        write("dr_w_falls_flush_latency_seconds", "Latency of display flushes", &self.flush_latency);
+        out
+    }
+}
+
+
+/// A single metric
+///
+pub trait Metric {
+    /// Retrieve the Prometheus type of this metric
+    ///
+    fn kind(&self) -> &'static str;
+
+    /// Render the metric's samples with the given name and labels
+    ///
+    /// The `labels` are expected to be either empty or a comma separated list
+    /// of label pairs.
+    ///
+    fn render_labeled(&self, out: &mut String, name: &str, labels: &str);
+
+    /// Render the metric's samples with the given name
+    ///
+    fn render(&self, out: &mut String, name: &str) {
+        self.render_labeled(out, name, "")
+    }
+}
+
+
+/// Monotonically increasing counter
+///
+#[derive(Debug)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    /// Create a new counter
+    ///
+    pub const fn new() -> Self {
+        Self(AtomicU64::new(0))
+    }
+
+    /// Increment the counter by one
+    ///
+    pub fn inc(&self) {
+        self.add(1)
+    }
+
+    /// Increment the counter by the given value
+    ///
+    pub fn add(&self, value: u64) {
+        self.0.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Retrieve the current value
+    ///
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Counter {
+    fn kind(&self) -> &'static str {
+        "counter"
+    }
+
+    fn render_labeled(&self, out: &mut String, name: &str, labels: &str) {
+        out.push_str(&format!("{}{} {}\n", name, braced(labels), self.get()))
+    }
+}
+
+
+/// Gauge for values which may go up and down
+///
+#[derive(Debug)]
+pub struct Gauge(AtomicI64);
+
+impl Gauge {
+    /// Create a new gauge
+    ///
+    pub const fn new() -> Self {
+        Self(AtomicI64::new(0))
+    }
+
+    /// Increment the gauge for the lifetime of the returned guard
+    ///
+    pub fn track(&self) -> GaugeGuard<'_> {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        GaugeGuard(self)
+    }
+
+    /// Retrieve the current value
+    ///
+    pub fn get(&self) -> i64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Gauge {
+    fn kind(&self) -> &'static str {
+        "gauge"
+    }
+
+    fn render_labeled(&self, out: &mut String, name: &str, labels: &str) {
+        out.push_str(&format!("{}{} {}\n", name, braced(labels), self.get()))
+    }
+}
+
+
+/// Guard decrementing a [Gauge] when dropped
+///
+#[derive(Debug)]
+pub struct GaugeGuard<'a>(&'a Gauge);
+
+impl Drop for GaugeGuard<'_> {
+    fn drop(&mut self) {
+        (self.0).0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+
+/// Histogram of observed values
+///
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    buckets: [AtomicU64; MAX_BUCKETS],
+    sum: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Histogram {
+    /// Create a new histogram with the given upper bucket bounds
+    ///
+    /// The bounds are expected to be sorted. Only the first `MAX_BUCKETS`
+    /// bounds are considered.
+    ///
+    pub const fn new(bounds: &'static [f64]) -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        Self {bounds, buckets: [ZERO; MAX_BUCKETS], sum: AtomicU64::new(0), count: AtomicU64::new(0)}
+    }
+
+    /// Record an observed value
+    ///
+    pub fn observe(&self, value: f64) {
+        if let Some(n) = self.bounds().iter().position(|b| value <= *b) {
+            self.buckets[n].fetch_add(1, Ordering::Relaxed);
+        }
+        self.sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |s| Some((f64::from_bits(s) + value).to_bits()))
+            .ok();
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Retrieve the upper bounds of the buckets
+    ///
+    fn bounds(&self) -> &'static [f64] {
+        &self.bounds[..self.bounds.len().min(MAX_BUCKETS)]
+    }
+}
+
+impl Metric for Histogram {
+    fn kind(&self) -> &'static str {
+        "histogram"
+    }
+
+    fn render_labeled(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (bound, bucket) in self.bounds().iter().zip(self.buckets.iter()) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            out.push_str(&format!("{}_bucket{{{}{}le=\"{}\"}} {}\n", name, labels, sep, bound, cumulative));
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        out.push_str(&format!("{}_bucket{{{}{}le=\"+Inf\"}} {}\n", name, labels, sep, count));
+        out.push_str(&format!("{}_sum{} {}\n", name, braced(labels), f64::from_bits(self.sum.load(Ordering::Relaxed))));
+        out.push_str(&format!("{}_count{} {}\n", name, braced(labels), count));
+    }
+}
+
+
+/// Family of metrics distinguished by the value of a single label
+///
+#[derive(Debug)]
+pub struct Family<M, const N: usize> {
+    label: &'static str,
+    members: [(&'static str, M); N],
+}
+
+impl<M, const N: usize> Family<M, N> {
+    /// Create a new family from the given label values and metrics
+    ///
+    pub const fn new(label: &'static str, members: [(&'static str, M); N]) -> Self {
+        Self {label, members}
+    }
+
+    /// Retrieve the metric for the given label value
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the family does not contain the value.
+    ///
+    pub fn get(&self, value: &str) -> &M {
+        self.members
+            .iter()
+            .find(|(v, _)| *v == value)
+            .map(|(_, m)| m)
+            .expect("Unknown label value")
+    }
+}
+
+impl<M: Metric, const N: usize> Metric for Family<M, N> {
+    fn kind(&self) -> &'static str {
+        self.members.first().map(|(_, m)| m.kind()).unwrap_or("untyped")
+    }
+
+    fn render_labeled(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        self.members.iter().for_each(|(value, metric)| {
+            metric.render_labeled(out, name, &format!("{}{}{}=\"{}\"", labels, sep, self.label, value))
+        })
+    }
+}
+
+
+/// Wrap labels in braces, unless there are none
+///
+fn braced(labels: &str) -> String {
+    if labels.is_empty() {
+        Default::default()
+    } else {
+        format!("{{{}}}", labels)
+    }
+}
+
+
+/// Maximum number of buckets in a histogram
+///
+const MAX_BUCKETS: usize = 12;
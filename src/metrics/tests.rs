@@ -0,0 +1,56 @@
+//! Metrics tests
+
+use super::*;
+
+
+#[quickcheck]
+fn histogram_counts(values: Vec<u16>) -> bool {
+    static BOUNDS: [f64; 3] = [10., 100., 1000.];
+
+    let histogram = Histogram::new(&BOUNDS);
+    values.iter().for_each(|v| histogram.observe((*v).into()));
+
+    let mut out = String::new();
+    histogram.render(&mut out, "h");
+
+    let below = |b: f64| values.iter().filter(|v| f64::from(**v) <= b).count();
+    let expected = format!(
+        "h_bucket{{le=\"10\"}} {}\nh_bucket{{le=\"100\"}} {}\nh_bucket{{le=\"1000\"}} {}\nh_bucket{{le=\"+Inf\"}} {}\nh_sum {}\nh_count {}\n",
+        below(10.),
+        below(100.),
+        below(1000.),
+        values.len(),
+        values.iter().fold(0., |s, v| s + f64::from(*v)),
+        values.len(),
+    );
+    out == expected
+}
+
+
+#[test]
+fn family_render() {
+    let family = Family::new("phase", [("lobby", Gauge::new()), ("round", Gauge::new())]);
+    let guards: Vec<_> = (0..3).map(|_| family.get("round").track()).collect();
+    let lobby = family.get("lobby").track();
+    drop(lobby);
+
+    let mut out = String::new();
+    family.render(&mut out, "tasks");
+    assert_eq!(out, "tasks{phase=\"lobby\"} 0\ntasks{phase=\"round\"} 3\n");
+
+    drop(guards);
+    assert_eq!(family.get("round").get(), 0);
+}
+
+
+#[test]
+fn metrics_render() {
+    let out = METRICS.render();
+    assert!(out.contains("# TYPE dr_w_falls_rounds_total counter\n"));
+    assert!(out.contains("# TYPE dr_w_falls_round_duration_seconds histogram\n"));
+    assert!(out.contains("dr_w_falls_conn_task_errors_total{kind=\"other\"} "));
+    assert!(out
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .all(|l| l.rsplit(' ').next().map(|v| v.parse::<f64>().is_ok()).unwrap_or(false)));
+}
@@ -136,15 +136,22 @@ impl quickcheck::Arbitrary for Tag {
             name: tests::Name::arbitrary(g).into(),
             addr: Arbitrary::arbitrary(g),
             score: u32::arbitrary(g).into(),
+            wins: u32::arbitrary(g).into(),
+            stats: Default::default(),
             conn_state: None.into(),
         })}
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
-        let res = (tests::Name(self.name.clone()), self.addr, self.score.load(Ordering::Relaxed))
+        let res = (
+            tests::Name(self.name.clone()),
+            self.addr,
+            self.score.load(Ordering::Relaxed),
+            self.wins.load(Ordering::Relaxed),
+        )
             .shrink()
-            .map(|(n, addr, s)| Tag {
-                data: Arc::new(Data {name: n.into(), addr, score: s.into(), conn_state: None.into()}),
+            .map(|(n, addr, s, w)| Tag {
+                data: Arc::new(Data {name: n.into(), addr, score: s.into(), wins: w.into(), stats: Default::default(), conn_state: None.into()}),
             });
         Box::new(res)
     }
@@ -158,6 +165,8 @@ pub struct Data {
     name: String,
     addr: SocketAddr,
     score: AtomicU32,
+    wins: AtomicU32,
+    stats: Stats,
     conn_state: RwLock<Option<ConnTaskHandle>>,
 }
 
@@ -165,7 +174,7 @@ impl Data {
     /// Create a new player data object
     ///
     pub fn new(name: String, addr: SocketAddr, handle: ConnTaskHandle) -> Self {
-        Self {name, addr, score: 0.into(), conn_state: Some(handle).into()}
+        Self {name, addr, score: 0.into(), wins: 0.into(), stats: Default::default(), conn_state: Some(handle).into()}
     }
 
     /// Retrieve the player's name
@@ -192,6 +201,24 @@ impl Data {
         self.score.fetch_add(value, Ordering::Release)
     }
 
+    /// Retrieve the number of rounds won
+    ///
+    pub fn wins(&self) -> u32 {
+        self.wins.load(Ordering::Relaxed)
+    }
+
+    /// Count a round won by the player
+    ///
+    pub fn add_win(&self) -> u32 {
+        self.wins.fetch_add(1, Ordering::Release)
+    }
+
+    /// Retrieve the player's statistics
+    ///
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
     /// Check whether the player is still connected
     ///
     pub fn is_connected(&self) -> bool {
@@ -213,6 +240,69 @@ impl Data {
 }
 
 
+/// Statistics accumulated by a player over the course of a game
+///
+#[derive(Debug, Default)]
+pub struct Stats {
+    viruses: AtomicU32,
+    max_chain: AtomicU32,
+    attacks: AtomicU32,
+    garbage: AtomicU32,
+}
+
+impl Stats {
+    /// Retrieve the number of viruses cleared
+    ///
+    pub fn viruses_cleared(&self) -> u32 {
+        self.viruses.load(Ordering::Relaxed)
+    }
+
+    /// Add a given number of cleared viruses
+    ///
+    pub fn add_viruses_cleared(&self, value: u32) -> u32 {
+        self.viruses.fetch_add(value, Ordering::Release)
+    }
+
+    /// Retrieve the biggest chain, in number of rows eliminated
+    ///
+    pub fn max_chain(&self) -> u32 {
+        self.max_chain.load(Ordering::Relaxed)
+    }
+
+    /// Record a chain of the given number of rows eliminated
+    ///
+    /// The biggest chain will only be updated if the given chain is bigger.
+    ///
+    pub fn record_chain(&self, rows: u32) -> u32 {
+        self.max_chain.fetch_max(rows, Ordering::Release)
+    }
+
+    /// Retrieve the number of capsule elements sent to other players
+    ///
+    pub fn attacks_sent(&self) -> u32 {
+        self.attacks.load(Ordering::Relaxed)
+    }
+
+    /// Add a given number of capsule elements sent to other players
+    ///
+    pub fn add_attacks_sent(&self, value: u32) -> u32 {
+        self.attacks.fetch_add(value, Ordering::Release)
+    }
+
+    /// Retrieve the number of garbage rows sent to other players
+    ///
+    pub fn garbage_sent(&self) -> u32 {
+        self.garbage.load(Ordering::Relaxed)
+    }
+
+    /// Add a given number of garbage rows sent to other players
+    ///
+    pub fn add_garbage_sent(&self, value: u32) -> u32 {
+        self.garbage.fetch_add(value, Ordering::Release)
+    }
+}
+
+
 /// Task handle of connection tasks
 ///
 pub type ConnTaskHandle = JoinHandle<()>;
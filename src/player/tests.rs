@@ -71,6 +71,14 @@ fn data_score(name: Name, addr: std::net::SocketAddr, add: Vec<u32>) -> std::io:
 }
 
 
+#[quickcheck]
+fn stats_chain(chains: Vec<u32>) -> bool {
+    let stats: Stats = Default::default();
+    chains.iter().for_each(|c| { stats.record_chain(*c); });
+    stats.max_chain() == chains.into_iter().max().unwrap_or(0)
+}
+
+
 #[quickcheck]
 fn data_init(name: Name, addr: std::net::SocketAddr) -> std::io::Result<bool> {
     let rt = tokio::runtime::Runtime::new()?;
@@ -78,7 +86,7 @@ fn data_init(name: Name, addr: std::net::SocketAddr) -> std::io::Result<bool> {
     let name: String = name.into();
     let task = rt.spawn(std::future::pending());
     let data = Data::new(name.clone(), addr, task);
-    Ok(data.name() == name && data.addr() == &addr && data.score() == 0 && data.is_connected())
+    Ok(data.name() == name && data.addr() == &addr && data.score() == 0 && data.wins() == 0 && data.is_connected())
 }
 
 
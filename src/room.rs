@@ -0,0 +1,677 @@
+//! Game rooms
+
+mod selection;
+
+#[cfg(test)]
+mod tests;
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+use tokio::net;
+use tokio::sync::{RwLock, broadcast, mpsc, watch};
+
+use crate::error;
+use crate::game;
+use crate::player;
+use crate::util;
+
+use error::WrappedErr;
+
+
+/// Accept players and let them select a room
+///
+/// This function accepts connections via the given `listener`. Each connection
+/// is presented with a list of rooms from the `registry` and may join or create
+/// a room. The function only returns if an error occurs.
+///
+pub async fn run<R>(
+    listener: net::TcpListener,
+    registry: Arc<Registry<R>>,
+) -> Result<(), WrappedErr>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    use error::TryExt;
+
+    loop {
+        if let Some((stream, peer)) = listener.accept().await.or_warn("Could not accept connection") {
+            log::info!("Accepting connection from {}", peer);
+            let registry = registry.clone();
+            tokio::spawn(async move { selection::serve_connection(stream, peer, registry).await });
+        }
+    }
+}
+
+
+/// Registry of all rooms on the server
+///
+pub struct Registry<R: rand::Rng> {
+    rooms: RwLock<Vec<Arc<Room<R>>>>,
+    next_id: AtomicU32,
+    defaults: Settings,
+    max_rooms: usize,
+    updates: watch::Sender<()>,
+    shutting_down: AtomicBool,
+    abandon_timeout: Duration,
+}
+
+impl<R> Registry<R>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    /// Create a new registry
+    ///
+    /// New rooms will be created with the given `defaults` as their initial
+    /// settings. No more than `max_rooms` rooms may exist at the same time.
+    ///
+    pub fn new(defaults: Settings, max_rooms: usize) -> Self {
+        let (updates, _) = watch::channel(());
+        Self {
+            rooms: Default::default(),
+            next_id: AtomicU32::new(1),
+            defaults,
+            max_rooms,
+            updates,
+            shutting_down: AtomicBool::new(false),
+            abandon_timeout: ROOM_ABANDON_TIMEOUT,
+        }
+    }
+
+    /// Create a new room with the given name
+    ///
+    /// This function spawns a new game for the room. The room will be removed
+    /// from the registry once that game ended.
+    ///
+    pub async fn create(self: &Arc<Self>, name: &str) -> Result<Arc<Room<R>>, CreationError> {
+        use error::TryExt;
+
+        if name.is_empty()
+            || name.len() > MAX_ROOM_NAME_LEN
+            || !name.chars().all(|c| c.is_ascii_graphic())
+            || name.parse::<u32>().is_ok() {
+            return Err(CreationError::InvalidName)
+        }
+
+        let (room, game, mut bounced) = {
+            let mut rooms = self.rooms.write().await;
+            if self.shutting_down.load(Ordering::Acquire) {
+                return Err(CreationError::ShuttingDown)
+            } else if rooms.len() >= self.max_rooms {
+                return Err(CreationError::MaxRooms)
+            } else if rooms.iter().any(|r| r.name() == name) {
+                return Err(CreationError::NameTaken)
+            }
+
+            let (control_sender, control_receiver) = watch::channel(self.defaults.as_lobby_control());
+            let (phase_sender, phase) = watch::channel(game::GamePhase::default());
+            let (intake, intake_receiver) = mpsc::channel(ROOM_INTAKE_CAPACITY);
+            let (bounce, bounced) = mpsc::unbounded_channel();
+            let (reports, _) = broadcast::channel(ROOM_REPORT_CAPACITY);
+            let roster: Arc<RwLock<player::Roster>> = Default::default();
+
+            let room = Arc::new(Room {
+                id: self.next_id.fetch_add(1, Ordering::Relaxed),
+                name: name.to_string(),
+                central: RwLock::new(Central {control: control_sender.into(), settings: self.defaults.clone()}),
+                phase: phase.clone(),
+                roster: roster.clone(),
+                intake,
+                reports: reports.clone(),
+            });
+            rooms.push(room.clone());
+
+            let game = game::run(intake_receiver, bounce, control_receiver, roster, phase_sender, phase, reports);
+            (room, game, bounced)
+        };
+
+        log::info!("Created room {} ({})", room.id(), room.name());
+        tokio::spawn({
+            let registry = self.clone();
+            let id = room.id();
+            async move {
+                game.await.or_err("Game failed");
+                registry.remove(id).await;
+            }
+        });
+        tokio::spawn({
+            let registry = self.clone();
+            async move {
+                while let Some(arrival) = bounced.recv().await {
+                    tokio::spawn(selection::serve_bounced(arrival, registry.clone()));
+                }
+            }
+        });
+        self.notify();
+
+        // Make sure the room is in its lobby phase before handing it out
+        let mut phase = room.phase();
+        if phase.borrow().is_end_of_game() {
+            phase.changed().await.or_warn("Phase channel closed");
+        }
+
+        Ok(room)
+    }
+
+    /// Create a new room with the given name on behalf of a player
+    ///
+    /// In addition to what `create` does, this function makes sure the room
+    /// doesn't linger if it is abandoned. The room's game is ended if no
+    /// player is registered for a while during the lobby phase, either because
+    /// nobody registered in the first place or because all players left.
+    ///
+    pub async fn create_transient(self: &Arc<Self>, name: &str) -> Result<Arc<Room<R>>, CreationError> {
+        let room = self.create(name).await?;
+        tokio::spawn(end_if_abandoned(Arc::downgrade(&room), room.subscribe(), self.abandon_timeout));
+        Ok(room)
+    }
+
+    /// Retrieve all current rooms
+    ///
+    pub async fn rooms(&self) -> Vec<Arc<Room<R>>> {
+        self.rooms.read().await.clone()
+    }
+
+    /// Retrieve the room with the given id, if it exists
+    ///
+    pub async fn get(&self, id: u32) -> Option<Arc<Room<R>>> {
+        self.rooms.read().await.iter().find(|r| r.id() == id).cloned()
+    }
+
+    /// Retrieve the room with the given name, if it exists
+    ///
+    pub async fn find(&self, name: &str) -> Option<Arc<Room<R>>> {
+        self.rooms.read().await.iter().find(|r| r.name() == name).cloned()
+    }
+
+    /// End the games in all rooms and refuse creation of new ones
+    ///
+    pub async fn shutdown(&self) {
+        use error::TryExt;
+
+        self.shutting_down.store(true, Ordering::Release);
+        for room in self.rooms().await {
+            room.end().await.or_err("Could not end game");
+        }
+    }
+
+    /// Check whether the registry was shut down and all rooms are gone
+    ///
+    pub async fn is_shut_down(&self) -> bool {
+        self.shutting_down.load(Ordering::Acquire) && self.rooms.read().await.is_empty()
+    }
+
+    /// Subscribe to updates of the set of rooms
+    ///
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.updates.subscribe()
+    }
+
+    /// Remove the room with the given id
+    ///
+    async fn remove(&self, id: u32) {
+        self.rooms.write().await.retain(|r| r.id() != id);
+        log::info!("Removed room {}", id);
+        self.notify();
+    }
+
+    /// Notify subscribers about an update
+    ///
+    fn notify(&self) {
+        // Nobody may be listening, which is fine
+        self.updates.send(()).ok();
+    }
+}
+
+
+#[cfg(test)]
+impl<R: rand::Rng> Registry<R> {
+    /// Set the time after which rooms created on behalf of players are ended
+    /// if abandoned
+    ///
+    pub fn abandon_timeout(self, abandon_timeout: Duration) -> Self {
+        Self {abandon_timeout, ..self}
+    }
+}
+
+
+/// End a room's game once the room is abandoned
+///
+/// The room is considered abandoned if it is in the lobby phase without any
+/// registered players and no event was reported for the given `timeout`. The
+/// function returns once the room's game started or was ended.
+///
+async fn end_if_abandoned<R: rand::Rng>(
+    room: std::sync::Weak<Room<R>>,
+    mut reports: broadcast::Receiver<game::Report>,
+    timeout: Duration,
+) {
+    use broadcast::error::RecvError;
+
+    use error::TryExt;
+
+    loop {
+        match tokio::time::timeout(timeout, reports.recv()).await {
+            Ok(Ok(_)) | Ok(Err(RecvError::Lagged(_))) => continue,
+            Ok(Err(RecvError::Closed)) => return,
+            Err(_) => (),
+        }
+
+        let room = match room.upgrade() {
+            Some(room) => room,
+            None => return,
+        };
+        if !room.in_lobby() {
+            return
+        }
+        if room.player_count().await == 0 {
+            log::info!("Ending abandoned room {} ({})", room.id(), room.name());
+            room.end().await.or_err("Could not end game");
+            return
+        }
+    }
+}
+
+
+/// A single game room
+///
+pub struct Room<R: rand::Rng> {
+    id: u32,
+    name: String,
+    central: RwLock<Central>,
+    phase: watch::Receiver<game::GamePhase<R>>,
+    roster: Arc<RwLock<player::Roster>>,
+    intake: mpsc::Sender<game::Arrival>,
+    reports: broadcast::Sender<game::Report>,
+}
+
+impl<R: rand::Rng> Room<R> {
+    /// Retrieve the room's id
+    ///
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Retrieve the room's name
+    ///
+    pub fn name(&self) -> &str {
+        self.name.as_ref()
+    }
+
+    /// Retrieve the central control objects of this room
+    ///
+    pub fn central(&self) -> &RwLock<Central> {
+        &self.central
+    }
+
+    /// Retrieve a receiver for the room's game phase
+    ///
+    pub fn phase(&self) -> watch::Receiver<game::GamePhase<R>> {
+        self.phase.clone()
+    }
+
+    /// Retrieve the room's roster
+    ///
+    pub fn roster(&self) -> &Arc<RwLock<player::Roster>> {
+        &self.roster
+    }
+
+    /// Subscribe to reports of events in the room's game
+    ///
+    pub fn subscribe(&self) -> broadcast::Receiver<game::Report> {
+        self.reports.subscribe()
+    }
+
+    /// Retrieve the number of players registered in this room
+    ///
+    pub async fn player_count(&self) -> usize {
+        self.roster.read().await.len()
+    }
+
+    /// Check whether the room's game is in the lobby phase
+    ///
+    pub fn in_lobby(&self) -> bool {
+        matches!(*self.phase.borrow(), game::GamePhase::Lobby{..})
+    }
+
+    /// Check whether the room currently accepts new players
+    ///
+    pub async fn is_open(&self) -> bool {
+        self.in_lobby() && self.central.read().await.settings.accept_players
+    }
+
+    /// Hand a connection over to the room's game
+    ///
+    /// If the room does not accept new players or can't take any more arrivals
+    /// at the moment, the arrival is returned.
+    ///
+    pub async fn admit(&self, arrival: game::Arrival) -> Result<(), game::Arrival> {
+        if self.is_open().await {
+            self.intake.try_send(arrival).map_err(|e| e.into_inner())
+        } else {
+            Err(arrival)
+        }
+    }
+
+    /// Start the room's game
+    ///
+    pub async fn start(&self) -> Result<(), WrappedErr> {
+        let mut central = self.central.write().await;
+        let msg = central.settings.as_game_control();
+        central.control.send_regular(msg).await
+    }
+
+    /// End the room's game
+    ///
+    pub async fn end(&self) -> Result<(), WrappedErr> {
+        self.central.write().await.control.send_regular(game::GameControl::EndOfGame).await
+    }
+}
+
+
+/// Error which may occur during the creation of a room
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CreationError {
+    InvalidName,
+    NameTaken,
+    MaxRooms,
+    ShuttingDown,
+}
+
+impl std::error::Error for CreationError {}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName   => write!(f, "Invalid room name"),
+            Self::NameTaken     => write!(f, "Room name is already taken"),
+            Self::MaxRooms      => write!(f, "Max number of rooms reached"),
+            Self::ShuttingDown  => write!(f, "Server is shutting down"),
+        }
+    }
+}
+
+
+/// Utility struct for central objects shared between all consoles of a room
+///
+pub struct Central {
+    pub control: ControlSender,
+    pub settings: Settings,
+}
+
+impl Central {
+    /// Set and send accept player setting
+    ///
+    /// This function returns an error if `control` is not a
+    /// `ControlSender::Lobby`.
+    ///
+    pub fn accept_players(&mut self, accept: bool) -> Result<(), WrappedErr> {
+        self.settings.accept_players = accept;
+        self.send_lobby_settings()
+    }
+
+    /// Set and send max player setting
+    ///
+    /// This function returns an error if `control` is not a
+    /// `ControlSender::Lobby`.
+    ///
+    pub fn set_max_players(&mut self, max_players: u8) -> Result<(), WrappedErr> {
+        self.settings.max_players = max_players;
+        self.send_lobby_settings()
+    }
+
+    /// Set and send virus count setting
+    ///
+    /// This function returns an error if `control` is not a
+    /// `ControlSender::Regular`.
+    ///
+    pub fn set_virus_count(&mut self, virus_count: u8) -> Result<bool, WrappedErr> {
+        self.settings.virus_count = virus_count;
+        self.send_game_settings()
+    }
+
+    /// Set and send tock duration setting
+    ///
+    /// This function returns an error if `control` is not a
+    /// `ControlSender::Regular`.
+    ///
+    pub fn set_tick_duration(&mut self, duration: Duration) -> Result<bool, WrappedErr> {
+        self.settings.tick_duration = duration;
+        self.send_game_settings()
+    }
+
+    /// Set and send tournament match size setting
+    ///
+    /// A match size of `0` disables tournament mode. The setting only takes
+    /// effect if set before the game starts.
+    ///
+    pub fn set_match_size(&mut self, match_size: u8) -> Result<bool, WrappedErr> {
+        if match_size == 1 {
+            return Err(WrappedErr::new("A match needs at least two players", error::NoneError))
+        }
+        self.settings.match_size = match_size;
+        self.send_game_settings()
+    }
+
+    /// Set and send win condition setting
+    ///
+    /// This function returns an error if `control` is not a
+    /// `ControlSender::Regular`.
+    ///
+    pub fn set_win_condition(&mut self, condition: game::WinCondition) -> Result<bool, WrappedErr> {
+        self.settings.win_condition = condition;
+        self.send_game_settings()
+    }
+
+    /// Set and send field dimensions setting
+    ///
+    /// The setting takes effect at the beginning of the next round.
+    ///
+    pub fn set_dimensions(&mut self, dimensions: util::Dimensions) -> Result<bool, WrappedErr> {
+        self.settings.dimensions = dimensions;
+        self.send_game_settings()
+    }
+
+    /// Set and send palette setting
+    ///
+    /// The setting takes effect at the beginning of the next round.
+    ///
+    pub fn set_palette(&mut self, palette: util::Palette) -> Result<bool, WrappedErr> {
+        self.settings.palette = palette;
+        self.send_game_settings()
+    }
+
+    /// Set and send match length setting
+    ///
+    /// The setting takes effect at the beginning of the next round.
+    ///
+    pub fn set_match_length(&mut self, match_length: util::MatchLength) -> Result<bool, WrappedErr> {
+        self.settings.match_length = match_length;
+        self.send_game_settings()
+    }
+
+    /// Set and send diagonal matching setting
+    ///
+    /// The setting takes effect at the beginning of the next round.
+    ///
+    pub fn set_diagonal(&mut self, diagonal: bool) -> Result<bool, WrappedErr> {
+        self.settings.diagonal = diagonal;
+        self.send_game_settings()
+    }
+
+    /// Set and send visual effects setting
+    ///
+    /// The setting takes effect at the beginning of the next round.
+    ///
+    pub fn set_effects(&mut self, effects: bool) -> Result<bool, WrappedErr> {
+        self.settings.effects = effects;
+        self.send_game_settings()
+    }
+
+    /// Set and send special elements setting
+    ///
+    /// The setting takes effect at the beginning of the next round.
+    ///
+    pub fn set_specials(&mut self, specials: bool) -> Result<bool, WrappedErr> {
+        self.settings.specials = specials;
+        self.send_game_settings()
+    }
+
+    /// Send the current lobby settings
+    ///
+    /// Send the current lobby settings via the control channel. This function
+    /// returns an error if `control` is not a `ControlSender::Lobby`.
+    ///
+    pub fn send_lobby_settings(&mut self) -> Result<(), WrappedErr> {
+        self.control
+            .as_lobby_sender()
+            .ok_or_else(|| error::WrappedErr::new("Not in lobby phase", error::NoneError))?
+            .send(self.settings.as_lobby_control())
+            .map_err(|e| error::WrappedErr::new("Could not send new settings", e))
+    }
+
+    /// Send the current game settings
+    ///
+    /// Send the current game settings via the control channel. This function
+    /// returns an error if `control` is not a `ControlSender::Regular`.
+    ///
+    pub fn send_game_settings(&mut self) -> Result<bool, error::WrappedErr> {
+        if let Some (sender) = self.control.as_regular_sender() {
+            sender
+                .send(self.settings.as_game_control())
+                .map(|_| true)
+                .map_err(|e| error::WrappedErr::new("Could not send new settings", e))
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+
+/// Game settings
+///
+#[derive(Clone, Default, Debug)]
+pub struct Settings {
+    pub accept_players: bool,
+    pub max_players: u8,
+    pub virus_count: u8,
+    pub tick_duration: Duration,
+    pub match_size: u8,
+    pub win_condition: game::WinCondition,
+    pub dimensions: util::Dimensions,
+    pub palette: util::Palette,
+    pub match_length: util::MatchLength,
+    pub diagonal: bool,
+    pub specials: bool,
+    pub effects: bool,
+}
+
+impl Settings {
+    /// Create a LobbyControl message reflecting the relevant settings
+    pub fn as_lobby_control(&self) -> game::LobbyControl {
+        game::LobbyControl::Settings{
+            registration_acceptance: self.accept_players,
+            max_players: self.max_players,
+        }
+    }
+
+    /// Create a GameControl message reflecting the relevant settings
+    fn as_game_control(&self) -> game::GameControl {
+        game::GameControl::Settings{
+            dimensions: self.dimensions,
+            palette: self.palette,
+            match_length: self.match_length,
+            diagonal: self.diagonal,
+            specials: self.specials,
+            viruses: self.virus_count,
+            tick: self.tick_duration,
+            effects: self.effects,
+            match_size: self.match_size,
+            win_condition: self.win_condition,
+        }
+    }
+}
+
+
+/// Common sender for both lobby and "regular" control
+///
+pub enum ControlSender {
+    Lobby(watch::Sender<game::LobbyControl>),
+    Regular(watch::Sender<game::GameControl>),
+}
+
+impl ControlSender {
+    /// Send a regular control message, switching if necessary
+    ///
+    /// If the sender is a `Lobby`, this function will issue a game start
+    /// message and re-initialize the sender as a `Regular` with an appropriate
+    /// sender. Otherwise, the given contol message will be just sent over the
+    /// existing channel.
+    ///
+    pub async fn send_regular(
+        &mut self,
+        message: game::GameControl,
+    ) -> Result<(), error::WrappedErr> {
+
+        match self {
+            Self::Lobby(old) => {
+                let (sender, receiver) = watch::channel(message);
+                old.send(game::LobbyControl::GameStart(receiver))
+                    .map_err(|e| error::WrappedErr::new("Could not send game start message", e))?;
+                old.closed().await;
+                *self = Self::Regular(sender);
+                Ok(())
+            },
+            Self::Regular(sender) => sender
+                .send(message)
+                .map_err(|e| error::WrappedErr::new("Could not send control message", e)),
+        }
+    }
+
+    /// Retrieve a reference of the inner lobby control sender, if any
+    ///
+    pub fn as_lobby_sender(&self) -> Option<&watch::Sender<game::LobbyControl>> {
+        if let Self::Lobby(sender) = self {
+            Some(sender)
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve a reference of the inner regular game control sender, if any
+    ///
+    pub fn as_regular_sender(&self) -> Option<&watch::Sender<game::GameControl>> {
+        if let Self::Regular(sender) = self {
+            Some(sender)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<watch::Sender<game::LobbyControl>> for ControlSender {
+    fn from(sender: watch::Sender<game::LobbyControl>) -> Self {
+        Self::Lobby(sender)
+    }
+}
+
+
+/// Maximum length of a room's name
+///
+pub const MAX_ROOM_NAME_LEN: usize = 16;
+
+
+/// Time after which a room created on behalf of a player is ended if abandoned
+///
+const ROOM_ABANDON_TIMEOUT: Duration = Duration::from_secs(300);
+
+
+/// Number of connections which may be queued for admission to a room
+///
+const ROOM_INTAKE_CAPACITY: usize = 8;
+
+
+/// Number of reports which may be queued for each subscriber of a room
+///
+const ROOM_REPORT_CAPACITY: usize = 256;
@@ -0,0 +1,244 @@
+//! Room selection screen
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::net;
+
+use crate::display;
+use crate::game::{self, ConnTaskError};
+
+use super::{CreationError, Registry, Room};
+
+
+/// Serve a given connection until it joined a room
+///
+pub async fn serve_connection<R>(
+    stream: net::TcpStream,
+    peer: SocketAddr,
+    registry: Arc<Registry<R>>,
+)
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    use crate::error::TryExt;
+
+    let res = match game::Connection::from_tcp(stream) {
+        Ok(connection) => serve(connection, peer, registry, None).await,
+        Err(e) => Err(e.into()),
+    };
+    match res {
+        Err(ConnTaskError::Terminated) => log::info!("Player disconnected"),
+        e => { e.or_warn("Lost player"); },
+    }
+}
+
+
+/// Serve a connection handed back by a room until it joined a room
+///
+/// The future is boxed since rooms spawn it themselves, which would otherwise
+/// make it impossible to tell whether it is `Send`.
+///
+pub fn serve_bounced<R>(
+    (connection, peer): game::Arrival,
+    registry: Arc<Registry<R>>,
+) -> futures::future::BoxFuture<'static, ()>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    use crate::error::TryExt;
+
+    Box::pin(async move {
+        log::info!("{} was turned away by a room", peer);
+        match serve(connection, peer, registry, Some(JOIN_FAILED)).await {
+            Err(ConnTaskError::Terminated) => log::info!("Player disconnected"),
+            e => { e.or_warn("Lost player"); },
+        }
+    })
+}
+
+
+/// Connection function for the room selection
+///
+/// This function presents the player with a list of rooms. Once the player
+/// selected a room which admits the connection, the function returns. If a
+/// `message` is given, it is displayed initially.
+///
+async fn serve<R>(
+    mut connection: game::Connection<net::tcp::OwnedReadHalf, net::tcp::OwnedWriteHalf>,
+    peer: SocketAddr,
+    registry: Arc<Registry<R>>,
+    mut message: Option<&'static str>,
+) -> Result<(), ConnTaskError>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    use std::convert::TryInto;
+
+    use futures::stream::StreamExt;
+
+    let mut updates = registry.subscribe();
+
+    loop {
+        let room = {
+            let (display, input) = connection.split();
+
+            // Set up the display
+            let mut area = display.area().await?.pad_top(1);
+            let mut left = area.split_left(game::COLUMN_SPLIT);
+
+            left.place_top(display::StaticText::from(&PROMPT as &[_])).await?;
+            left = left.pad_top(1);
+            let mut name_input = left.place_top(
+                display::LineInput::new((super::MAX_ROOM_NAME_LEN as u16).try_into().unwrap())
+            ).await?;
+            let mut reply_text = left.place_center(
+                display::DynamicText::new(4u16.try_into().unwrap(), (game::COLUMN_SPLIT - 2).try_into().unwrap())
+            ).await?;
+
+            let list_rows = area.rows().saturating_sub(2).max(1);
+            let mut room_list = area.place_center(
+                display::DynamicText::new(list_rows.try_into().unwrap(), (LIST_WIDTH as u16).try_into().unwrap())
+            ).await?;
+
+            if let Some(message) = message.take() {
+                reply_text.update_single(&mut display.handle().await?, message).await?;
+            }
+            room_list.update(&mut display.handle().await?, room_lines(&registry).await).await?;
+
+            let mut refresh = tokio::time::interval(REFRESH_INTERVAL);
+
+            // Get the player to select a room
+            loop {
+                tokio::select!{
+                    res = input.next() => match res {
+                        Some(Ok(game::REDRAW_KEY)) => display.redraw(&[&name_input, &reply_text, &room_list]).await?,
+                        Some(Ok(c)) => {
+                            let choice = name_input
+                                .update(&mut display.handle().await?, c)
+                                .await?
+                                .map(ToString::to_string);
+                            if let Some(choice) = choice {
+                                match select(&registry, choice.as_ref()).await {
+                                    Ok(room) => break room,
+                                    Err(e) => reply_text.update_single(&mut display.handle().await?, e).await?,
+                                }
+                            }
+                        },
+                        Some(Err(e)) if !e.is_would_block() => return Err(e),
+                        None => return Err(ConnTaskError::Terminated),
+                        _ => (),
+                    },
+                    r = updates.changed() => {
+                        r.map_err(ConnTaskError::other)?;
+                        room_list.update(&mut display.handle().await?, room_lines(&registry).await).await?
+                    },
+                    _ = refresh.tick() => room_list
+                        .update(&mut display.handle().await?, room_lines(&registry).await)
+                        .await?,
+                }
+            }
+        };
+
+        match room.admit((connection, peer)).await {
+            Ok(()) => {
+                log::info!("{} joined room {}", peer, room.id());
+                break Ok(())
+            },
+            Err((c, _)) => {
+                connection = c;
+                message = Some(JOIN_FAILED);
+            },
+        }
+    }
+}
+
+
+/// Select a room based on the player's input
+///
+/// If the input is a number, the room with that id is selected. Otherwise, the
+/// room with the name given as input is selected, and created if necessary.
+/// Rooms created this way are ended once they are abandoned.
+///
+async fn select<R>(
+    registry: &Arc<Registry<R>>,
+    input: &str,
+) -> Result<Arc<Room<R>>, SelectionError>
+where R: rand::Rng + rand::SeedableRng + Clone + Send + Sync + fmt::Debug + 'static
+{
+    let room = if let Ok(id) = input.parse() {
+        registry.get(id).await.ok_or(SelectionError::NoSuchRoom)?
+    } else if let Some(room) = registry.find(input).await {
+        room
+    } else {
+        registry.create_transient(input).await?
+    };
+
+    if room.is_open().await {
+        Ok(room)
+    } else {
+        Err(SelectionError::Closed)
+    }
+}
+
+
+/// Generate the lines of the room list
+///
+async fn room_lines<R: rand::Rng>(registry: &Registry<R>) -> Vec<String> {
+    let mut lines = vec![format!("{:>3} {:<16} {:<10} {:>3}", "#", "Room", "Status", "Plr")];
+    for room in registry.rooms.read().await.iter() {
+        let phase = room.phase().borrow().to_string();
+        let line = format!("{:>3} {:<16} {:<10} {:>3}", room.id(), room.name(), phase, room.player_count().await);
+        lines.push(line);
+    }
+    lines
+}
+
+
+/// Reason for a failed room selection
+///
+#[derive(Copy, Clone, Debug)]
+enum SelectionError {
+    NoSuchRoom,
+    Closed,
+    Creation(CreationError),
+}
+
+impl From<CreationError> for SelectionError {
+    fn from(err: CreationError) -> Self {
+        Self::Creation(err)
+    }
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSuchRoom    => write!(f, "No such room"),
+            Self::Closed        => write!(f, "Room does not accept players"),
+            Self::Creation(e)   => fmt::Display::fmt(e, f),
+        }
+    }
+}
+
+
+/// Instructions for selecting a room
+///
+const PROMPT: [&str; 3] = [
+    "Enter the number or name of",
+    "a room to join it, or a new",
+    "name to create a room:",
+];
+
+
+/// Width of the room list
+///
+const LIST_WIDTH: usize = 35;
+
+
+/// Interval in which the room list is refreshed
+///
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+
+/// Message displayed if a room didn't take the player
+///
+const JOIN_FAILED: &str = "Could not join the room";
@@ -0,0 +1,106 @@
+//! Room tests
+
+use super::*;
+
+
+#[quickcheck]
+fn registry_create_names(names: Vec<crate::tests::ASCIIString>) -> Result<bool, Box<dyn std::error::Error>> {
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let registry = Arc::new(Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), names.len()));
+        let mut created: Vec<String> = Vec::new();
+        for name in names.into_iter().map(String::from) {
+            let res = registry.create(name.as_ref()).await.map(|r| r.name().to_string());
+            let expected = if name.len() > MAX_ROOM_NAME_LEN
+                || name.contains(' ')
+                || name.parse::<u32>().is_ok() {
+                Err(CreationError::InvalidName)
+            } else if created.contains(&name) {
+                Err(CreationError::NameTaken)
+            } else {
+                created.push(name.clone());
+                Ok(name)
+            };
+            if res != expected {
+                return Ok(false)
+            }
+        }
+
+        let rooms: Vec<_> = registry.rooms().await.iter().map(|r| r.name().to_string()).collect();
+        Ok(rooms == created)
+    })
+}
+
+
+#[quickcheck]
+fn registry_max_rooms(max_rooms: u8) -> Result<bool, Box<dyn std::error::Error>> {
+    let max_rooms = max_rooms % 16;
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let registry = Arc::new(Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), max_rooms.into()));
+        for n in 0..max_rooms {
+            registry.create(format!("room{}", n).as_ref()).await?;
+        }
+        Ok(registry.create("extra").await.err() == Some(CreationError::MaxRooms))
+    })
+}
+
+
+#[quickcheck]
+fn registry_lookup(names: Vec<crate::tests::ASCIIString>) -> Result<bool, Box<dyn std::error::Error>> {
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let registry = Arc::new(Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), names.len()));
+        for name in names.iter() {
+            if let Ok(room) = registry.create(name.as_ref()).await {
+                let by_id = registry.get(room.id()).await.map(|r| r.name().to_string());
+                let by_name = registry.find(name.as_ref()).await.map(|r| r.id());
+                if by_id.as_deref() != Some(name.as_ref()) || by_name != Some(room.id()) || !room.in_lobby() {
+                    return Ok(false)
+                }
+            }
+        }
+        Ok(true)
+    })
+}
+
+
+#[test]
+fn registry_shutdown() -> Result<(), Box<dyn std::error::Error>> {
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let registry = Arc::new(Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 4));
+        registry.create("a").await?;
+        registry.create("b").await?;
+
+        let mut updates = registry.subscribe();
+        registry.shutdown().await;
+        assert_eq!(registry.create("c").await.err(), Some(CreationError::ShuttingDown));
+        while !registry.is_shut_down().await {
+            updates.changed().await?;
+        }
+        Ok(())
+    })
+}
+
+
+#[test]
+fn registry_abandoned_room() -> Result<(), Box<dyn std::error::Error>> {
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let registry = Arc::new(
+            Registry::<rand_pcg::Pcg64Mcg>::new(Default::default(), 2).abandon_timeout(Duration::from_millis(100))
+        );
+        registry.create("main").await?;
+        let abandoned = registry.create_transient("abandoned").await?.id();
+
+        let mut updates = registry.subscribe();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while registry.get(abandoned).await.is_some() {
+                updates.changed().await?;
+            }
+            Ok::<_, watch::error::RecvError>(())
+        }).await??;
+
+        let rooms: Vec<_> = registry.rooms().await.iter().map(|r| r.name().to_string()).collect();
+        assert_eq!(rooms, ["main"]);
+        registry.create_transient("new").await?;
+        Ok(())
+    })
+}
@@ -53,3 +53,276 @@ impl Arbitrary for ASCIIString {
     }
 }
 
+
+
+#[test]
+fn settings_from_args() -> Result<(), Box<dyn std::error::Error>> {
+    let matches = super::app().get_matches_from_safe(vec![
+        "dr-w-falls",
+        "--max-players", "5",
+        "--virs", "3",
+        "--tick", "150",
+        "--match-len", "5",
+        "--no-effects",
+    ])?;
+    let settings = super::settings(&matches)?;
+    assert_eq!(settings.max_players, 5);
+    assert_eq!(settings.virus_count, 3);
+    assert_eq!(settings.tick_duration, std::time::Duration::from_millis(150));
+    assert_eq!(settings.match_length, "5".parse()?);
+    assert!(!settings.effects);
+    Ok(())
+}
+
+
+#[tokio::test(flavor = "multi_thread")]
+async fn loopback_game() -> Result<(), Box<dyn std::error::Error>> {
+    use std::time::Duration;
+
+    const PLAYERS: &[&str] = &["Alice", "Bob", "Carol"];
+
+    let settings = crate::room::Settings {
+        accept_players: true,
+        max_players: u8::MAX,
+        virus_count: 1,
+        tick_duration: Duration::from_millis(10),
+        effects: false,
+        ..Default::default()
+    };
+    let server = TestServer::start(settings.clone()).await?;
+
+    let game = async {
+        let mut gm = server.gm().await?;
+        let mut observer = server.gm().await?;
+        observer.command("subscribe").await?;
+
+        let mut clients = Vec::new();
+        for name in PLAYERS {
+            clients.push(server.player(name).await?);
+        }
+        let rooms = gm.command("rooms").await?;
+        assert_eq!(rooms, [format!("1 {} lobby {}", super::INITIAL_ROOM, PLAYERS.len())]);
+
+        gm.command("set rule rounds:1").await?;
+        gm.command("start").await?;
+        for client in clients.iter_mut() {
+            client.wait_for_text("Press any key when ready.").await?;
+            client.send(" ").await?;
+        }
+        gm.wait_for_phase("round 1").await?;
+
+        // Nobody steers, so every player will top out eventually
+        for client in clients.iter_mut() {
+            client.wait_for_text("Game over!").await?;
+        }
+        gm.wait_for_phase("standings").await?;
+
+        let mut winners = Vec::new();
+        for (client, name) in clients.iter_mut().zip(PLAYERS) {
+            client.wait_for_text("The game is over!").await?;
+            if client.vt().contains("You won the game!") {
+                winners.push(*name);
+            }
+        }
+        assert_eq!(winners.len(), 1);
+        assert!(clients.iter().all(|c| c.vt().contains(winners[0])));
+
+        gm.command("end").await?;
+        for client in clients.iter_mut() {
+            client.wait_for_text("Press any key to leave.").await?;
+            assert!(PLAYERS.iter().all(|n| client.vt().contains(n)));
+            client.send(" ").await?;
+        }
+
+        let events = observer.wait_for_line(|l| l == "event phase 1 end").await?;
+        assert!(PLAYERS.iter().all(|n| events.contains(&format!("event registered 1 {}", n))));
+        assert!(events.contains(&"event phase 1 round 1".to_string()));
+        assert!(events.contains(&format!("event round_start 1 1 {}", PLAYERS.join(" "))));
+        assert_eq!(events.iter().filter(|l| l.starts_with("event defeat 1 ")).count(), PLAYERS.len());
+        assert!(events.contains(&format!("event match_won 1 {}", winners[0])));
+
+        // The ranking lists each player with the number of viruses left, best
+        // first. Since nobody steers, viruses are only cleared by chance.
+        let ranking = events
+            .iter()
+            .find_map(|l| l.strip_prefix("event round_end 1 1 "))
+            .ok_or("No round_end event")?;
+        let ranking: Vec<_> = ranking
+            .split(' ')
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|e| match e {
+                [name, score] => Ok((name.to_string(), score.parse::<u32>()?)),
+                _ => Err(format!("Malformed ranking: {}", ranking).into()),
+            })
+            .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+        let mut names: Vec<_> = ranking.iter().map(|(n, _)| n.as_str()).collect();
+        names.sort();
+        assert_eq!(names, PLAYERS);
+        assert!(ranking.iter().all(|(_, s)| *s <= settings.virus_count.into()));
+        assert!(ranking.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(ranking[0].0, winners[0]);
+        assert!(events.contains(&format!("event round_won 1 1 {}", winners[0])));
+        Ok::<_, Box<dyn std::error::Error>>(())
+    };
+    tokio::time::timeout(Duration::from_secs(60), game).await?
+}
+
+
+/// Server listening on loopback interfaces
+///
+/// The server accepts players on an ephemeral TCP port and GM consoles on a
+/// UNIX domain socket in the temporary directory. It features a single room.
+///
+pub struct TestServer {
+    players: std::net::SocketAddr,
+    gm: std::path::PathBuf,
+}
+
+impl TestServer {
+    /// Start a new server with the given room settings
+    ///
+    pub async fn start(settings: crate::room::Settings) -> Result<Self, Box<dyn std::error::Error>> {
+        use std::sync::Arc;
+
+        use tokio::net;
+
+        let registry = Arc::new(crate::room::Registry::<rand_pcg::Pcg64Mcg>::new(settings, 8));
+        registry.create(super::INITIAL_ROOM).await?;
+
+        let player_sock = net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0)).await?;
+        let players = player_sock.local_addr()?;
+        let gm = std::env::temp_dir().join(format!("dr-w-falls-{}-{}.sock", std::process::id(), players.port()));
+        let gm_sock = net::UnixListener::bind(&gm)?;
+
+        tokio::spawn(crate::console::game_master(registry.clone(), Some(gm_sock), None));
+        tokio::spawn(crate::room::run(player_sock, registry));
+        Ok(Self {players, gm})
+    }
+
+    /// Connect a player and let it join the initial room under the given name
+    ///
+    pub async fn player(&self, name: &str) -> std::io::Result<TestClient> {
+        let (read, write) = tokio::net::TcpStream::connect(self.players).await?.into_split();
+        let mut client = TestClient {
+            term: crate::display::tests::VTReader::new(read, crate::game::DISPLAY_HEIGHT, crate::game::DISPLAY_WIDTH),
+            write,
+        };
+        client.wait_for_text(super::INITIAL_ROOM).await?;
+        client.send(&format!("{}\n", super::INITIAL_ROOM)).await?;
+        client.wait_for_text("Please enter your name:").await?;
+        client.send(&format!("{}\n", name)).await?;
+        client.wait_for_text("T: theme").await?;
+        Ok(client)
+    }
+
+    /// Connect a GM console
+    ///
+    pub async fn gm(&self) -> std::io::Result<TestGM> {
+        use tokio::io::AsyncBufReadExt;
+
+        let (read, write) = tokio::net::UnixStream::connect(&self.gm).await?.into_split();
+        Ok(TestGM {lines: tokio::io::BufReader::new(read).lines(), write})
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.gm).ok();
+    }
+}
+
+
+/// Scripted player connected to a [TestServer]
+///
+pub struct TestClient {
+    term: crate::display::tests::VTReader<tokio::net::tcp::OwnedReadHalf>,
+    write: tokio::net::tcp::OwnedWriteHalf,
+}
+
+impl TestClient {
+    /// Send the given input
+    ///
+    pub async fn send(&mut self, input: &str) -> std::io::Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        self.write.write_all(input.as_bytes()).await
+    }
+
+    /// Wait until the given text is displayed
+    ///
+    pub async fn wait_for_text(&mut self, text: &str) -> std::io::Result<()> {
+        self.term.wait_for(|vt| vt.contains(text)).await
+    }
+
+    /// Retrieve the player's terminal
+    ///
+    pub fn vt(&self) -> &crate::display::tests::VT {
+        self.term.vt()
+    }
+}
+
+
+/// GM console connected to a [TestServer]
+///
+pub struct TestGM {
+    lines: tokio::io::Lines<tokio::io::BufReader<tokio::net::unix::OwnedReadHalf>>,
+    write: tokio::net::unix::OwnedWriteHalf,
+}
+
+impl TestGM {
+    /// Issue a command, returning the lines of output preceding the "OK"
+    ///
+    /// If the command fails, an error carrying the error message is returned.
+    /// As the message is not distinguishable from regular output, an unknown
+    /// command is issued after the actual one. Its reply marks the end of the
+    /// actual command's reply.
+    ///
+    pub async fn command(&mut self, command: &str) -> std::io::Result<Vec<String>> {
+        use std::io::{Error, ErrorKind};
+
+        use tokio::io::AsyncWriteExt;
+
+        const SENTINEL: &str = "sentinel";
+        const SENTINEL_REPLY: &str = "No such command, see 'help' for a list";
+
+        self.write.write_all(format!("{}\n{}\n", command, SENTINEL).as_bytes()).await?;
+
+        let mut res = Vec::new();
+        loop {
+            let line = self.lines.next_line().await?.ok_or(ErrorKind::UnexpectedEof)?;
+            if line == SENTINEL_REPLY && !res.is_empty() {
+                break
+            }
+            res.push(line);
+        }
+        match res.pop() {
+            Some(line) if line == "OK"  => Ok(res),
+            line                        => Err(Error::other(line.unwrap_or_default())),
+        }
+    }
+
+    /// Read lines until one matches the given predicate, returning all of them
+    ///
+    pub async fn wait_for_line(&mut self, predicate: impl Fn(&str) -> bool) -> std::io::Result<Vec<String>> {
+        let mut res = Vec::new();
+        loop {
+            let line = self.lines.next_line().await?.ok_or(std::io::ErrorKind::UnexpectedEof)?;
+            let done = predicate(&line);
+            res.push(line);
+            if done {
+                break Ok(res)
+            }
+        }
+    }
+
+    /// Wait until the initial room reached the given phase
+    ///
+    pub async fn wait_for_phase(&mut self, phase: &str) -> std::io::Result<()> {
+        let expected = format!(" {} {} ", super::INITIAL_ROOM, phase);
+        while !self.command("rooms").await?.iter().any(|r| r.contains(&expected)) {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@
 use std::convert::{TryFrom, TryInto};
 
 use rand::Rng;
-use rand::distributions::{Distribution, Standard as StandardDist};
+use rand::distributions::Distribution;
 
 #[cfg(test)]
 use quickcheck::{Arbitrary, Gen};
@@ -13,8 +13,171 @@ use quickcheck::{Arbitrary, Gen};
 mod tests;
 
 
-pub const FIELD_WIDTH: u8 = 8;
-pub const FIELD_HEIGHT: u8 = 16;
+/// Minimum width of a field
+///
+pub const MIN_FIELD_WIDTH: u8 = 4;
+
+/// Maximum width of a field
+///
+pub const MAX_FIELD_WIDTH: u8 = 10;
+
+/// Minimum height of a field
+///
+pub const MIN_FIELD_HEIGHT: u8 = 8;
+
+/// Maximum height of a field
+///
+pub const MAX_FIELD_HEIGHT: u8 = 20;
+
+/// Width of a field of default `Dimensions`
+///
+const DEFAULT_FIELD_WIDTH: u8 = 8;
+
+/// Height of a field of default `Dimensions`
+///
+const DEFAULT_FIELD_HEIGHT: u8 = 16;
+
+
+/// Dimensions of a field
+///
+/// Instances of this type describe the size of a field chosen at runtime. The
+/// size is always within the limits imposed by `MIN_FIELD_WIDTH`,
+/// `MAX_FIELD_WIDTH`, `MIN_FIELD_HEIGHT` and `MAX_FIELD_HEIGHT`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Dimensions {
+    width: u8,
+    height: u8,
+}
+
+impl Dimensions {
+    /// Dimensions of the biggest possible field
+    ///
+    pub const MAXIMUM: Self = Self {width: MAX_FIELD_WIDTH, height: MAX_FIELD_HEIGHT};
+
+    /// Create new dimensions with the given width and height
+    ///
+    /// This function returns `None` if the given size is outside the limits.
+    ///
+    pub fn new(width: u8, height: u8) -> Option<Self> {
+        if (MIN_FIELD_WIDTH..=MAX_FIELD_WIDTH).contains(&width)
+            && (MIN_FIELD_HEIGHT..=MAX_FIELD_HEIGHT).contains(&height) {
+            Some(Self {width, height})
+        } else {
+            None
+        }
+    }
+
+    /// Retrieve the width, i.e. the number of columns
+    ///
+    pub fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Retrieve the height, i.e. the number of rows
+    ///
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Index of the bottom row
+    ///
+    pub fn bottom_row(&self) -> RowIndex {
+        RowIndex {data: self.height - 1}
+    }
+
+    /// Index of the rightmost column
+    ///
+    pub fn rightmost_column(&self) -> ColumnIndex {
+        ColumnIndex {data: self.width - 1}
+    }
+
+    /// Range including all rows
+    ///
+    pub fn rows(&self) -> RangeInclusive<RowIndex> {
+        RangeInclusive::new(RowIndex::TOP_ROW, self.bottom_row())
+    }
+
+    /// Range including all columns
+    ///
+    pub fn columns(&self) -> RangeInclusive<ColumnIndex> {
+        RangeInclusive::new(ColumnIndex::LEFTMOST_COLUMN, self.rightmost_column())
+    }
+
+    /// Create an iterator over all positions in the given row
+    ///
+    pub fn complete_row(&self, row: RowIndex) -> impl Iterator<Item = Position> + Clone {
+        self.columns().map(move |c| (row, c))
+    }
+
+    /// Create an iterator over all positions, row by row
+    ///
+    #[cfg(test)]
+    pub fn positions(&self) -> impl Iterator<Item = Position> + Clone {
+        let dimensions = *self;
+        self.rows().flat_map(move |r| dimensions.complete_row(r))
+    }
+
+    /// Check whether the given position lies within a field of this size
+    ///
+    pub fn contains(&self, (row, col): Position) -> bool {
+        row.data < self.height && col.data < self.width
+    }
+}
+
+impl Default for Dimensions {
+    fn default() -> Self {
+        Self {width: DEFAULT_FIELD_WIDTH, height: DEFAULT_FIELD_HEIGHT}
+    }
+}
+
+impl std::fmt::Display for Dimensions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl std::str::FromStr for Dimensions {
+    type Err = DimensionsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, 'x');
+        let width = parts.next().and_then(|w| w.parse().ok()).ok_or(DimensionsError)?;
+        let height = parts.next().and_then(|h| h.parse().ok()).ok_or(DimensionsError)?;
+        Self::new(width, height).ok_or(DimensionsError)
+    }
+}
+
+#[cfg(test)]
+impl Arbitrary for Dimensions {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            width: MIN_FIELD_WIDTH + u8::arbitrary(g) % (MAX_FIELD_WIDTH - MIN_FIELD_WIDTH + 1),
+            height: MIN_FIELD_HEIGHT + u8::arbitrary(g) % (MAX_FIELD_HEIGHT - MIN_FIELD_HEIGHT + 1),
+        }
+    }
+}
+
+
+/// Error indicating invalid field dimensions
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DimensionsError;
+
+impl std::error::Error for DimensionsError {}
+
+impl std::fmt::Display for DimensionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected <width>x<height> between {}x{} and {}x{}",
+            MIN_FIELD_WIDTH,
+            MIN_FIELD_HEIGHT,
+            MAX_FIELD_WIDTH,
+            MAX_FIELD_HEIGHT,
+        )
+    }
+}
 
 
 /// Convenience type for positions
@@ -44,7 +207,7 @@ impl std::ops::Add<Direction> for Option<Position> {
 
 /// Description of a direction
 ///
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Direction {
     Left,
     Right,
@@ -87,7 +250,8 @@ impl Arbitrary for Direction {
 /// Row index type
 ///
 /// Instances of this type serve as an index for a row in a field. It represents
-/// values from `0` (for the top row) to `15` (for the bottom row).
+/// values from `0` (for the top row) to `MAX_FIELD_HEIGHT - 1`. Whether a given
+/// row is part of a field depends on the field's `Dimensions`.
 ///
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct RowIndex {
@@ -98,10 +262,6 @@ impl RowIndex {
     /// Index of the top row
     ///
     pub const TOP_ROW: Self = Self {data: 0};
-
-    /// Index of the bottom row
-    ///
-    pub const BOTTOM_ROW: Self = Self {data: FIELD_HEIGHT - 1};
 }
 
 impl From<RowIndex> for usize {
@@ -113,14 +273,14 @@ impl From<RowIndex> for usize {
 impl TryFrom<usize> for RowIndex {
     type Error = usize;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
-        value.try_into().ok().filter(|i| *i < FIELD_HEIGHT).map(|data| Self {data}).ok_or(value)
+        value.try_into().ok().filter(|i| *i < MAX_FIELD_HEIGHT).map(|data| Self {data}).ok_or(value)
     }
 }
 
 #[cfg(test)]
 impl Arbitrary for RowIndex {
     fn arbitrary(g: &mut Gen) -> Self {
-        Self {data: u8::arbitrary(g) % FIELD_HEIGHT}
+        Self {data: u8::arbitrary(g) % DEFAULT_FIELD_HEIGHT}
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
@@ -129,16 +289,12 @@ impl Arbitrary for RowIndex {
 }
 
 
-/// Range including all rows
-///
-pub const ROWS: RangeInclusive<RowIndex> = RangeInclusive::new(RowIndex::TOP_ROW, RowIndex::BOTTOM_ROW);
-
-
 /// Column index type
 ///
 /// Instances of this type serve as an index for a column in a field. It
-/// represents values from `0` (for the leftmost column) to `7` (for the
-/// rightmost column).
+/// represents values from `0` (for the leftmost column) to
+/// `MAX_FIELD_WIDTH - 1`. Whether a given column is part of a field depends on
+/// the field's `Dimensions`.
 ///
 #[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub struct ColumnIndex {
@@ -149,10 +305,6 @@ impl ColumnIndex {
     /// Index of the leftmost column
     ///
     pub const LEFTMOST_COLUMN: Self = Self {data: 0};
-
-    /// Index of the rightmost column
-    ///
-    pub const RIGHTMOST_COLUMN: Self = Self {data: FIELD_WIDTH - 1};
 }
 
 impl From<ColumnIndex> for usize {
@@ -164,14 +316,14 @@ impl From<ColumnIndex> for usize {
 impl TryFrom<usize> for ColumnIndex {
     type Error = usize;
     fn try_from(value: usize) -> Result<Self, Self::Error> {
-        value.try_into().ok().filter(|i| *i < FIELD_WIDTH).map(|data| Self {data}).ok_or(value)
+        value.try_into().ok().filter(|i| *i < MAX_FIELD_WIDTH).map(|data| Self {data}).ok_or(value)
     }
 }
 
 #[cfg(test)]
 impl Arbitrary for ColumnIndex {
     fn arbitrary(g: &mut Gen) -> Self {
-        Self {data: u8::arbitrary(g) % FIELD_WIDTH}
+        Self {data: u8::arbitrary(g) % DEFAULT_FIELD_WIDTH}
     }
 
     fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
@@ -180,14 +332,6 @@ impl Arbitrary for ColumnIndex {
 }
 
 
-/// Range including all columns
-///
-pub const COLUMNS: RangeInclusive<ColumnIndex> = RangeInclusive::new(
-    ColumnIndex::LEFTMOST_COLUMN,
-    ColumnIndex::RIGHTMOST_COLUMN
-);
-
-
 /// Project-specific partial predefinition of `std::iter::Step`
 ///
 pub trait Step: Sized {
@@ -318,13 +462,6 @@ impl<I> Arbitrary for RangeInclusive<I>
 }
 
 
-/// Create an iterator over all positions in the given row
-///
-pub fn complete_row(row: RowIndex) -> impl Iterator<Item = Position> + Clone {
-    COLUMNS.map(move |c| (row, c))
-}
-
-
 /// Colour of viruses and capsule elements
 ///
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -332,39 +469,246 @@ pub enum Colour {
     Red,
     Yellow,
     Blue,
+    Green,
+    Magenta,
+    Cyan,
 }
 
 impl Colour {
-    /// Cycle through the colours
-    ///
-    /// This function chooses anothe colour based on `dir`. Each colour will be
-    /// returned only once for three "rotatios" with a given `dir`.
-    pub fn rotate(self, dir: bool) -> Self {
-        match (self, dir) {
-            (Self::Red,    false) => Self::Yellow,
-            (Self::Red,    true ) => Self::Blue,
-            (Self::Yellow, false) => Self::Blue,
-            (Self::Yellow, true ) => Self::Red,
-            (Self::Blue,   false) => Self::Red,
-            (Self::Blue,   true ) => Self::Yellow,
+    /// All colours, in the order in which they are included in palettes
+    ///
+    pub const ALL: [Self; MAX_COLOURS as usize] =
+        [Self::Red, Self::Yellow, Self::Blue, Self::Green, Self::Magenta, Self::Cyan];
+}
+
+#[cfg(test)]
+impl Arbitrary for Colour {
+    fn arbitrary(g: &mut Gen) -> Self {
+        *g.choose(&Self::ALL).unwrap()
+    }
+}
+
+
+/// Minimum number of colours in a palette
+///
+pub const MIN_COLOURS: u8 = 2;
+
+/// Maximum number of colours in a palette
+///
+pub const MAX_COLOURS: u8 = 6;
+
+/// Default number of colours in a palette
+///
+const DEFAULT_COLOURS: u8 = 3;
+
+
+/// Set of colours in use for a round
+///
+/// A palette consists of the first few colours listed in `Colour::ALL`. Random
+/// colours are drawn from a palette by using it as a `Distribution`.
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Palette {
+    size: u8,
+}
+
+impl Palette {
+    /// Palette containing all colours
+    ///
+    #[cfg(test)]
+    pub const MAXIMUM: Self = Self {size: MAX_COLOURS};
+
+    /// Create a new palette with the given number of colours
+    ///
+    /// This function returns `None` if the given number is outside the limits.
+    ///
+    pub fn new(size: u8) -> Option<Self> {
+        if (MIN_COLOURS..=MAX_COLOURS).contains(&size) {
+            Some(Self {size})
+        } else {
+            None
         }
     }
+
+    /// Retrieve the number of colours in this palette
+    ///
+    pub fn len(&self) -> u8 {
+        self.size
+    }
+
+    /// Check whether the given colour is part of this palette
+    ///
+    #[cfg(test)]
+    pub fn contains(&self, colour: Colour) -> bool {
+        (colour as u8) < self.size
+    }
+
+    /// Cycle through the colours of this palette
+    ///
+    /// This function chooses another colour based on `dir`. Each colour in the
+    /// palette will be returned only once for `len()` "rotations" with a given
+    /// `dir`. The `colour` is expected to be part of this palette.
+    ///
+    pub fn rotate(&self, colour: Colour, dir: bool) -> Colour {
+        let pos = colour as u8 % self.size;
+        let pos = if dir {
+            pos.checked_sub(1).unwrap_or(self.size - 1)
+        } else {
+            (pos + 1) % self.size
+        };
+        Colour::ALL[pos as usize]
+    }
 }
 
-impl Distribution<Colour> for StandardDist {
+impl Default for Palette {
+    fn default() -> Self {
+        Self {size: DEFAULT_COLOURS}
+    }
+}
+
+impl Distribution<Colour> for Palette {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Colour {
-        match rng.gen_range(0u8..3u8) {
-            0 => Colour::Red,
-            1 => Colour::Yellow,
-            _ => Colour::Blue,
+        Colour::ALL[rng.gen_range(0..self.size) as usize]
+    }
+}
+
+impl std::fmt::Display for Palette {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.size.fmt(f)
+    }
+}
+
+impl std::str::FromStr for Palette {
+    type Err = PaletteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().ok().and_then(Self::new).ok_or(PaletteError)
+    }
+}
+
+#[cfg(test)]
+impl Arbitrary for Palette {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {size: MIN_COLOURS + u8::arbitrary(g) % (MAX_COLOURS - MIN_COLOURS + 1)}
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        Box::new((MIN_COLOURS..self.size).map(|size| Self {size}))
+    }
+}
+
+
+/// Error indicating an invalid number of colours
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct PaletteError;
+
+impl std::error::Error for PaletteError {}
+
+impl std::fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expected number of colours between {} and {}", MIN_COLOURS, MAX_COLOURS)
+    }
+}
+
+
+/// Minimum number of tiles in a row eliminating them
+///
+pub const MIN_MATCH_LEN: u8 = 3;
+
+/// Maximum number of tiles in a row eliminating them
+///
+pub const MAX_MATCH_LEN: u8 = 5;
+
+/// Default number of tiles in a row eliminating them
+///
+const DEFAULT_MATCH_LEN: u8 = 4;
+
+
+/// Minimum length of a row of tiles of the same colour eliminating them
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MatchLength {
+    len: u8,
+}
+
+impl MatchLength {
+    /// Create a new match length
+    ///
+    /// This function returns `None` if the given length is outside the limits.
+    ///
+    pub fn new(len: u8) -> Option<Self> {
+        if (MIN_MATCH_LEN..=MAX_MATCH_LEN).contains(&len) {
+            Some(Self {len})
+        } else {
+            None
         }
     }
 }
 
+impl Default for MatchLength {
+    fn default() -> Self {
+        Self {len: DEFAULT_MATCH_LEN}
+    }
+}
+
+impl From<MatchLength> for usize {
+    fn from(len: MatchLength) -> Self {
+        len.len.into()
+    }
+}
+
+impl std::fmt::Display for MatchLength {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.len.fmt(f)
+    }
+}
+
+impl std::str::FromStr for MatchLength {
+    type Err = MatchLengthError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().ok().and_then(Self::new).ok_or(MatchLengthError)
+    }
+}
+
 #[cfg(test)]
-impl Arbitrary for Colour {
+impl Arbitrary for MatchLength {
     fn arbitrary(g: &mut Gen) -> Self {
-        *g.choose(&[Self::Red, Self::Yellow, Self::Blue]).unwrap()
+        Self {len: MIN_MATCH_LEN + u8::arbitrary(g) % (MAX_MATCH_LEN - MIN_MATCH_LEN + 1)}
+    }
+}
+
+
+/// Error indicating an invalid match length
+///
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MatchLengthError;
+
+impl std::error::Error for MatchLengthError {}
+
+impl std::fmt::Display for MatchLengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Expected match length between {} and {}", MIN_MATCH_LEN, MAX_MATCH_LEN)
+    }
+}
+
+
+/// Rule deciding which configurations of tiles are eliminated
+///
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MatchRule {
+    /// Minimum number of tiles of the same colour in a row
+    pub length: MatchLength,
+    /// Whether diagonal rows are considered in addition to horizontal and
+    /// vertical ones
+    pub diagonal: bool,
+}
+
+#[cfg(test)]
+impl Arbitrary for MatchRule {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {length: Arbitrary::arbitrary(g), diagonal: Arbitrary::arbitrary(g)}
     }
 }
 
@@ -383,6 +727,12 @@ pub trait PotentiallyColoured {
     {
         self.colour()
     }
+
+    /// Check whether this item matches any colour
+    ///
+    fn is_wildcard(&self) -> bool {
+        false
+    }
 }
 
 impl PotentiallyColoured for Option<Colour> {
@@ -42,19 +42,27 @@ fn ccw_rotation(dir: Direction) -> bool {
 }
 
 
-#[test]
-fn rows_len() {
-    assert_eq!(ROWS.len(), FIELD_HEIGHT as usize);
-    assert_eq!(ROWS.count(), FIELD_HEIGHT as usize);
-    assert_eq!(ROWS.rfold(0, |c, _| c + 1), FIELD_HEIGHT as usize);
+#[quickcheck]
+fn rows_len(dimensions: Dimensions) -> bool {
+    let height = dimensions.height() as usize;
+    dimensions.rows().len() == height &&
+        dimensions.rows().count() == height &&
+        dimensions.rows().rfold(0, |c, _| c + 1) == height
+}
+
+
+#[quickcheck]
+fn columns_len(dimensions: Dimensions) -> bool {
+    let width = dimensions.width() as usize;
+    dimensions.columns().len() == width &&
+        dimensions.columns().count() == width &&
+        dimensions.columns().rfold(0, |c, _| c + 1) == width
 }
 
 
-#[test]
-fn columns_len() {
-    assert_eq!(COLUMNS.len(), FIELD_WIDTH as usize);
-    assert_eq!(COLUMNS.count(), FIELD_WIDTH as usize);
-    assert_eq!(COLUMNS.rfold(0, |c, _| c + 1), FIELD_WIDTH as usize);
+#[quickcheck]
+fn dimensions_parse(dimensions: Dimensions) -> bool {
+    dimensions.to_string().parse::<Dimensions>().ok() == Some(dimensions)
 }
 
 
@@ -85,17 +93,38 @@ fn rows_backward(first: RowIndex, last: RowIndex) -> TestResult {
 
 
 #[quickcheck]
-fn colour_rotation(colour: Colour, dir: bool) -> bool {
-    let c1 = colour.rotate(dir);
-    let c2 = c1.rotate(dir);
-    let c3 = c2.rotate(dir);
-    c1 != colour && c2 != colour && c3 == colour
+fn colour_rotation(palette: Palette, colour: Colour, dir: bool) -> TestResult {
+    if !palette.contains(colour) {
+        return TestResult::discard()
+    }
+
+    let rotations: Vec<_> = std::iter::successors(Some(colour), |c| Some(palette.rotate(*c, dir)))
+        .skip(1)
+        .take(palette.len().into())
+        .collect();
+    let mut distinct = rotations.clone();
+    distinct.sort_by_key(|c| *c as u8);
+    distinct.dedup();
+    TestResult::from_bool(
+        rotations.last() == Some(&colour) &&
+            distinct.len() == rotations.len() &&
+            rotations.iter().all(|c| palette.contains(*c))
+    )
 }
 
 #[quickcheck]
 fn colour_dirot(colour: Colour) -> bool {
-    let c1 = colour.rotate(true);
-    let c2 = colour.rotate(false);
+    let palette = Palette::MAXIMUM;
+    let c1 = palette.rotate(colour, true);
+    let c2 = palette.rotate(colour, false);
     colour != c1 && colour != c2 && c1 != c2
 }
 
+#[quickcheck]
+fn palette_sample(palette: Palette, seed: u64) -> bool {
+    use rand::SeedableRng;
+
+    let mut rng = rand_pcg::Pcg64Mcg::seed_from_u64(seed);
+    (0..32).all(|_| palette.contains(rng.sample(palette)))
+}
+